@@ -1,4 +1,5 @@
 #include <stdlib.h>
+#include <string.h>
 #include <unistd.h>
 #include <stdio.h>
 #include <time.h>
@@ -9,7 +10,9 @@
 #include "controller.h"
 
 void controller_error(const char* msg);
-void controller_enter_statistics_loop(struct ControllerData* controller_data, double min, double max, long count, double avg, double variance, double std_deviation);
+void controller_enter_statistics_loop(struct ControllerData* controller_data, double min, double max, long count, double avg, double variance, double std_deviation,
+                                      long* floor_counts, double* floor_avgs, char* extra_statistics_text);
+double controller_statistics_round(double value);
 int wait_for_stdin();
 unsigned char controller_read_byte();
 unsigned short controller_read_short();
@@ -26,15 +29,31 @@ void controller_init_sdl(struct ControllerData* controller_data)
 
 void controller_read_init_message(struct ControllerData* controller_data)
 {
-  controller_data->scene_file_name = controller_read_string();
+  controller_data->floors_count = controller_read_byte();
+  controller_data->scene_file_names = malloc(sizeof(char*) * controller_data->floors_count);
+  for (int i = 0; i < controller_data->floors_count; i++)
+    controller_data->scene_file_names[i] = controller_read_string();
+  if (controller_data->floor >= controller_data->floors_count)
+    controller_error("Scene has no such floor.");
   controller_data->scene_scale = controller_read_double();
   controller_data->sdl_data.density_map_min_threshold = controller_read_double();
   controller_data->sdl_data.density_map_max_threshold = controller_read_double();
+
+  unsigned char forces_count = controller_read_byte();
+  fprintf(stderr, "Active forces:");
+  for (int i = 0; i < forces_count; i++) {
+    char* force_name = controller_read_string();
+    double weight = controller_read_double();
+    fprintf(stderr, " %s (weight %.2f)", force_name, weight);
+    free(force_name);
+  }
+  fprintf(stderr, "\n");
+  controller_data->paced_by_core = controller_read_byte();
 }
 
 void controller_load_textures(struct ControllerData* controller_data)
 {
-  sdl_set_svg_background(&controller_data->sdl_data, controller_data->scene_file_name);
+  sdl_set_svg_background(&controller_data->sdl_data, controller_data->scene_file_names[controller_data->floor]);
   sdl_load_person_svg(&controller_data->sdl_data, controller_data->person_file_name, controller_data->person_file_scale, controller_data->scene_scale);
 }
 
@@ -50,6 +69,9 @@ void controller_main_loop(struct ControllerData* controller_data)
   unsigned char statistics_mode = 0;
   double min, max, avg, variance, std_deviation;
   long count;
+  long* floor_counts = NULL;
+  double* floor_avgs = NULL;
+  char* extra_statistics_text = NULL;
 
   while (!feof(stdin) && !statistics_mode) {
     wait_for_stdin();
@@ -69,22 +91,29 @@ void controller_main_loop(struct ControllerData* controller_data)
           short person_x = controller_read_short();
           short person_y = controller_read_short();
           double heading = controller_read_double();
-          sdl_draw_person(&controller_data->sdl_data, person_x, person_y, heading);
+          double radius = controller_read_double();
+          unsigned char floor = controller_read_byte();
+          if (floor == controller_data->floor)
+            sdl_draw_person(&controller_data->sdl_data, person_x, person_y, heading, radius);
         }
       }
       break;
     case DENSITY_MAP_TYPE:
       {
+        unsigned char floor = controller_read_byte();
         long densities_count = controller_read_long();
-        sdl_clear_density(&controller_data->sdl_data);
+        if (floor == controller_data->floor)
+          sdl_clear_density(&controller_data->sdl_data);
         for (long i = 0; i < densities_count; i++) {
           int x = controller_read_short();
           int y = controller_read_short();
           double density = controller_read_double();
-          sdl_set_density(&controller_data->sdl_data, x, y, density);
+          if (floor == controller_data->floor)
+            sdl_set_density(&controller_data->sdl_data, x, y, density);
         }
+        if (floor == controller_data->floor)
+          sdl_draw_density(&controller_data->sdl_data);
       }
-      sdl_draw_density(&controller_data->sdl_data);
       break;
     case STATISTICS_TYPE:
       {
@@ -95,6 +124,74 @@ void controller_main_loop(struct ControllerData* controller_data)
         avg = controller_read_double();
         variance = controller_read_double();
         std_deviation = controller_read_double();
+        unsigned char floors_count = controller_read_byte();
+        floor_counts = malloc(sizeof(long) * floors_count);
+        floor_avgs = malloc(sizeof(double) * floors_count);
+        for (int i = 0; i < floors_count; i++) {
+          floor_counts[i] = controller_read_long();
+          floor_avgs[i] = controller_read_double();
+        }
+        unsigned char services_count = controller_read_byte();
+        extra_statistics_text = malloc(sizeof(char) * (1 + 300 * services_count));
+        int length = 0;
+        extra_statistics_text[0] = '\0';
+        for (int i = 0; i < services_count; i++) {
+          char* service_name = controller_read_string();
+          long waiting_count = controller_read_long();
+          double waiting_avg = controller_read_double();
+          double waiting_max = controller_read_double();
+          double queue_avg = controller_read_double();
+          double queue_max = controller_read_double();
+          length += snprintf(extra_statistics_text + length, 300, "%.100s: served=%ld, avg wait=%.2f, max wait=%.2f, avg queue=%.2f, max queue=%.0f\n",
+                             service_name, waiting_count, controller_statistics_round(waiting_avg), controller_statistics_round(waiting_max),
+                             controller_statistics_round(queue_avg), queue_max);
+          free(service_name);
+        }
+        unsigned char hazard_enabled = controller_read_byte();
+        if (hazard_enabled) {
+          long exposure_count = controller_read_long();
+          double exposure_avg = controller_read_double();
+          double exposure_max = controller_read_double();
+          extra_statistics_text = realloc(extra_statistics_text, length + 200);
+          length += snprintf(extra_statistics_text + length, 200, "Hazard exposure: count=%ld, avg=%.2f, max=%.2f\n",
+                             exposure_count, controller_statistics_round(exposure_avg), controller_statistics_round(exposure_max));
+        }
+        unsigned char spawn_areas_count = controller_read_byte();
+        extra_statistics_text = realloc(extra_statistics_text, length + 1 + 200 * spawn_areas_count);
+        for (int i = 0; i < spawn_areas_count; i++) {
+          unsigned char spawn_id = controller_read_byte();
+          long spawned_count = controller_read_long();
+          double waiting_avg = controller_read_double();
+          double waiting_max = controller_read_double();
+          double backlog_avg = controller_read_double();
+          double backlog_max = controller_read_double();
+          length += snprintf(extra_statistics_text + length, 200, "Spawn area %d: spawned=%ld, avg wait=%.2f, max wait=%.2f, avg backlog=%.2f, max backlog=%.0f\n",
+                             spawn_id, spawned_count, controller_statistics_round(waiting_avg), controller_statistics_round(waiting_max),
+                             controller_statistics_round(backlog_avg), backlog_max);
+        }
+        unsigned char stuck_enabled = controller_read_byte();
+        if (stuck_enabled) {
+          long stuck_flagged = controller_read_long();
+          long stuck_removed = controller_read_long();
+          extra_statistics_text = realloc(extra_statistics_text, length + 100);
+          length += snprintf(extra_statistics_text + length, 100, "Stuck people: flagged=%ld, removed=%ld\n",
+                             stuck_flagged, stuck_removed);
+        }
+        char* termination_reason = controller_read_string();
+        extra_statistics_text = realloc(extra_statistics_text, length + 100);
+        length += snprintf(extra_statistics_text + length, 100, "Stopped by: %.50s\n", termination_reason);
+        free(termination_reason);
+        const char* per_person_names[] = {"Distance walked, m", "Mean speed, m/s"};
+        extra_statistics_text = realloc(extra_statistics_text, length + 300);
+        for (int i = 0; i < 2; i++) {
+          double item_min = controller_read_double();
+          double item_max = controller_read_double();
+          double item_avg = controller_read_double();
+          double item_std = controller_read_double();
+          length += snprintf(extra_statistics_text + length, 150, "%s: min=%.2f, max=%.2f, avg=%.2f, std=%.2f\n",
+                             per_person_names[i], controller_statistics_round(item_min), controller_statistics_round(item_max),
+                             controller_statistics_round(item_avg), controller_statistics_round(item_std));
+        }
       }
       break;
     }
@@ -102,7 +199,7 @@ void controller_main_loop(struct ControllerData* controller_data)
     gettimeofday(&current_time, NULL);
     current_time_double = current_time.tv_sec + current_time.tv_usec / 1000000.0;
     double delay = data_time - (current_time_double - controller_data->start_time);
-    if (delay > 0.0) {
+    if (!controller_data->paced_by_core && delay > 0.0) {
 #ifdef DEBUG
       fprintf(stderr, "Ahead of time on %f s, waiting\n", delay);
 #endif
@@ -115,20 +212,32 @@ void controller_main_loop(struct ControllerData* controller_data)
     sdl_update(&controller_data->sdl_data);
   }
 
-  controller_enter_statistics_loop(controller_data, min, max, count, avg, variance, std_deviation);
+  controller_enter_statistics_loop(controller_data, min, max, count, avg, variance, std_deviation, floor_counts, floor_avgs, extra_statistics_text);
+  free(floor_counts);
+  free(floor_avgs);
+  free(extra_statistics_text);
 }
 
 double controller_statistics_round(double value) {
   return round(value * 100) / 100.0;
 }
 
-void controller_enter_statistics_loop(struct ControllerData* controller_data, double min, double max, long count, double avg, double variance, double std_deviation) {
-  char *statistics_text = malloc(sizeof(char) * 1000);
-  sprintf(statistics_text,
-    "Simulation done!\nTravel time statistics: min=%.2f, max=%.2f, count=%d, avg=%.2f, variance=%.2f, std_deviation=%.2f\nPress spacebar to exit.",
+void controller_enter_statistics_loop(struct ControllerData* controller_data, double min, double max, long count, double avg, double variance, double std_deviation,
+                                      long* floor_counts, double* floor_avgs, char* extra_statistics_text) {
+  char *statistics_text = malloc(sizeof(char) * (1000 + 100 * controller_data->floors_count + (extra_statistics_text ? strlen(extra_statistics_text) : 0)));
+  int length = sprintf(statistics_text,
+    "Simulation done!\nTravel time statistics: min=%.2f, max=%.2f, count=%d, avg=%.2f, variance=%.2f, std_deviation=%.2f\n",
     controller_statistics_round(min), controller_statistics_round(max), count,
     controller_statistics_round(avg), controller_statistics_round(variance), controller_statistics_round(std_deviation)
   );
+  if (controller_data->floors_count > 1 && floor_counts != NULL) {
+    for (int i = 0; i < controller_data->floors_count; i++)
+      length += sprintf(statistics_text + length, "Floor %d: count=%ld, avg=%.2f\n",
+                        i, floor_counts[i], controller_statistics_round(floor_avgs[i]));
+  }
+  if (extra_statistics_text != NULL)
+    length += sprintf(statistics_text + length, "%s", extra_statistics_text);
+  sprintf(statistics_text + length, "Press spacebar to exit.");
   SDL_Texture* statistics_texture = sdl_get_statistics_texture(&controller_data->sdl_data, statistics_text);
   free(statistics_text);
   while (!sdl_is_spacebar_pressed(&controller_data->sdl_data)) {
@@ -145,7 +254,9 @@ void controller_enter_statistics_loop(struct ControllerData* controller_data, do
 void controller_shutdown(struct ControllerData* controller_data)
 {
   sdl_shutdown(&controller_data->sdl_data);
-  free(controller_data->scene_file_name);
+  for (int i = 0; i < controller_data->floors_count; i++)
+    free(controller_data->scene_file_names[i]);
+  free(controller_data->scene_file_names);
 }
 
 char* controller_read_string()
@@ -10,8 +10,13 @@ struct ControllerData {
 
   const char *person_file_name;
   double person_file_scale;
-  char* scene_file_name;
+  unsigned char floors_count;
+  char** scene_file_names;
+  // floor of multi-level scene which is displayed
+  unsigned char floor;
   double scene_scale;
+  // core keeps data in step with wall clock itself, maybe faster than real time
+  unsigned char paced_by_core;
 };
 
 #define CURRENT_TIME_TYPE 0
@@ -74,7 +74,7 @@ void sdl_draw_texture(struct SDLData* sdl_data, SDL_Texture* texture)
   SDL_RenderCopy(sdl_data->renderer, texture, NULL, &dst);
 }
 
-void sdl_draw_person(struct SDLData* sdl_data, int x, int y, double heading)
+void sdl_draw_person(struct SDLData* sdl_data, int x, int y, double heading, double radius)
 {
   double angle = heading * 180 / M_PI + 90;
   long angle_int = lround(angle);
@@ -91,6 +91,8 @@ void sdl_draw_person(struct SDLData* sdl_data, int x, int y, double heading)
 
   SDL_Rect dst;
   SDL_QueryTexture(texture, NULL, NULL, &dst.w, &dst.h);
+  dst.w = lround(dst.w * radius / DEFAULT_PERSON_RADIUS);
+  dst.h = lround(dst.h * radius / DEFAULT_PERSON_RADIUS);
   dst.x = x - dst.w / 2;
   dst.y = y - dst.h / 2;
 
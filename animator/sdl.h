@@ -6,6 +6,8 @@
 
 #define SCREEN_WIDTH 1280
 #define SCREEN_HEIGHT 720
+// person svg is drawn at its own scale for a person of this radius (meters)
+#define DEFAULT_PERSON_RADIUS 0.4
 
 struct SDLData {
   SDL_Window *window;
@@ -27,7 +29,7 @@ void sdl_init(struct SDLData*, const char* window_title);
 void sdl_set_svg_background(struct SDLData*, const char* file);
 void sdl_load_person_svg(struct SDLData*, const char* file, double person_svg_scale, double scene_scale);
 void sdl_draw_texture(struct SDLData*, SDL_Texture*);
-void sdl_draw_person(struct SDLData*, int x, int y, double heading);
+void sdl_draw_person(struct SDLData*, int x, int y, double heading, double radius);
 void sdl_set_density(struct SDLData*, int x, int y, double density);
 void sdl_clear_density(struct SDLData*);
 void sdl_draw_density(struct SDLData*);
@@ -18,6 +18,17 @@ macro_rules! config {
     };
 }
 
+macro_rules! optional_config {
+    ($config:ident, $config_type:ident) => {
+        {
+            match $config.get::<::configuration::$config_type>() {
+                Some(&::configuration::$config_type(ref config_tmp)) => Some(config_tmp.clone()),
+                None => None
+            }
+        }
+    };
+}
+
 #[derive(Debug,Clone)]
 pub enum DistributionValue {
     UniformDistributionValue{ from: f64, to: f64 },
@@ -34,6 +45,35 @@ pub enum SimType {
 #[derive(Debug,Clone)]
 pub struct SimTypeCfgWrap(pub SimType);
 
+#[derive(Debug,Clone)]
+pub enum InteractionModel {
+    SocialForce,
+    Orca
+}
+
+#[derive(Debug,Clone)]
+pub struct InteractionModelCfgWrap(pub InteractionModel);
+
+#[derive(Debug,Clone)]
+pub struct EventsFilename(pub String);
+#[derive(Debug,Clone)]
+pub struct RandomSeed(pub u32);
+#[derive(Debug,Clone)]
+pub struct TripsFilename(pub String);
+
+#[derive(Debug,Clone)]
+pub enum RunMode {
+    // as fast as possible
+    Batch,
+    // simulated time follows wall clock multiplied by speed factor
+    RealTime
+}
+
+#[derive(Debug,Clone)]
+pub struct RunModeCfgWrap(pub RunMode);
+#[derive(Debug,Clone)]
+pub struct RunSpeedFactor(pub f64);
+
 #[derive(Debug,Clone)]
 pub struct SceneWidth(pub u16);
 #[derive(Debug,Clone)]
@@ -43,13 +83,15 @@ pub struct SceneScale(pub f64);
 #[derive(Debug,Clone)]
 pub struct SceneWall {
     pub x0 : u16, pub y0 : u16, pub x1 : u16, pub y1: u16,
+    pub floor: u8
 }
 #[derive(Debug,Clone)]
 pub struct SceneWalls(pub Vec<SceneWall>);
 #[derive(Debug,Clone)]
 pub struct SceneSpawnArea {
     pub x0 : u16, pub y0 : u16, pub x1 : u16, pub y1: u16,
-    pub id: u8
+    pub id: u8,
+    pub floor: u8
 }
 #[derive(Debug,Clone)]
 pub struct SceneSpawnAreas(pub Vec<SceneSpawnArea>);
@@ -58,12 +100,58 @@ pub struct SceneTargetArea {
     pub x0 : u16, pub y0 : u16, pub x1 : u16, pub y1: u16,
     pub id: u8,
     pub sequence_no: u8,
-    pub last: bool
+    pub last: bool,
+    pub floor: u8
 }
 #[derive(Debug,Clone)]
 pub struct SceneTargetAreas(pub Vec<SceneTargetArea>);
 #[derive(Debug,Clone)]
-pub struct SceneFilename(pub String);
+pub struct SceneTerrainZone {
+    pub speed_factor: f64,
+    pub reverse_speed_factor: f64,
+    pub direction: f64,
+    pub points: Vec<(u16, u16)>,
+    pub floor: u8
+}
+#[derive(Debug,Clone)]
+pub struct SceneTerrainZones(pub Vec<SceneTerrainZone>);
+// counts people crossing it in both directions
+#[derive(Debug,Clone)]
+pub struct SceneMeasureLine {
+    pub x0 : u16, pub y0 : u16, pub x1 : u16, pub y1: u16,
+    pub id: u8,
+    pub floor: u8
+}
+#[derive(Debug,Clone)]
+pub struct SceneMeasureLines(pub Vec<SceneMeasureLine>);
+// measures density and speed of people inside
+#[derive(Debug,Clone)]
+pub struct SceneMeasureArea {
+    pub x0 : u16, pub y0 : u16, pub x1 : u16, pub y1: u16,
+    pub id: u8,
+    pub floor: u8
+}
+#[derive(Debug,Clone)]
+pub struct SceneMeasureAreas(pub Vec<SceneMeasureArea>);
+#[derive(Debug,Clone)]
+pub struct SceneFilenames(pub Vec<String>);
+#[derive(Debug,Clone)]
+pub enum ConnectorKind {
+    Stairs,
+    Lift
+}
+#[derive(Debug,Clone)]
+pub struct Connector {
+    pub kind: ConnectorKind,
+    pub from_floor: u8,
+    pub from_x0 : u16, pub from_y0 : u16, pub from_x1 : u16, pub from_y1: u16,
+    pub to_floor: u8,
+    pub to_x0 : u16, pub to_y0 : u16, pub to_x1 : u16, pub to_y1: u16,
+    pub traversal_time: f64,
+    pub capacity: u16
+}
+#[derive(Debug,Clone)]
+pub struct Connectors(pub Vec<Connector>);
 
 #[derive(Debug,Clone)]
 pub struct TimeEndTime(pub u32);
@@ -74,11 +162,74 @@ pub struct TimeTick(pub f64);
 pub struct SpawnTime(pub DistributionValue);
 #[derive(Debug,Clone)]
 pub struct SpawnRate(pub f64);
+#[derive(Debug,Clone)]
+pub struct SpawnPopulationFilename(pub String);
+
+#[derive(Debug,Clone)]
+pub enum InitialPopulation {
+    Count(u32),
+    // persons per square meter
+    Density(f64)
+}
+#[derive(Debug,Clone)]
+pub struct SpawnAreaPopulation {
+    pub spawn_id: u8,
+    pub population: InitialPopulation
+}
+#[derive(Debug,Clone)]
+pub struct SpawnAreaPopulations(pub Vec<SpawnAreaPopulation>);
+
+#[derive(Debug,Clone)]
+pub struct SpawnProfileMix {
+    pub spawn_id: u8,
+    pub profile_id: u8,
+    pub ratio: f64
+}
+#[derive(Debug,Clone)]
+pub struct SpawnProfileMixes(pub Vec<SpawnProfileMix>);
 
 #[derive(Debug,Clone)]
 pub struct ForcesTargetSpeed(pub DistributionValue);
 #[derive(Debug,Clone)]
 pub struct ForcesRepulsionCoeff(pub DistributionValue);
+#[derive(Debug,Clone)]
+pub struct ForcesRepulsionDistanceSqrThreshold(pub f64);
+#[derive(Debug,Clone)]
+pub struct ForcesRepulsionEllipseRx(pub f64);
+#[derive(Debug,Clone)]
+pub struct ForcesRepulsionEllipseRy(pub f64);
+#[derive(Debug,Clone)]
+pub struct ForcesRepulsionCap(pub f64);
+#[derive(Debug,Clone)]
+pub struct ForcesFluctuationMagnitude(pub f64);
+#[derive(Debug,Clone)]
+pub struct ForcesFluctuationPower(pub DistributionValue);
+#[derive(Debug,Clone)]
+pub enum NoiseMode {
+    White,
+    OrnsteinUhlenbeck
+}
+#[derive(Debug,Clone)]
+pub struct ForcesFluctuationMode(pub NoiseMode);
+#[derive(Debug,Clone)]
+pub struct ForcesFluctuationCorrelationTime(pub f64);
+#[derive(Debug,Clone)]
+pub struct ForcesFluctuationLevel(pub DistributionValue);
+#[derive(Debug,Clone,PartialEq)]
+pub enum ForceKind {
+    Repulsion,
+    Target,
+    Fluctuation,
+    Hazard
+}
+#[derive(Debug,Clone)]
+pub struct ForceCompositionItem {
+    pub kind: ForceKind,
+    pub enabled: Option<bool>,
+    pub weight: Option<f64>,
+}
+#[derive(Debug,Clone)]
+pub struct ForceComposition(pub Vec<ForceCompositionItem>);
 
 #[derive(Debug,Clone)]
 pub struct FovForward(pub DistributionValue);
@@ -91,6 +242,169 @@ pub struct DensityMapEnabled(pub bool);
 pub struct DensityMapMinThreshold(pub f64);
 #[derive(Debug,Clone)]
 pub struct DensityMapMaxThreshold(pub f64);
+#[derive(Debug,Clone)]
+pub enum DensityMethod {
+    // kernel sum in pixel space, no physical units
+    Kernel,
+    // people per square meter from personal voronoi cells
+    Voronoi
+}
+#[derive(Debug,Clone)]
+pub struct DensityMapMethod(pub DensityMethod);
+#[derive(Debug,Clone)]
+pub struct DensityVoronoiCutoff(pub f64);
+
+#[derive(Debug,Clone)]
+pub struct ProfileDefinition {
+    pub id: u8,
+    pub name: String,
+    pub radius: Option<DistributionValue>,
+    pub target_speed: Option<DistributionValue>,
+    pub repulsion_coeff: Option<DistributionValue>,
+    pub forward_fov: Option<DistributionValue>,
+    pub backward_fov: Option<DistributionValue>,
+}
+#[derive(Debug,Clone)]
+pub struct ProfileDefinitions(pub Vec<ProfileDefinition>);
+
+#[derive(Debug,Clone)]
+pub enum ScheduleAction {
+    AddWall(SceneWall),
+    RemoveWall(SceneWall),
+    EnableTargetArea{ x0 : u16, y0 : u16, x1 : u16, y1: u16, floor: u8 },
+    DisableTargetArea{ x0 : u16, y0 : u16, x1 : u16, y1: u16, floor: u8 },
+    ChangeSpawnRate{ spawn_id: Option<u8>, rate: f64 },
+    SwitchTerminationRule(SimType),
+}
+#[derive(Debug,Clone)]
+pub struct ScheduleEvent {
+    pub time: f64,
+    pub action: ScheduleAction,
+}
+#[derive(Debug,Clone)]
+pub struct ScheduleEvents(pub Vec<ScheduleEvent>);
+
+#[derive(Debug,Clone)]
+pub struct ServicePointDefinition {
+    pub id: u8,
+    pub name: String,
+    pub floor: u8,
+    pub x0 : u16, pub y0 : u16, pub x1 : u16, pub y1: u16,
+    pub servers: u16,
+    pub queue_direction: f64,
+    pub dwell_time: DistributionValue,
+}
+#[derive(Debug,Clone)]
+pub struct ServicePointDefinitions(pub Vec<ServicePointDefinition>);
+
+#[derive(Debug,Clone)]
+pub struct HazardCellSize(pub f64);
+#[derive(Debug,Clone)]
+pub struct HazardDiffusion(pub f64);
+#[derive(Debug,Clone)]
+pub struct HazardDecay(pub f64);
+#[derive(Debug,Clone)]
+pub struct HazardSpeedReduction(pub f64);
+#[derive(Debug,Clone)]
+pub struct HazardFovReduction(pub f64);
+#[derive(Debug,Clone)]
+pub struct HazardRepulsionCoeff(pub f64);
+#[derive(Debug,Clone)]
+pub struct HazardSource {
+    pub floor: u8,
+    pub x: u16, pub y: u16,
+    pub start_time: f64,
+    pub emission_rate: f64,
+}
+#[derive(Debug,Clone)]
+pub struct HazardSources(pub Vec<HazardSource>);
+
+#[derive(Debug,Clone)]
+pub struct OrcaTimeHorizon(pub f64);
+#[derive(Debug,Clone)]
+pub struct OrcaObstacleTimeHorizon(pub f64);
+#[derive(Debug,Clone)]
+pub struct OrcaNeighborDistance(pub f64);
+
+#[derive(Debug,Clone)]
+pub struct CheckpointSaveFilename(pub String);
+#[derive(Debug,Clone)]
+pub struct CheckpointSaveTime(pub f64);
+#[derive(Debug,Clone)]
+pub struct CheckpointRestoreFilename(pub String);
+
+#[derive(Debug,Clone)]
+pub struct KinematicsTurnRate(pub DistributionValue);
+#[derive(Debug,Clone)]
+pub struct KinematicsMaxSpeed(pub DistributionValue);
+#[derive(Debug,Clone)]
+pub struct KinematicsMaxAcceleration(pub DistributionValue);
+#[derive(Debug,Clone)]
+pub enum HeadingModel {
+    // person always faces where he walks
+    Walking,
+    // body turns on its own, person may step sideways or backwards
+    Body
+}
+#[derive(Debug,Clone)]
+pub struct KinematicsHeadingModel(pub HeadingModel);
+#[derive(Debug,Clone)]
+pub struct KinematicsInstantTurnThreshold(pub f64);
+#[derive(Debug,Clone)]
+pub struct KinematicsMaxHeadingDiffToTarget(pub f64);
+
+#[derive(Debug,Clone)]
+pub struct StuckDetectionEnabled(pub bool);
+#[derive(Debug,Clone)]
+pub struct StuckWindow(pub f64);
+#[derive(Debug,Clone)]
+pub struct StuckMinProgress(pub f64);
+#[derive(Debug,Clone)]
+pub enum StuckStrategy {
+    // only flag person in statistics and events
+    Report,
+    // move person a bit to a random free place
+    Perturb,
+    // send person to another alternative of his target
+    Reroute,
+    // take person off the scene
+    Remove
+}
+#[derive(Debug,Clone)]
+pub struct StuckResolution(pub StuckStrategy);
+#[derive(Debug,Clone)]
+pub struct StuckPerturbation(pub f64);
+
+#[derive(Debug,Clone)]
+pub struct TerminationMaxTime(pub f64);
+#[derive(Debug,Clone)]
+pub struct TerminationMaxArrivals(pub u32);
+#[derive(Debug,Clone)]
+pub struct TerminationExitFraction(pub f64);
+#[derive(Debug,Clone)]
+pub struct TerminationWallClock(pub f64);
+#[derive(Debug,Clone)]
+pub struct TerminationSteadyStateWindow(pub f64);
+#[derive(Debug,Clone)]
+pub struct TerminationSteadyStateTolerance(pub f64);
+
+#[derive(Debug,Clone)]
+pub struct TrajectoryFilename(pub String);
+#[derive(Debug,Clone)]
+pub struct TrajectoryInterval(pub f64);
+#[derive(Debug,Clone)]
+pub struct TrajectoryFloorHeight(pub f64);
+#[derive(Debug,Clone)]
+pub struct TrajectoryOrientation(pub bool);
+#[derive(Debug,Clone)]
+pub struct TrajectoryDensity(pub bool);
+
+#[derive(Debug,Clone)]
+pub struct MeasurementBin(pub f64);
+#[derive(Debug,Clone)]
+pub struct MeasurementFilename(pub String);
+#[derive(Debug,Clone)]
+pub struct MeasurementDiagramFilename(pub String);
 
 pub fn new(file: &mut Read) -> AnyMap {
     let mut config = AnyMap::new();
@@ -121,6 +435,18 @@ fn parse_single_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) -> b
             0x04 => parse_forces_item(config, file, buf),
             0x05 => parse_fov_item(config, file, buf),
             0x06 => parse_density_map_item(config, file, buf),
+            0x07 => parse_profiles_item(config, file, buf),
+            0x08 => parse_schedule_item(config, file, buf),
+            0x09 => parse_connectors_item(config, file, buf),
+            0x0A => parse_services_item(config, file, buf),
+            0x0B => parse_hazard_item(config, file, buf),
+            0x0C => parse_orca_item(config, file, buf),
+            0x0D => parse_checkpoint_item(config, file, buf),
+            0x0E => parse_kinematics_item(config, file, buf),
+            0x0F => parse_stuck_item(config, file, buf),
+            0x10 => parse_termination_item(config, file, buf),
+            0x11 => parse_trajectory_item(config, file, buf),
+            0x12 => parse_measurement_item(config, file, buf),
             _ => panic!("Unknown section in config: {}", section)
         }
         // let str_value = str::from_utf8(&[116, 116, 101, 115, 116]).unwrap().to_string().clone();
@@ -135,15 +461,48 @@ fn parse_general_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
     let element = parse_u16(file, buf);
     match element {
         0x01 => {
-            let typ = parse_u8(file, buf);
-            let config_typ = match typ {
-                0x01 => SimType::Flow,
-                0x02 => SimType::Escape,
-                _ => panic!("Unknown type in general config: {}", typ)
-            };
+            let config_typ = parse_sim_type(file, buf);
             debug!("Parsed SimType: {:?}", config_typ);
             config.insert(SimTypeCfgWrap(config_typ));
         },
+        0x02 => {
+            let model = match parse_u8(file, buf) {
+                0x01 => InteractionModel::SocialForce,
+                0x02 => InteractionModel::Orca,
+                model => panic!("Unknown interaction model in config: {}", model)
+            };
+            debug!("Parsed InteractionModel: {:?}", model);
+            config.insert(InteractionModelCfgWrap(model));
+        },
+        0x03 => {
+            let events_filename = parse_string(file, buf);
+            debug!("Parsed EventsFilename: {}", events_filename);
+            config.insert(EventsFilename(events_filename));
+        },
+        0x04 => {
+            let seed = parse_u32(file, buf);
+            debug!("Parsed RandomSeed: {}", seed);
+            config.insert(RandomSeed(seed));
+        },
+        0x05 => {
+            let run_mode = match parse_u8(file, buf) {
+                0x01 => RunMode::Batch,
+                0x02 => RunMode::RealTime,
+                mode => panic!("Unknown run mode in config: {}", mode)
+            };
+            debug!("Parsed RunMode: {:?}", run_mode);
+            config.insert(RunModeCfgWrap(run_mode));
+        },
+        0x06 => {
+            let speed_factor = parse_f64(file, buf);
+            debug!("Parsed RunSpeedFactor: {}", speed_factor);
+            config.insert(RunSpeedFactor(speed_factor));
+        },
+        0x07 => {
+            let trips_filename = parse_string(file, buf);
+            debug!("Parsed TripsFilename: {}", trips_filename);
+            config.insert(TripsFilename(trips_filename));
+        },
         _ => panic!("Unknown element in general config: {}", element)
     };
 }
@@ -153,6 +512,7 @@ fn parse_scene_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
     match element {
         0x01 => {
             let (x0, y0, x1, y1) = parse_coordinates(file, buf);
+            let floor = parse_u8(file, buf);
 
             let mut walls_vec = match config.remove::<SceneWalls>() {
                 Some(scene_walls) => {
@@ -162,13 +522,14 @@ fn parse_scene_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
                 None => Vec::new()
             };
 
-            walls_vec.push(SceneWall{ x0: x0, y0: y0, x1: x1, y1: y1});
+            walls_vec.push(SceneWall{ x0: x0, y0: y0, x1: x1, y1: y1, floor: floor });
             config.insert(SceneWalls(walls_vec));
-            debug!("Parsed SceneWall: {} {} {} {}", x0, y0, x1, y1);
+            debug!("Parsed SceneWall: {} {} {} {} {}", x0, y0, x1, y1, floor);
         },
         0x02 => {
             let (x0, y0, x1, y1) = parse_coordinates(file, buf);
             let id = parse_u8(file, buf);
+            let floor = parse_u8(file, buf);
 
             let mut spawn_areas_vec = match config.remove::<SceneSpawnAreas>() {
                 Some(scene_spawn_areas) => {
@@ -178,9 +539,9 @@ fn parse_scene_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
                 None => Vec::new()
             };
 
-            spawn_areas_vec.push(SceneSpawnArea{ x0: x0, y0: y0, x1: x1, y1: y1, id: id});
+            spawn_areas_vec.push(SceneSpawnArea{ x0: x0, y0: y0, x1: x1, y1: y1, id: id, floor: floor });
             config.insert(SceneSpawnAreas(spawn_areas_vec));
-            debug!("Parsed SceneSpawnArea: {} {} {} {} {}", x0, y0, x1, y1, id);
+            debug!("Parsed SceneSpawnArea: {} {} {} {} {} {}", x0, y0, x1, y1, id, floor);
         },
         0x03 => {
             let (x0, y0, x1, y1) = parse_coordinates(file, buf);
@@ -188,6 +549,7 @@ fn parse_scene_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
             let seq_no_and_last = parse_u8(file, buf);
             let last = seq_no_and_last & 0x01 == 0x01;
             let seq_no = (seq_no_and_last & 0xFE) >> 1;
+            let floor = parse_u8(file, buf);
 
             let mut target_areas_vec = match config.remove::<SceneTargetAreas>() {
                 Some(scene_target_areas) => {
@@ -197,9 +559,69 @@ fn parse_scene_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
                 None => Vec::new()
             };
 
-            target_areas_vec.push(SceneTargetArea{ x0: x0, y0: y0, x1: x1, y1: y1, id: id, sequence_no: seq_no, last: last});
+            target_areas_vec.push(SceneTargetArea{ x0: x0, y0: y0, x1: x1, y1: y1, id: id, sequence_no: seq_no, last: last, floor: floor });
             config.insert(SceneTargetAreas(target_areas_vec));
-            debug!("Parsed SceneTargetArea: {} {} {} {} {} {} {}", x0, y0, x1, y1, id, seq_no, last);
+            debug!("Parsed SceneTargetArea: {} {} {} {} {} {} {} {}", x0, y0, x1, y1, id, seq_no, last, floor);
+        },
+        0x04 => {
+            let speed_factor = parse_f64(file, buf);
+            let reverse_speed_factor = parse_f64(file, buf);
+            let direction = parse_f64(file, buf);
+            let points_count = parse_u16(file, buf);
+            let mut points = Vec::new();
+            for _i in 0..points_count {
+                let x = parse_u16(file, buf);
+                let y = parse_u16(file, buf);
+                points.push((x, y));
+            }
+            let floor = parse_u8(file, buf);
+
+            let mut terrain_zones_vec = match config.remove::<SceneTerrainZones>() {
+                Some(scene_terrain_zones) => {
+                    let SceneTerrainZones(vec) = scene_terrain_zones;
+                    vec
+                },
+                None => Vec::new()
+            };
+
+            debug!("Parsed SceneTerrainZone: {} {} {} {:?} {}", speed_factor, reverse_speed_factor, direction, points, floor);
+            terrain_zones_vec.push(SceneTerrainZone{ speed_factor: speed_factor, reverse_speed_factor: reverse_speed_factor,
+                                                     direction: direction, points: points, floor: floor });
+            config.insert(SceneTerrainZones(terrain_zones_vec));
+        },
+        0x05 => {
+            let (x0, y0, x1, y1) = parse_coordinates(file, buf);
+            let id = parse_u8(file, buf);
+            let floor = parse_u8(file, buf);
+
+            let mut measure_lines_vec = match config.remove::<SceneMeasureLines>() {
+                Some(scene_measure_lines) => {
+                    let SceneMeasureLines(vec) = scene_measure_lines;
+                    vec
+                },
+                None => Vec::new()
+            };
+
+            measure_lines_vec.push(SceneMeasureLine{ x0: x0, y0: y0, x1: x1, y1: y1, id: id, floor: floor });
+            config.insert(SceneMeasureLines(measure_lines_vec));
+            debug!("Parsed SceneMeasureLine: {} {} {} {} {} {}", x0, y0, x1, y1, id, floor);
+        },
+        0x06 => {
+            let (x0, y0, x1, y1) = parse_coordinates(file, buf);
+            let id = parse_u8(file, buf);
+            let floor = parse_u8(file, buf);
+
+            let mut measure_areas_vec = match config.remove::<SceneMeasureAreas>() {
+                Some(scene_measure_areas) => {
+                    let SceneMeasureAreas(vec) = scene_measure_areas;
+                    vec
+                },
+                None => Vec::new()
+            };
+
+            measure_areas_vec.push(SceneMeasureArea{ x0: x0, y0: y0, x1: x1, y1: y1, id: id, floor: floor });
+            config.insert(SceneMeasureAreas(measure_areas_vec));
+            debug!("Parsed SceneMeasureArea: {} {} {} {} {} {}", x0, y0, x1, y1, id, floor);
         },
         0x11 => {
             let scene_width = parse_u16(file, buf);
@@ -217,9 +639,19 @@ fn parse_scene_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
             debug!("Parsed SceneScale: {}", scene_scale);
         },
         0xFF => {
+            // one scene file per floor, in floors order
             let scene_filename = parse_string(file, buf);
             debug!("Parsed SceneFilename: {}", scene_filename);
-            config.insert(SceneFilename(scene_filename));
+
+            let mut filenames_vec = match config.remove::<SceneFilenames>() {
+                Some(scene_filenames) => {
+                    let SceneFilenames(vec) = scene_filenames;
+                    vec
+                },
+                None => Vec::new()
+            };
+            filenames_vec.push(scene_filename);
+            config.insert(SceneFilenames(filenames_vec));
         }
         _ => panic!("Unknown element in scene config: {}", element)
     };
@@ -255,33 +687,128 @@ fn parse_spawn_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
             debug!("Parsed SpawnTime: {:?}", distribution);
             config.insert(SpawnTime(distribution));
         },
+        0x04 => {
+            let filename = parse_string(file, buf);
+            debug!("Parsed SpawnPopulationFilename: {}", filename);
+            config.insert(SpawnPopulationFilename(filename));
+        },
+        0x05 => {
+            let spawn_id = parse_u8(file, buf);
+            let population = match parse_u8(file, buf) {
+                0x01 => InitialPopulation::Count(parse_f64(file, buf) as u32),
+                0x02 => InitialPopulation::Density(parse_f64(file, buf)),
+                kind => panic!("Unknown initial population kind in config: {}", kind)
+            };
+
+            let mut populations_vec = optional_config!(config, SpawnAreaPopulations).unwrap_or(Vec::new());
+            debug!("Parsed SpawnAreaPopulation: {} {:?}", spawn_id, population);
+            populations_vec.push(SpawnAreaPopulation{ spawn_id: spawn_id, population: population });
+            config.insert(SpawnAreaPopulations(populations_vec));
+        },
+        0x03 => {
+            let spawn_id = parse_u8(file, buf);
+            let profile_id = parse_u8(file, buf);
+            let ratio = parse_f64(file, buf);
+
+            let mut mixes_vec = match config.remove::<SpawnProfileMixes>() {
+                Some(spawn_profile_mixes) => {
+                    let SpawnProfileMixes(vec) = spawn_profile_mixes;
+                    vec
+                },
+                None => Vec::new()
+            };
+
+            mixes_vec.push(SpawnProfileMix{ spawn_id: spawn_id, profile_id: profile_id, ratio: ratio });
+            config.insert(SpawnProfileMixes(mixes_vec));
+            debug!("Parsed SpawnProfileMix: {} {} {}", spawn_id, profile_id, ratio);
+        },
         _ => panic!("Unknow element in spawn config: {}", element)
     }
 }
 
 fn parse_forces_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
     let sub_section = parse_u8(file, buf);
-    match sub_section {
-        0x01 => parse_repulsion_force_item(config, file, buf),
-        0x02 => parse_target_force_item(config, file, buf),
+    let kind = match sub_section {
+        0x01 => ForceKind::Repulsion,
+        0x02 => ForceKind::Target,
+        0x03 => ForceKind::Fluctuation,
+        0x04 => ForceKind::Hazard,
         _ => panic!("Unknown force: {}", sub_section)
+    };
+    let element = parse_u8(file, buf);
+    match element {
+        // elements common for every force
+        0x10 | 0x11 => parse_force_composition_item(config, file, buf, kind, element),
+        _ => match kind {
+            ForceKind::Repulsion => parse_repulsion_force_item(config, file, buf, element),
+            ForceKind::Target => parse_target_force_item(config, file, buf, element),
+            ForceKind::Fluctuation => parse_fluctuation_force_item(config, file, buf, element),
+            ForceKind::Hazard => panic!("Unknown element in hazard force: {}", element)
+        }
     }
 }
 
-fn parse_repulsion_force_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
-    let element = parse_u8(file, buf);
+fn parse_force_composition_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8], kind: ForceKind, element: u8) {
+    let mut composition_vec = match config.remove::<ForceComposition>() {
+        Some(force_composition) => {
+            let ForceComposition(vec) = force_composition;
+            vec
+        },
+        None => Vec::new()
+    };
+    if !composition_vec.iter().any(|item| item.kind == kind) {
+        composition_vec.push(ForceCompositionItem{ kind: kind.clone(), enabled: None, weight: None });
+    }
+
+    {
+        let item = composition_vec.iter_mut().find(|item| item.kind == kind).unwrap();
+        match element {
+            0x10 => {
+                item.enabled = Some(parse_u8(file, buf) != 0);
+                debug!("Parsed ForceCompositionItem enabled: {:?} {:?}", kind, item.enabled);
+            },
+            0x11 => {
+                item.weight = Some(parse_f64(file, buf));
+                debug!("Parsed ForceCompositionItem weight: {:?} {:?}", kind, item.weight);
+            },
+            _ => panic!("Unknown common element in force config: {}", element)
+        };
+    }
+    config.insert(ForceComposition(composition_vec));
+}
+
+fn parse_repulsion_force_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8], element: u8) {
     match element {
         0x01 => {
             let distribution = parse_distribution(file, buf);
             debug!("Parsed ForcesRepulsionCoeff: {:?}", distribution);
             config.insert(ForcesRepulsionCoeff(distribution));
-        }
+        },
+        0x02 => {
+            let threshold = parse_f64(file, buf);
+            debug!("Parsed ForcesRepulsionDistanceSqrThreshold: {}", threshold);
+            config.insert(ForcesRepulsionDistanceSqrThreshold(threshold));
+        },
+        0x03 => {
+            let r_x = parse_f64(file, buf);
+            debug!("Parsed ForcesRepulsionEllipseRx: {}", r_x);
+            config.insert(ForcesRepulsionEllipseRx(r_x));
+        },
+        0x04 => {
+            let r_y = parse_f64(file, buf);
+            debug!("Parsed ForcesRepulsionEllipseRy: {}", r_y);
+            config.insert(ForcesRepulsionEllipseRy(r_y));
+        },
+        0x05 => {
+            let cap = parse_f64(file, buf);
+            debug!("Parsed ForcesRepulsionCap: {}", cap);
+            config.insert(ForcesRepulsionCap(cap));
+        },
         _ => panic!("Unknown element in repulsion force: {}", element)
     }
 }
 
-fn parse_target_force_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
-    let element = parse_u8(file, buf);
+fn parse_target_force_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8], element: u8) {
     match element {
         0x01 => {
             let distribution = parse_distribution(file, buf);
@@ -292,6 +819,41 @@ fn parse_target_force_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]
     }
 }
 
+fn parse_fluctuation_force_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8], element: u8) {
+    match element {
+        0x01 => {
+            let magnitude = parse_f64(file, buf);
+            debug!("Parsed ForcesFluctuationMagnitude: {}", magnitude);
+            config.insert(ForcesFluctuationMagnitude(magnitude));
+        },
+        0x02 => {
+            let power = parse_distribution(file, buf);
+            debug!("Parsed ForcesFluctuationPower: {:?}", power);
+            config.insert(ForcesFluctuationPower(power));
+        },
+        0x03 => {
+            let mode = match parse_u8(file, buf) {
+                0x01 => NoiseMode::White,
+                0x02 => NoiseMode::OrnsteinUhlenbeck,
+                mode => panic!("Unknown fluctuation mode in config: {}", mode)
+            };
+            debug!("Parsed ForcesFluctuationMode: {:?}", mode);
+            config.insert(ForcesFluctuationMode(mode));
+        },
+        0x04 => {
+            let correlation_time = parse_f64(file, buf);
+            debug!("Parsed ForcesFluctuationCorrelationTime: {}", correlation_time);
+            config.insert(ForcesFluctuationCorrelationTime(correlation_time));
+        },
+        0x05 => {
+            let level = parse_distribution(file, buf);
+            debug!("Parsed ForcesFluctuationLevel: {:?}", level);
+            config.insert(ForcesFluctuationLevel(level));
+        }
+        _ => panic!("Unknown element in fluctuation force: {}", element)
+    }
+}
+
 fn parse_fov_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
     let element = parse_u16(file, buf);
     match element {
@@ -328,10 +890,489 @@ fn parse_density_map_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8])
             config.insert(DensityMapMaxThreshold(max_threshold));
             debug!("Parsed DensityMapMaxThreshold: {}", max_threshold);
         },
+        0x04 => {
+            let method = match parse_u8(file, buf) {
+                0x01 => DensityMethod::Kernel,
+                0x02 => DensityMethod::Voronoi,
+                method => panic!("Unknown density method in config: {}", method)
+            };
+            debug!("Parsed DensityMapMethod: {:?}", method);
+            config.insert(DensityMapMethod(method));
+        },
+        0x05 => {
+            let cutoff = parse_f64(file, buf);
+            debug!("Parsed DensityVoronoiCutoff: {}", cutoff);
+            config.insert(DensityVoronoiCutoff(cutoff));
+        },
         _ => panic!("Unknown element in density map config: {}", element)
     };
 }
 
+fn parse_profiles_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
+    let element = parse_u16(file, buf);
+    let profile_id = parse_u8(file, buf);
+
+    let mut definitions_vec = match config.remove::<ProfileDefinitions>() {
+        Some(profile_definitions) => {
+            let ProfileDefinitions(vec) = profile_definitions;
+            vec
+        },
+        None => Vec::new()
+    };
+    if !definitions_vec.iter().any(|definition| definition.id == profile_id) {
+        definitions_vec.push(ProfileDefinition{ id: profile_id, name: format!("profile_{}", profile_id),
+                                                radius: None, target_speed: None, repulsion_coeff: None,
+                                                forward_fov: None, backward_fov: None });
+    }
+
+    {
+        let definition = definitions_vec.iter_mut().find(|definition| definition.id == profile_id).unwrap();
+        match element {
+            0x01 => {
+                definition.name = parse_string(file, buf);
+                debug!("Parsed ProfileDefinition name: {} {}", profile_id, definition.name);
+            },
+            0x02 => {
+                let distribution = parse_distribution(file, buf);
+                debug!("Parsed ProfileDefinition radius: {} {:?}", profile_id, distribution);
+                definition.radius = Some(distribution);
+            },
+            0x03 => {
+                let distribution = parse_distribution(file, buf);
+                debug!("Parsed ProfileDefinition target speed: {} {:?}", profile_id, distribution);
+                definition.target_speed = Some(distribution);
+            },
+            0x04 => {
+                let distribution = parse_distribution(file, buf);
+                debug!("Parsed ProfileDefinition repulsion coeff: {} {:?}", profile_id, distribution);
+                definition.repulsion_coeff = Some(distribution);
+            },
+            0x05 => {
+                let distribution = parse_distribution(file, buf);
+                debug!("Parsed ProfileDefinition forward fov: {} {:?}", profile_id, distribution);
+                definition.forward_fov = Some(distribution);
+            },
+            0x06 => {
+                let distribution = parse_distribution(file, buf);
+                debug!("Parsed ProfileDefinition backward fov: {} {:?}", profile_id, distribution);
+                definition.backward_fov = Some(distribution);
+            },
+            _ => panic!("Unknown element in profiles config: {}", element)
+        };
+    }
+    config.insert(ProfileDefinitions(definitions_vec));
+}
+
+fn parse_schedule_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
+    let element = parse_u16(file, buf);
+    let time = parse_f64(file, buf);
+    let action = match element {
+        0x01 => {
+            let (x0, y0, x1, y1) = parse_coordinates(file, buf);
+            let floor = parse_u8(file, buf);
+            ScheduleAction::AddWall(SceneWall{ x0: x0, y0: y0, x1: x1, y1: y1, floor: floor })
+        },
+        0x02 => {
+            let (x0, y0, x1, y1) = parse_coordinates(file, buf);
+            let floor = parse_u8(file, buf);
+            ScheduleAction::RemoveWall(SceneWall{ x0: x0, y0: y0, x1: x1, y1: y1, floor: floor })
+        },
+        0x03 => {
+            let (x0, y0, x1, y1) = parse_coordinates(file, buf);
+            let floor = parse_u8(file, buf);
+            ScheduleAction::EnableTargetArea{ x0: x0, y0: y0, x1: x1, y1: y1, floor: floor }
+        },
+        0x04 => {
+            let (x0, y0, x1, y1) = parse_coordinates(file, buf);
+            let floor = parse_u8(file, buf);
+            ScheduleAction::DisableTargetArea{ x0: x0, y0: y0, x1: x1, y1: y1, floor: floor }
+        },
+        0x05 => {
+            // 0xFF - change rate of all spawn areas
+            let spawn_id = match parse_u8(file, buf) {
+                0xFF => None,
+                id => Some(id)
+            };
+            let rate = parse_f64(file, buf);
+            ScheduleAction::ChangeSpawnRate{ spawn_id: spawn_id, rate: rate }
+        },
+        0x06 => ScheduleAction::SwitchTerminationRule(parse_sim_type(file, buf)),
+        _ => panic!("Unknown element in schedule config: {}", element)
+    };
+
+    let mut events_vec = match config.remove::<ScheduleEvents>() {
+        Some(schedule_events) => {
+            let ScheduleEvents(vec) = schedule_events;
+            vec
+        },
+        None => Vec::new()
+    };
+
+    debug!("Parsed ScheduleEvent: {} {:?}", time, action);
+    events_vec.push(ScheduleEvent{ time: time, action: action });
+    config.insert(ScheduleEvents(events_vec));
+}
+
+fn parse_connectors_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
+    let element = parse_u16(file, buf);
+    let kind = match element {
+        0x01 => ConnectorKind::Stairs,
+        0x02 => ConnectorKind::Lift,
+        _ => panic!("Unknown element in connectors config: {}", element)
+    };
+    let from_floor = parse_u8(file, buf);
+    let (from_x0, from_y0, from_x1, from_y1) = parse_coordinates(file, buf);
+    let to_floor = parse_u8(file, buf);
+    let (to_x0, to_y0, to_x1, to_y1) = parse_coordinates(file, buf);
+    let traversal_time = parse_f64(file, buf);
+    let capacity = parse_u16(file, buf);
+
+    let mut connectors_vec = match config.remove::<Connectors>() {
+        Some(connectors) => {
+            let Connectors(vec) = connectors;
+            vec
+        },
+        None => Vec::new()
+    };
+
+    let connector = Connector{ kind: kind,
+                               from_floor: from_floor, from_x0: from_x0, from_y0: from_y0, from_x1: from_x1, from_y1: from_y1,
+                               to_floor: to_floor, to_x0: to_x0, to_y0: to_y0, to_x1: to_x1, to_y1: to_y1,
+                               traversal_time: traversal_time, capacity: capacity };
+    debug!("Parsed Connector: {:?}", connector);
+    connectors_vec.push(connector);
+    config.insert(Connectors(connectors_vec));
+}
+
+fn parse_services_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
+    let element = parse_u16(file, buf);
+    let service_id = parse_u8(file, buf);
+
+    let mut definitions_vec = match config.remove::<ServicePointDefinitions>() {
+        Some(service_point_definitions) => {
+            let ServicePointDefinitions(vec) = service_point_definitions;
+            vec
+        },
+        None => Vec::new()
+    };
+    if !definitions_vec.iter().any(|definition| definition.id == service_id) {
+        definitions_vec.push(ServicePointDefinition{ id: service_id, name: format!("service_{}", service_id),
+                                                     floor: 0, x0: 0, y0: 0, x1: 0, y1: 0,
+                                                     servers: 1, queue_direction: 0_f64,
+                                                     dwell_time: DistributionValue::UniformDistributionValue{ from: 0_f64, to: 0_f64 } });
+    }
+
+    {
+        let definition = definitions_vec.iter_mut().find(|definition| definition.id == service_id).unwrap();
+        match element {
+            0x01 => {
+                definition.name = parse_string(file, buf);
+                debug!("Parsed ServicePointDefinition name: {} {}", service_id, definition.name);
+            },
+            0x02 => {
+                definition.floor = parse_u8(file, buf);
+                let (x0, y0, x1, y1) = parse_coordinates(file, buf);
+                definition.x0 = x0; definition.y0 = y0; definition.x1 = x1; definition.y1 = y1;
+                debug!("Parsed ServicePointDefinition area: {} {} {} {} {} {}", service_id, x0, y0, x1, y1, definition.floor);
+            },
+            0x03 => {
+                definition.servers = parse_u16(file, buf);
+                debug!("Parsed ServicePointDefinition servers: {} {}", service_id, definition.servers);
+            },
+            0x04 => {
+                definition.queue_direction = parse_f64(file, buf);
+                debug!("Parsed ServicePointDefinition queue direction: {} {}", service_id, definition.queue_direction);
+            },
+            0x05 => {
+                definition.dwell_time = parse_distribution(file, buf);
+                debug!("Parsed ServicePointDefinition dwell time: {} {:?}", service_id, definition.dwell_time);
+            },
+            _ => panic!("Unknown element in services config: {}", element)
+        };
+    }
+    config.insert(ServicePointDefinitions(definitions_vec));
+}
+
+fn parse_hazard_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
+    let element = parse_u16(file, buf);
+    match element {
+        0x01 => {
+            let cell_size = parse_f64(file, buf);
+            debug!("Parsed HazardCellSize: {}", cell_size);
+            config.insert(HazardCellSize(cell_size));
+        },
+        0x02 => {
+            let diffusion = parse_f64(file, buf);
+            debug!("Parsed HazardDiffusion: {}", diffusion);
+            config.insert(HazardDiffusion(diffusion));
+        },
+        0x03 => {
+            let decay = parse_f64(file, buf);
+            debug!("Parsed HazardDecay: {}", decay);
+            config.insert(HazardDecay(decay));
+        },
+        0x04 => {
+            let floor = parse_u8(file, buf);
+            let x = parse_u16(file, buf);
+            let y = parse_u16(file, buf);
+            let start_time = parse_f64(file, buf);
+            let emission_rate = parse_f64(file, buf);
+
+            let mut sources_vec = match config.remove::<HazardSources>() {
+                Some(hazard_sources) => {
+                    let HazardSources(vec) = hazard_sources;
+                    vec
+                },
+                None => Vec::new()
+            };
+
+            let source = HazardSource{ floor: floor, x: x, y: y, start_time: start_time, emission_rate: emission_rate };
+            debug!("Parsed HazardSource: {:?}", source);
+            sources_vec.push(source);
+            config.insert(HazardSources(sources_vec));
+        },
+        0x05 => {
+            let speed_reduction = parse_f64(file, buf);
+            debug!("Parsed HazardSpeedReduction: {}", speed_reduction);
+            config.insert(HazardSpeedReduction(speed_reduction));
+        },
+        0x06 => {
+            let fov_reduction = parse_f64(file, buf);
+            debug!("Parsed HazardFovReduction: {}", fov_reduction);
+            config.insert(HazardFovReduction(fov_reduction));
+        },
+        0x07 => {
+            let repulsion_coeff = parse_f64(file, buf);
+            debug!("Parsed HazardRepulsionCoeff: {}", repulsion_coeff);
+            config.insert(HazardRepulsionCoeff(repulsion_coeff));
+        },
+        _ => panic!("Unknown element in hazard config: {}", element)
+    };
+}
+
+fn parse_orca_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
+    let element = parse_u16(file, buf);
+    match element {
+        0x01 => {
+            let time_horizon = parse_f64(file, buf);
+            debug!("Parsed OrcaTimeHorizon: {}", time_horizon);
+            config.insert(OrcaTimeHorizon(time_horizon));
+        },
+        0x02 => {
+            let obstacle_time_horizon = parse_f64(file, buf);
+            debug!("Parsed OrcaObstacleTimeHorizon: {}", obstacle_time_horizon);
+            config.insert(OrcaObstacleTimeHorizon(obstacle_time_horizon));
+        },
+        0x03 => {
+            let neighbor_distance = parse_f64(file, buf);
+            debug!("Parsed OrcaNeighborDistance: {}", neighbor_distance);
+            config.insert(OrcaNeighborDistance(neighbor_distance));
+        },
+        _ => panic!("Unknown element in orca config: {}", element)
+    };
+}
+
+fn parse_checkpoint_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
+    let element = parse_u16(file, buf);
+    match element {
+        0x01 => {
+            let filename = parse_string(file, buf);
+            debug!("Parsed CheckpointSaveFilename: {}", filename);
+            config.insert(CheckpointSaveFilename(filename));
+        },
+        0x02 => {
+            let time = parse_f64(file, buf);
+            debug!("Parsed CheckpointSaveTime: {}", time);
+            config.insert(CheckpointSaveTime(time));
+        },
+        0x03 => {
+            let filename = parse_string(file, buf);
+            debug!("Parsed CheckpointRestoreFilename: {}", filename);
+            config.insert(CheckpointRestoreFilename(filename));
+        },
+        _ => panic!("Unknown element in checkpoint config: {}", element)
+    };
+}
+
+fn parse_kinematics_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
+    let element = parse_u16(file, buf);
+    match element {
+        0x01 => {
+            let turn_rate = parse_distribution(file, buf);
+            debug!("Parsed KinematicsTurnRate: {:?}", turn_rate);
+            config.insert(KinematicsTurnRate(turn_rate));
+        },
+        0x02 => {
+            let max_speed = parse_distribution(file, buf);
+            debug!("Parsed KinematicsMaxSpeed: {:?}", max_speed);
+            config.insert(KinematicsMaxSpeed(max_speed));
+        },
+        0x03 => {
+            let max_acceleration = parse_distribution(file, buf);
+            debug!("Parsed KinematicsMaxAcceleration: {:?}", max_acceleration);
+            config.insert(KinematicsMaxAcceleration(max_acceleration));
+        },
+        0x04 => {
+            let heading_model = match parse_u8(file, buf) {
+                0x01 => HeadingModel::Walking,
+                0x02 => HeadingModel::Body,
+                model => panic!("Unknown heading model in config: {}", model)
+            };
+            debug!("Parsed KinematicsHeadingModel: {:?}", heading_model);
+            config.insert(KinematicsHeadingModel(heading_model));
+        },
+        0x05 => {
+            let threshold = parse_f64(file, buf);
+            debug!("Parsed KinematicsInstantTurnThreshold: {}", threshold);
+            config.insert(KinematicsInstantTurnThreshold(threshold));
+        },
+        0x06 => {
+            let max_diff = parse_f64(file, buf);
+            debug!("Parsed KinematicsMaxHeadingDiffToTarget: {}", max_diff);
+            config.insert(KinematicsMaxHeadingDiffToTarget(max_diff));
+        },
+        _ => panic!("Unknown element in kinematics config: {}", element)
+    };
+}
+
+fn parse_stuck_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
+    let element = parse_u16(file, buf);
+    match element {
+        0x01 => {
+            let enabled = parse_u8(file, buf) != 0_u8;
+            debug!("Parsed StuckDetectionEnabled: {}", enabled);
+            config.insert(StuckDetectionEnabled(enabled));
+        },
+        0x02 => {
+            let window = parse_f64(file, buf);
+            debug!("Parsed StuckWindow: {}", window);
+            config.insert(StuckWindow(window));
+        },
+        0x03 => {
+            let min_progress = parse_f64(file, buf);
+            debug!("Parsed StuckMinProgress: {}", min_progress);
+            config.insert(StuckMinProgress(min_progress));
+        },
+        0x04 => {
+            let strategy = match parse_u8(file, buf) {
+                0x01 => StuckStrategy::Report,
+                0x02 => StuckStrategy::Perturb,
+                0x03 => StuckStrategy::Reroute,
+                0x04 => StuckStrategy::Remove,
+                strategy => panic!("Unknown stuck resolution strategy in config: {}", strategy)
+            };
+            debug!("Parsed StuckResolution: {:?}", strategy);
+            config.insert(StuckResolution(strategy));
+        },
+        0x05 => {
+            let perturbation = parse_f64(file, buf);
+            debug!("Parsed StuckPerturbation: {}", perturbation);
+            config.insert(StuckPerturbation(perturbation));
+        },
+        _ => panic!("Unknown element in stuck config: {}", element)
+    };
+}
+
+fn parse_termination_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
+    let element = parse_u16(file, buf);
+    match element {
+        0x01 => {
+            let max_time = parse_f64(file, buf);
+            debug!("Parsed TerminationMaxTime: {}", max_time);
+            config.insert(TerminationMaxTime(max_time));
+        },
+        0x02 => {
+            let max_arrivals = parse_u32(file, buf);
+            debug!("Parsed TerminationMaxArrivals: {}", max_arrivals);
+            config.insert(TerminationMaxArrivals(max_arrivals));
+        },
+        0x03 => {
+            let exit_fraction = parse_f64(file, buf);
+            debug!("Parsed TerminationExitFraction: {}", exit_fraction);
+            config.insert(TerminationExitFraction(exit_fraction));
+        },
+        0x04 => {
+            let wall_clock = parse_f64(file, buf);
+            debug!("Parsed TerminationWallClock: {}", wall_clock);
+            config.insert(TerminationWallClock(wall_clock));
+        },
+        0x05 => {
+            let window = parse_f64(file, buf);
+            debug!("Parsed TerminationSteadyStateWindow: {}", window);
+            config.insert(TerminationSteadyStateWindow(window));
+        },
+        0x06 => {
+            let tolerance = parse_f64(file, buf);
+            debug!("Parsed TerminationSteadyStateTolerance: {}", tolerance);
+            config.insert(TerminationSteadyStateTolerance(tolerance));
+        },
+        _ => panic!("Unknown element in termination config: {}", element)
+    };
+}
+
+fn parse_trajectory_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
+    let element = parse_u16(file, buf);
+    match element {
+        0x01 => {
+            let filename = parse_string(file, buf);
+            debug!("Parsed TrajectoryFilename: {}", filename);
+            config.insert(TrajectoryFilename(filename));
+        },
+        0x02 => {
+            let interval = parse_f64(file, buf);
+            debug!("Parsed TrajectoryInterval: {}", interval);
+            config.insert(TrajectoryInterval(interval));
+        },
+        0x03 => {
+            let floor_height = parse_f64(file, buf);
+            debug!("Parsed TrajectoryFloorHeight: {}", floor_height);
+            config.insert(TrajectoryFloorHeight(floor_height));
+        },
+        0x04 => {
+            let orientation = parse_u8(file, buf) != 0_u8;
+            debug!("Parsed TrajectoryOrientation: {}", orientation);
+            config.insert(TrajectoryOrientation(orientation));
+        },
+        0x05 => {
+            let density = parse_u8(file, buf) != 0_u8;
+            debug!("Parsed TrajectoryDensity: {}", density);
+            config.insert(TrajectoryDensity(density));
+        },
+        _ => panic!("Unknown element in trajectory config: {}", element)
+    };
+}
+
+fn parse_measurement_item(config: &mut AnyMap, file: &mut Read, buf : &mut [u8]) {
+    let element = parse_u16(file, buf);
+    match element {
+        0x01 => {
+            let bin = parse_f64(file, buf);
+            debug!("Parsed MeasurementBin: {}", bin);
+            config.insert(MeasurementBin(bin));
+        },
+        0x02 => {
+            let filename = parse_string(file, buf);
+            debug!("Parsed MeasurementFilename: {}", filename);
+            config.insert(MeasurementFilename(filename));
+        },
+        0x03 => {
+            let filename = parse_string(file, buf);
+            debug!("Parsed MeasurementDiagramFilename: {}", filename);
+            config.insert(MeasurementDiagramFilename(filename));
+        },
+        _ => panic!("Unknown element in measurement config: {}", element)
+    };
+}
+
+fn parse_sim_type(file: &mut Read, buf : &mut [u8]) -> SimType {
+    let typ = parse_u8(file, buf);
+    match typ {
+        0x01 => SimType::Flow,
+        0x02 => SimType::Escape,
+        _ => panic!("Unknown sim type: {}", typ)
+    }
+}
+
 fn parse_coordinates(file: &mut Read, buf : &mut [u8]) -> (u16, u16, u16, u16) {
     let mut coordinates = [0u16; 4];
     read_n_bytes(file, buf, 8);
@@ -0,0 +1,14 @@
+#[macro_use] extern crate log;
+
+#[macro_use] pub mod configuration;
+pub mod simulation;
+pub mod output;
+pub mod utils;
+
+pub use simulation::Simulation;
+pub use simulation::scene::Scene;
+pub use simulation::person::Person;
+pub use simulation::forces::Forceable;
+pub use utils::linelg::Line;
+pub use utils::linelg::Point;
+pub use utils::linelg::Vector;
@@ -1,16 +1,14 @@
-#[macro_use] extern crate log;
 extern crate env_logger;
+extern crate crowd_sim;
 
-#[macro_use] mod configuration;
-mod simulation;
-mod output;
-mod utils;
+use crowd_sim::configuration;
+use crowd_sim::Simulation;
 
 fn main() {
     env_logger::init().unwrap();
 
     // let mut file = File::open(config_filename).ok().expect("Can't open provided config file!");
     let configuration = configuration::new(&mut std::io::stdin());
-    let mut simulation = simulation::Simulation::new(configuration);
+    let mut simulation = Simulation::new(configuration);
     simulation.main_loop();
 }
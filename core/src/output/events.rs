@@ -0,0 +1,47 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::prelude::*;
+
+use ::simulation::Simulation;
+use ::simulation::observer::SimulationObserver;
+use ::simulation::events::SimulationEvent;
+use ::simulation::events::SimulationEventKind;
+
+// writes simulation events to file, one json object per line
+pub struct EventLog {
+    file: BufWriter<File>,
+}
+
+impl EventLog {
+    pub fn new(filename: &str) -> EventLog {
+        let file = File::create(filename).ok().expect("Can't create events file");
+        EventLog{ file: BufWriter::new(file) }
+    }
+
+    fn format_event(event: &SimulationEvent) -> String {
+        let mut line = format!("{{\"time\":{},\"event\":\"{}\"", event.time, event.name());
+        if let Some(person_id) = event.person_id {
+            line.push_str(&format!(",\"person_id\":{}", person_id));
+        }
+        line.push_str(&format!(",\"floor\":{},\"x\":{},\"y\":{}", event.floor, event.location.x, event.location.y));
+        match event.kind {
+            SimulationEventKind::SpawnFailed{ spawn_id } => line.push_str(&format!(",\"spawn_id\":{}", spawn_id)),
+            SimulationEventKind::TargetReached{ target_index } => line.push_str(&format!(",\"target_index\":{}", target_index)),
+            SimulationEventKind::SmallForce{ force } => line.push_str(&format!(",\"force\":{}", force)),
+            SimulationEventKind::Stuck{ progress } => line.push_str(&format!(",\"progress\":{}", progress)),
+            _ => ()
+        }
+        line.push('}');
+        line
+    }
+}
+
+impl SimulationObserver for EventLog {
+    fn on_event(&mut self, _simulation: &Simulation, event: &SimulationEvent) {
+        writeln!(self.file, "{}", EventLog::format_event(event)).ok().expect("Can't write to events file");
+    }
+
+    fn on_finish(&mut self, _simulation: &Simulation) {
+        self.file.flush().ok().expect("Can't write to events file");
+    }
+}
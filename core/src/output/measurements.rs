@@ -0,0 +1,276 @@
+extern crate anymap;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::prelude::*;
+
+use self::anymap::AnyMap;
+
+use ::simulation::Simulation;
+use ::simulation::observer::SimulationObserver;
+use ::simulation::person::Person;
+use ::simulation::voronoi::Voronoi;
+
+use ::utils::linelg::Line;
+use ::utils::linelg::Point;
+
+// seconds
+const DEFAULT_BIN: f64 = 1_f64;
+
+const MEASUREMENTS_HEADER: &'static str = "time_from,time_to,kind,id,forward,backward,flow,density,speed,voronoi_density,voronoi_speed";
+const DIAGRAM_HEADER: &'static str = "time_from,time_to,area_id,density,speed,flow,line_flow,voronoi_density,voronoi_speed,voronoi_flow";
+
+struct MeasureLine {
+    id: u8,
+    floor: u8,
+    line: Line,
+    forward: u32,
+    backward: u32,
+}
+
+struct MeasureArea {
+    id: u8,
+    floor: u8,
+    p0: Point,
+    p1: Point,
+    // measure lines lying inside, their flow goes to fundamental diagram along with area density
+    lines: Vec<usize>,
+    person_ticks: u32,
+    // meters per second
+    speed_sum: f64,
+    // sum over ticks of voronoi density
+    voronoi_density_sum: f64,
+    // sums over ticks of speed times area of cells inside and of that area, cut off cells may leave gaps
+    voronoi_speed_sum: f64,
+    voronoi_covered_sum: f64,
+}
+
+impl MeasureArea {
+    fn contains(&self, p: &Point) -> bool {
+        p.x >= self.p0.x && p.x <= self.p1.x && p.y >= self.p0.y && p.y <= self.p1.y
+    }
+
+    // square pixels
+    fn area(&self) -> f64 {
+        (self.p1.x - self.p0.x) * (self.p1.y - self.p0.y)
+    }
+}
+
+// counts crossings of measure lines and density and speed in measure areas per time bin
+pub struct Measurements {
+    file: Option<BufWriter<File>>,
+    diagram_file: Option<BufWriter<File>>,
+    bin: f64,
+    lines: Vec<MeasureLine>,
+    areas: Vec<MeasureArea>,
+    voronoi: Voronoi,
+    current_bin: u64,
+    // ticks accounted in current bin
+    ticks: u32,
+    // positions on previous tick by person id
+    previous_positions: HashMap<u32, (Point, u8)>,
+}
+
+impl Measurements {
+    pub fn new(configuration: &AnyMap) -> Option<Measurements> {
+        let file = optional_config!(configuration, MeasurementFilename)
+            .map(|filename| BufWriter::new(File::create(filename).ok().expect("Can't create measurements file")));
+        let diagram_file = optional_config!(configuration, MeasurementDiagramFilename)
+            .map(|filename| BufWriter::new(File::create(filename).ok().expect("Can't create fundamental diagram file")));
+        if file.is_none() && diagram_file.is_none() {
+            return None;
+        }
+
+        let lines: Vec<MeasureLine> = optional_config!(configuration, SceneMeasureLines).unwrap_or(Vec::new()).iter().map(|line|
+            MeasureLine{ id: line.id, floor: line.floor, forward: 0, backward: 0,
+                         line: Line::new_from_raw(line.x0 as f64, line.y0 as f64, line.x1 as f64, line.y1 as f64) }
+        ).collect();
+        let areas = optional_config!(configuration, SceneMeasureAreas).unwrap_or(Vec::new()).iter().map(|area| {
+            let mut measure_area = MeasureArea{ id: area.id, floor: area.floor, lines: Vec::new(), person_ticks: 0, speed_sum: 0_f64,
+                                                voronoi_density_sum: 0_f64, voronoi_speed_sum: 0_f64, voronoi_covered_sum: 0_f64,
+                                                p0: Point::new(area.x0.min(area.x1) as f64, area.y0.min(area.y1) as f64),
+                                                p1: Point::new(area.x0.max(area.x1) as f64, area.y0.max(area.y1) as f64) };
+            measure_area.lines = lines.iter().enumerate().filter(|&(_i, line)|
+                line.floor == area.floor && measure_area.contains(&line.line.from) && measure_area.contains(&line.line.to)
+            ).map(|(i, _line)| i).collect();
+            measure_area
+        }).collect();
+
+        Some(Measurements{ file: file, diagram_file: diagram_file,
+                           bin: optional_config!(configuration, MeasurementBin).unwrap_or(DEFAULT_BIN),
+                           lines: lines, areas: areas, voronoi: Voronoi::new(configuration),
+                           current_bin: 0, ticks: 0, previous_positions: HashMap::new() })
+    }
+
+    fn remember_positions(&mut self, simulation: &Simulation) {
+        self.previous_positions = simulation.scene.people.iter().map(|person| (person.id, (person.coordinates, person.floor))).collect();
+    }
+
+    // writes finished bin once simulation moves to the next one
+    fn advance_bin(&mut self, simulation: &Simulation) {
+        // tick from current time on belongs to the bin its middle falls in
+        let bin = ((simulation.time.current_time + simulation.time.tick / 2_f64) / self.bin) as u64;
+        if bin != self.current_bin {
+            if self.ticks > 0 {
+                self.write_bin(simulation);
+            }
+            self.current_bin = bin;
+        }
+    }
+
+    fn count_crossings(&mut self, person: &Person) {
+        let (previous, floor) = match self.previous_positions.get(&person.id) {
+            Some(&position) => position,
+            None => return
+        };
+        // changing floor isn't crossing anything
+        if floor != person.floor {
+            return;
+        }
+        let step = Line::new(previous, person.coordinates);
+        for line in self.lines.iter_mut().filter(|line| line.floor == person.floor) {
+            if step.intersects(&line.line) {
+                // forward is from left to right side looking from line start to its end
+                let direction = line.line.to - line.line.from;
+                let side = direction.x * (person.coordinates.y - line.line.from.y) -
+                    direction.y * (person.coordinates.x - line.line.from.x);
+                if side > 0_f64 {
+                    line.forward += 1;
+                } else if side < 0_f64 {
+                    line.backward += 1;
+                }
+            }
+        }
+    }
+
+    fn measure(&mut self, simulation: &Simulation) {
+        let scale = simulation.scene.scale;
+        for person in simulation.scene.people.iter() {
+            self.count_crossings(person);
+            for area in self.areas.iter_mut().filter(|area| area.floor == person.floor) {
+                if area.contains(&person.coordinates) {
+                    area.person_ticks += 1;
+                    area.speed_sum += person.velocity.length() * scale;
+                }
+            }
+        }
+        self.measure_voronoi(simulation);
+        self.ticks += 1;
+    }
+
+    // every cell counts as much as its part inside area, speed is weighted the same way
+    fn measure_voronoi(&mut self, simulation: &Simulation) {
+        for floor in 0..simulation.scene.floors.len() {
+            if !self.areas.iter().any(|area| area.floor as usize == floor) {
+                continue;
+            }
+            let cells = self.voronoi.cells(&simulation.scene, floor as u8);
+            for area in self.areas.iter_mut().filter(|area| area.floor as usize == floor) {
+                let mut density = 0_f64;
+                for cell in cells.iter() {
+                    let inside = cell.area_inside(&area.p0, &area.p1);
+                    if inside > 0_f64 {
+                        density += inside / cell.area();
+                        area.voronoi_speed_sum += inside * simulation.scene.people[cell.person_index].velocity.length() * simulation.scene.scale;
+                        area.voronoi_covered_sum += inside;
+                    }
+                }
+                area.voronoi_density_sum += density / (area.area() * simulation.scene.scale * simulation.scene.scale);
+            }
+        }
+    }
+
+    fn write_bin(&mut self, simulation: &Simulation) {
+        let scale = simulation.scene.scale;
+        let duration = self.ticks as f64 * simulation.time.tick;
+        let time_from = self.current_bin as f64 * self.bin;
+        let time_to = time_from + duration;
+
+        // people per second and per meter of line width
+        let specific_flows: Vec<f64> = self.lines.iter().map(|line|
+            (line.forward + line.backward) as f64 / duration / (line.line.length() * scale)
+        ).collect();
+        if let Some(ref mut file) = self.file {
+            for line in self.lines.iter() {
+                writeln!(file, "{},{},line,{},{},{},{:.4},,,,", time_from, time_to, line.id, line.forward, line.backward,
+                         (line.forward + line.backward) as f64 / duration).ok().expect("Can't write to measurements file");
+            }
+        }
+        for area in self.areas.iter() {
+            // people per square meter
+            let density = area.person_ticks as f64 / self.ticks as f64 / (area.area() * scale * scale);
+            let voronoi_density = area.voronoi_density_sum / self.ticks as f64;
+            let voronoi_speed = if area.voronoi_covered_sum > 0_f64 { area.voronoi_speed_sum / area.voronoi_covered_sum } else { 0_f64 };
+            let speed = if area.person_ticks > 0 { Some(area.speed_sum / area.person_ticks as f64) } else { None };
+            let speed_str = speed.map_or(String::new(), |speed| format!("{:.4}", speed));
+            if let Some(ref mut file) = self.file {
+                writeln!(file, "{},{},area,{},,,,{:.4},{},{:.4},{:.4}", time_from, time_to, area.id, density, speed_str,
+                         voronoi_density, voronoi_speed)
+                    .ok().expect("Can't write to measurements file");
+            }
+            // empty area tells nothing about the diagram
+            if let (Some(ref mut file), Some(speed)) = (self.diagram_file.as_mut(), speed) {
+                let line_flow = if area.lines.is_empty() {
+                    String::new()
+                } else {
+                    format!("{:.4}", area.lines.iter().map(|&i| specific_flows[i]).fold(0_f64, |sum, flow| sum + flow) / area.lines.len() as f64)
+                };
+                // flow from hydrodynamic relation, line_flow is measured on lines inside area
+                writeln!(file, "{},{},{},{:.4},{:.4},{:.4},{},{:.4},{:.4},{:.4}", time_from, time_to, area.id, density, speed,
+                         density * speed, line_flow, voronoi_density, voronoi_speed, voronoi_density * voronoi_speed)
+                    .ok().expect("Can't write to fundamental diagram file");
+            }
+        }
+
+        for line in self.lines.iter_mut() {
+            line.forward = 0;
+            line.backward = 0;
+        }
+        for area in self.areas.iter_mut() {
+            area.person_ticks = 0;
+            area.speed_sum = 0_f64;
+            area.voronoi_density_sum = 0_f64;
+            area.voronoi_speed_sum = 0_f64;
+            area.voronoi_covered_sum = 0_f64;
+        }
+        self.ticks = 0;
+    }
+}
+
+impl SimulationObserver for Measurements {
+    // people leaving through exit are off scene by the tick end, their last step may cross a line at the door
+    fn on_exit(&mut self, simulation: &Simulation, person: &Person) {
+        self.advance_bin(simulation);
+        self.count_crossings(person);
+    }
+
+    fn on_start(&mut self, simulation: &Simulation) {
+        if let Some(ref mut file) = self.file {
+            writeln!(file, "{}", MEASUREMENTS_HEADER).ok().expect("Can't write to measurements file");
+        }
+        if let Some(ref mut file) = self.diagram_file {
+            writeln!(file, "{}", DIAGRAM_HEADER).ok().expect("Can't write to fundamental diagram file");
+        }
+        self.current_bin = (simulation.time.current_time / self.bin) as u64;
+        self.remember_positions(simulation);
+    }
+
+    fn on_tick(&mut self, simulation: &Simulation) {
+        self.advance_bin(simulation);
+        self.measure(simulation);
+        self.remember_positions(simulation);
+    }
+
+    fn on_finish(&mut self, simulation: &Simulation) {
+        if self.ticks > 0 {
+            self.write_bin(simulation);
+        }
+        if let Some(ref mut file) = self.file {
+            file.flush().ok().expect("Can't write to measurements file");
+        }
+        if let Some(ref mut file) = self.diagram_file {
+            file.flush().ok().expect("Can't write to fundamental diagram file");
+        }
+    }
+}
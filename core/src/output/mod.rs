@@ -1,48 +1,80 @@
 extern crate anymap;
 
+pub mod events;
+pub mod trips;
+pub mod trajectory;
+pub mod measurements;
+
 use self::anymap::AnyMap;
 
 use std;
 use std::io::prelude::*;
 
 use ::simulation::Simulation;
+use ::simulation::observer::SimulationObserver;
 use ::simulation::person::Person;
+use ::simulation::voronoi::Voronoi;
+
+use ::configuration::DensityMethod;
+use ::configuration::RunMode;
 
 const CURRENT_TIME_TYPE: u8 = 0_u8;
 const LOCATIONS_TYPE: u8 = 1_u8;
 const DENSITY_MAP_TYPE: u8 = 2_u8;
 const STATISTICS_TYPE: u8 = 3_u8;
 
+// writes simulation state to stdout for animator
 pub struct Output {
-    scene_file_name: String,
+    scene_file_names: Vec<String>,
     scene_scale: f64,
 
     density_map_enabled: bool,
     density_map_min_threshold: f64,
     density_map_max_threshold: f64,
+    density_method: DensityMethod,
+    voronoi: Voronoi,
     ticks_without_density: u32,
+    // animator shouldn't pace output on its own then
+    paced: bool,
 }
 
 impl Output {
     pub fn new(configuration: &AnyMap) -> Output {
         let scene_scale = config!(configuration, SceneScale);
-        let scene_filename = config!(configuration, SceneFilename);
+        let scene_filenames = config!(configuration, SceneFilenames);
 
         let density_map_enabled = config!(configuration, DensityMapEnabled);
         let density_map_min_threshold = config!(configuration, DensityMapMinThreshold);
         let density_map_max_threshold = config!(configuration, DensityMapMaxThreshold);
+        let density_method = optional_config!(configuration, DensityMapMethod).unwrap_or(DensityMethod::Kernel);
+        let paced = match optional_config!(configuration, RunModeCfgWrap) {
+            Some(RunMode::RealTime) => true,
+            _ => false
+        };
 
-        Output{ scene_file_name: scene_filename, scene_scale: scene_scale,
+        Output{ scene_file_names: scene_filenames, scene_scale: scene_scale,
                 density_map_enabled: density_map_enabled, density_map_min_threshold: density_map_min_threshold,
-                density_map_max_threshold: density_map_max_threshold, ticks_without_density: 0 }
+                density_map_max_threshold: density_map_max_threshold, density_method: density_method,
+                voronoi: Voronoi::new(configuration), ticks_without_density: 0, paced: paced }
     }
 
-    pub fn send_init(&self) {
+    pub fn send_init(&self, simulation: &Simulation) {
         let mut out = ::std::io::stdout();
-        self.write_string(&mut out, &self.scene_file_name);
+        self.write_u8(&mut out, self.scene_file_names.len() as u8);
+        for scene_file_name in self.scene_file_names.iter() {
+            self.write_string(&mut out, scene_file_name);
+        }
         self.write_f64(&mut out, self.scene_scale);
         self.write_f64(&mut out, self.density_map_min_threshold);
         self.write_f64(&mut out, self.density_map_max_threshold);
+
+        let active_forces = simulation.active_forces();
+        self.write_u8(&mut out, active_forces.len() as u8);
+        for &(name, weight) in active_forces.iter() {
+            self.write_string(&mut out, &name.to_string());
+            self.write_f64(&mut out, weight);
+        }
+        self.write_u8(&mut out, self.paced as u8);
     }
 
     pub fn dump_state(&mut self, simulation: &Simulation) {
@@ -52,7 +84,13 @@ impl Output {
 
         if self.density_map_enabled {
             if self.ticks_without_density == 0 {
-                self.dump_density_map(&mut out, &simulation.scene.get_density_map());
+                for floor in 0..simulation.scene.floors.len() {
+                    let density_map = match self.density_method {
+                        DensityMethod::Kernel => simulation.scene.get_density_map(floor as u8),
+                        DensityMethod::Voronoi => self.voronoi.density_map(&simulation.scene, floor as u8),
+                    };
+                    self.dump_density_map(&mut out, floor as u8, &density_map);
+                }
                 self.ticks_without_density = (1_f64 / simulation.time.tick).ceil() as u32;
             } else {
                 self.ticks_without_density -= 1;
@@ -75,6 +113,64 @@ impl Output {
         self.write_f64(&mut out, statistic_item.current_avg());
         self.write_f64(&mut out, statistic_item.current_variance());
         self.write_f64(&mut out, statistic_item.current_std_deviation());
+
+        let ref statistic_items_by_floor = simulation.statistics.travel_time_by_floor;
+        self.write_u8(&mut out, statistic_items_by_floor.len() as u8);
+        for statistic_item in statistic_items_by_floor.iter() {
+            self.write_u32(&mut out, statistic_item.count);
+            self.write_f64(&mut out, statistic_item.current_avg());
+        }
+
+        let ref service_points = simulation.statistics.service_points;
+        self.write_u8(&mut out, service_points.len() as u8);
+        for service_point in service_points.iter() {
+            self.write_string(&mut out, &service_point.name);
+            self.write_u32(&mut out, service_point.waiting_time.count);
+            self.write_f64(&mut out, service_point.waiting_time.current_avg());
+            self.write_f64(&mut out, service_point.waiting_time.max);
+            self.write_f64(&mut out, service_point.queue_length.current_avg());
+            self.write_f64(&mut out, service_point.queue_length.max);
+        }
+
+        match simulation.statistics.hazard_exposure {
+            Some(ref statistic_item) => {
+                self.write_u8(&mut out, 1);
+                self.write_u32(&mut out, statistic_item.count);
+                self.write_f64(&mut out, statistic_item.current_avg());
+                self.write_f64(&mut out, statistic_item.max);
+            },
+            None => self.write_u8(&mut out, 0)
+        }
+
+        let ref spawn_areas = simulation.statistics.spawn_areas;
+        self.write_u8(&mut out, spawn_areas.len() as u8);
+        for spawn_area in spawn_areas.iter() {
+            self.write_u8(&mut out, spawn_area.spawn_id);
+            self.write_u32(&mut out, spawn_area.waiting_time.count);
+            self.write_f64(&mut out, spawn_area.waiting_time.current_avg());
+            self.write_f64(&mut out, spawn_area.waiting_time.max);
+            self.write_f64(&mut out, spawn_area.backlog_size.current_avg());
+            self.write_f64(&mut out, spawn_area.backlog_size.max);
+        }
+
+        match simulation.statistics.stuck {
+            Some(ref stuck) => {
+                self.write_u8(&mut out, 1);
+                self.write_u32(&mut out, stuck.flagged);
+                self.write_u32(&mut out, stuck.removed);
+            },
+            None => self.write_u8(&mut out, 0)
+        }
+
+        let termination_reason = simulation.statistics.termination_reason.as_ref().map_or("stopped", |reason| reason.name());
+        self.write_string(&mut out, &termination_reason.to_string());
+
+        for statistic_item in [&simulation.statistics.travel_distance, &simulation.statistics.travel_speed].iter() {
+            self.write_f64(&mut out, statistic_item.min);
+            self.write_f64(&mut out, statistic_item.max);
+            self.write_f64(&mut out, statistic_item.current_avg());
+            self.write_f64(&mut out, statistic_item.current_std_deviation());
+        }
     }
 
     fn dump_current_time(&mut self, mut out: &mut Write, simulation: &Simulation) {
@@ -91,11 +187,14 @@ impl Output {
             self.write_u16(out, person.coordinates.x.round() as u16);
             self.write_u16(out, person.coordinates.y.round() as u16);
             self.write_f64(out, person.heading);
+            self.write_f64(out, person.radius);
+            self.write_u8(out, person.floor);
         }
     }
 
-    fn dump_density_map(&self, mut out: &mut Write, density_map: &Vec<Vec<f64>>) {
+    fn dump_density_map(&self, mut out: &mut Write, floor: u8, density_map: &Vec<Vec<f64>>) {
         self.write_u8(&mut out, DENSITY_MAP_TYPE);
+        self.write_u8(&mut out, floor);
         let mut values_to_write = Vec::new();
         for i in 0..density_map.len() {
             for j in 0..density_map[i].len() {
@@ -117,12 +216,12 @@ impl Output {
     fn write_string(&self, out: &mut Write, string: &String) {
         let string_length = string.len();
         self.write_u16(out, string_length as u16);
-        out.write(string.as_bytes()).ok().expect("Can't write to file");
+        out.write_all(string.as_bytes()).ok().expect("Can't write to file");
     }
 
     fn write_u8(&self, out: &mut Write, num: u8) {
         let buf = [num];
-        out.write(&buf).ok().expect("Can't write to file");
+        out.write_all(&buf).ok().expect("Can't write to file");
     }
 
     fn write_u16(&self, out: &mut Write, num: u16) {
@@ -130,7 +229,7 @@ impl Output {
             ((num >> 8) & 0xFF) as u8,
             (num & 0xFF) as u8,
         ];
-        out.write(&buf).ok().expect("Can't write to file");
+        out.write_all(&buf).ok().expect("Can't write to file");
     }
 
     fn write_u32(&self, out: &mut Write, num: u32) {
@@ -140,11 +239,26 @@ impl Output {
             ((num >> 8) & 0xFF) as u8,
             (num & 0xFF) as u8,
         ];
-        out.write(&buf).ok().expect("Can't write to file");
+        out.write_all(&buf).ok().expect("Can't write to file");
     }
 
     fn write_f64(&self, out: &mut Write, num: f64) {
         let buf : [u8; 8] = unsafe { std::mem::transmute(num) };
-        out.write(&buf).ok().expect("Can't write to file");
+        out.write_all(&buf).ok().expect("Can't write to file");
+    }
+}
+
+impl SimulationObserver for Output {
+    fn on_start(&mut self, simulation: &Simulation) {
+        debug!("Sending init message to output");
+        self.send_init(simulation);
+    }
+
+    fn on_tick(&mut self, simulation: &Simulation) {
+        self.dump_state(simulation);
+    }
+
+    fn on_finish(&mut self, simulation: &Simulation) {
+        self.dump_statistics(simulation);
     }
 }
@@ -0,0 +1,100 @@
+extern crate anymap;
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::prelude::*;
+
+use self::anymap::AnyMap;
+
+use ::simulation::Simulation;
+use ::simulation::observer::SimulationObserver;
+use ::simulation::voronoi::Voronoi;
+
+// writes positions of everyone on scene as `id frame x y` lines in meters, like pedestrian experiment datasets do
+pub struct TrajectoryLog {
+    file: BufWriter<File>,
+    // seconds between frames
+    interval: f64,
+    // z is written only if floors have height
+    floor_height: Option<f64>,
+    orientation: bool,
+    // personal voronoi density is written if present
+    voronoi: Option<Voronoi>,
+    next_frame: u64,
+}
+
+impl TrajectoryLog {
+    pub fn new(configuration: &AnyMap, tick: f64) -> Option<TrajectoryLog> {
+        let filename = match optional_config!(configuration, TrajectoryFilename) {
+            Some(filename) => filename,
+            None => return None
+        };
+        let file = File::create(filename).ok().expect("Can't create trajectory file");
+        // frames can't be more frequent than ticks
+        let interval = optional_config!(configuration, TrajectoryInterval).unwrap_or(tick).max(tick);
+        Some(TrajectoryLog{ file: BufWriter::new(file), interval: interval,
+                            floor_height: optional_config!(configuration, TrajectoryFloorHeight),
+                            orientation: optional_config!(configuration, TrajectoryOrientation).unwrap_or(false),
+                            voronoi: if optional_config!(configuration, TrajectoryDensity).unwrap_or(false) {
+                                Some(Voronoi::new(configuration))
+                            } else {
+                                None
+                            },
+                            next_frame: 0 })
+    }
+
+    fn write_header(&mut self) {
+        let mut columns = String::from("# id frame x/m y/m");
+        if self.floor_height.is_some() {
+            columns.push_str(" z/m");
+        }
+        if self.orientation {
+            columns.push_str(" heading/deg");
+        }
+        if self.voronoi.is_some() {
+            columns.push_str(" density/m-2");
+        }
+        writeln!(self.file, "# framerate: {}\n{}", 1_f64 / self.interval, columns).ok().expect("Can't write to trajectory file");
+    }
+
+    fn write_frame(&mut self, simulation: &Simulation) {
+        // frame numbers follow simulated time, so restored simulation continues the same numbering
+        let frame = (simulation.time.current_time / self.interval + 1e-6_f64).floor() as u64;
+        if frame < self.next_frame {
+            return;
+        }
+        self.next_frame = frame + 1;
+
+        let scale = simulation.scene.scale;
+        let densities = self.voronoi.as_ref().map(|voronoi| voronoi.personal_densities(&simulation.scene));
+        for (i, person) in simulation.scene.people.iter().enumerate() {
+            let mut line = format!("{} {} {:.4} {:.4}", person.id, frame, person.coordinates.x * scale, person.coordinates.y * scale);
+            if let Some(floor_height) = self.floor_height {
+                line.push_str(&format!(" {:.4}", person.floor as f64 * floor_height));
+            }
+            if self.orientation {
+                let heading = (person.heading.to_degrees() % 360_f64 + 360_f64) % 360_f64;
+                line.push_str(&format!(" {:.2}", heading));
+            }
+            if let Some(ref densities) = densities {
+                line.push_str(&format!(" {:.4}", densities[i]));
+            }
+            writeln!(self.file, "{}", line).ok().expect("Can't write to trajectory file");
+        }
+    }
+}
+
+impl SimulationObserver for TrajectoryLog {
+    fn on_start(&mut self, simulation: &Simulation) {
+        self.write_header();
+        self.write_frame(simulation);
+    }
+
+    fn on_tick(&mut self, simulation: &Simulation) {
+        self.write_frame(simulation);
+    }
+
+    fn on_finish(&mut self, _simulation: &Simulation) {
+        self.file.flush().ok().expect("Can't write to trajectory file");
+    }
+}
@@ -0,0 +1,62 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::prelude::*;
+
+use ::simulation::Simulation;
+use ::simulation::observer::SimulationObserver;
+use ::simulation::person::Person;
+
+const HEADER: &'static str = "id,path_id,profile_id,status,spawn_time,target_times,exit_time,distance,stuck_time,\
+                              target_speed,repulsion_coeff,forward_fov,backward_fov,noise_level";
+
+// writes a csv row for every person who left the scene or stayed there till the end
+pub struct TripLog {
+    file: BufWriter<File>,
+}
+
+impl TripLog {
+    pub fn new(filename: &str) -> TripLog {
+        let file = File::create(filename).ok().expect("Can't create trips file");
+        let mut file = BufWriter::new(file);
+        writeln!(file, "{}", HEADER).ok().expect("Can't write to trips file");
+        TripLog{ file: file }
+    }
+
+    // target times are separated by semicolons, distance is in meters
+    fn format_row(person: &Person, status: &str, exit_time: Option<f64>, scale: f64) -> String {
+        let target_times: Vec<String> = person.target_times.iter().map(|time| time.to_string()).collect();
+        let ref params = person.forces_params;
+        format!("{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                person.id, person.path_id, person.profile_id, status, person.spawned_at, target_times.join(";"),
+                exit_time.map_or(String::new(), |time| time.to_string()), person.distance_walked * scale, person.stuck_time,
+                params.target_speed, params.repulsion_coeff, params.forward_fov, params.backward_fov, params.noise_level)
+    }
+
+    fn write_row(&mut self, row: String) {
+        writeln!(self.file, "{}", row).ok().expect("Can't write to trips file");
+    }
+}
+
+impl SimulationObserver for TripLog {
+    fn on_exit(&mut self, simulation: &Simulation, person: &Person) {
+        let row = TripLog::format_row(person, "exited", Some(simulation.time.current_time), simulation.scene.scale);
+        self.write_row(row);
+    }
+
+    fn on_remove(&mut self, simulation: &Simulation, person: &Person) {
+        let row = TripLog::format_row(person, "removed", None, simulation.scene.scale);
+        self.write_row(row);
+    }
+
+    fn on_finish(&mut self, simulation: &Simulation) {
+        for person in simulation.scene.people.iter() {
+            let row = TripLog::format_row(person, "on_scene", None, simulation.scene.scale);
+            self.write_row(row);
+        }
+        for person in simulation.scene.people_in_connectors().into_iter() {
+            let row = TripLog::format_row(person, "in_connector", None, simulation.scene.scale);
+            self.write_row(row);
+        }
+        self.file.flush().ok().expect("Can't write to trips file");
+    }
+}
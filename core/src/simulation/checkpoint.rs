@@ -0,0 +1,319 @@
+use std::io::prelude::*;
+
+use ::simulation::person::Person;
+use ::simulation::scene::Area;
+use ::simulation::forces::PersonForcesParams;
+use ::simulation::kinematics::PersonKinematics;
+use ::simulation::stuck::ProgressMark;
+use ::simulation::services::ServiceState;
+use ::simulation::statistics::NumStatisticItem;
+
+use ::utils::linelg::Line;
+use ::utils::linelg::Point;
+use ::utils::linelg::Vector;
+
+const CHECKPOINT_MAGIC: &'static [u8; 4] = b"CSCP";
+const CHECKPOINT_VERSION: u8 = 1;
+
+// snapshot contains only state changing during simulation, everything else is taken from the same configuration on restore
+pub struct CheckpointWriter<'a> {
+    out: &'a mut Write,
+}
+
+impl<'a> CheckpointWriter<'a> {
+    pub fn new(out: &'a mut Write) -> CheckpointWriter<'a> {
+        let mut writer = CheckpointWriter{ out: out };
+        writer.write_bytes(CHECKPOINT_MAGIC);
+        writer.write_u8(CHECKPOINT_VERSION);
+        writer
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.out.write_all(bytes).ok().expect("Can't write checkpoint");
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.write_bytes(&[value]);
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.write_u64(value as u64);
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_u64(value as u64);
+    }
+
+    pub fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        let mut bytes = [0_u8; 8];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = (value >> (8 * i)) as u8;
+        }
+        self.write_bytes(&bytes);
+    }
+
+    // bit exact, so restored simulation continues exactly the same way
+    pub fn write_f64(&mut self, value: f64) {
+        self.write_u64(value.to_bits());
+    }
+
+    pub fn write_optional_f64(&mut self, value: Option<f64>) {
+        self.write_bool(value.is_some());
+        if let Some(value) = value {
+            self.write_f64(value);
+        }
+    }
+
+    pub fn write_point(&mut self, point: &Point) {
+        self.write_f64(point.x);
+        self.write_f64(point.y);
+    }
+
+    pub fn write_vector(&mut self, vector: &Vector) {
+        self.write_f64(vector.x);
+        self.write_f64(vector.y);
+    }
+
+    pub fn write_line(&mut self, line: &Line) {
+        self.write_point(&line.from);
+        self.write_point(&line.to);
+    }
+
+    pub fn write_area(&mut self, area: &Area) {
+        self.write_point(&area.p0);
+        self.write_point(&area.p1);
+        self.write_u8(area.sequence_no);
+        self.write_u8(area.floor);
+        self.write_bool(area.enabled);
+    }
+
+    pub fn write_statistic_item(&mut self, item: &NumStatisticItem) {
+        self.write_f64(item.min);
+        self.write_f64(item.max);
+        self.write_f64(item.sum);
+        self.write_f64(item.sum_of_squares);
+        self.write_u32(item.count);
+    }
+
+    pub fn write_person(&mut self, person: &Person) {
+        self.write_u32(person.id);
+        self.write_point(&person.coordinates);
+        self.write_f64(person.heading);
+        self.write_vector(&person.velocity);
+        self.write_u8(person.path_id);
+        self.write_u8(person.profile_id);
+        self.write_f64(person.radius);
+        self.write_u8(person.floor);
+        self.write_area(&person.current_target_area);
+        self.write_u16(person.current_target_index);
+        let ref forces_params = person.forces_params;
+        self.write_f64(forces_params.target_speed);
+        self.write_f64(forces_params.repulsion_coeff);
+        self.write_f64(forces_params.forward_fov);
+        self.write_f64(forces_params.backward_fov);
+        self.write_f64(forces_params.noise_level);
+        let ref kinematics = person.kinematics;
+        self.write_f64(kinematics.turn_rate);
+        self.write_optional_f64(kinematics.max_speed);
+        self.write_optional_f64(kinematics.max_acceleration);
+        match person.service_state {
+            ServiceState::Walking => self.write_u8(0),
+            ServiceState::Queued{ service_index, queued_at } => {
+                self.write_u8(1);
+                self.write_usize(service_index);
+                self.write_f64(queued_at);
+            },
+            ServiceState::Called{ service_index } => {
+                self.write_u8(2);
+                self.write_usize(service_index);
+            },
+            ServiceState::InService{ service_index, remaining } => {
+                self.write_u8(3);
+                self.write_usize(service_index);
+                self.write_f64(remaining);
+            },
+            ServiceState::Served => self.write_u8(4),
+        }
+        self.write_f64(person.visibility);
+        self.write_f64(person.hazard_exposure);
+        self.write_vector(&person.noise);
+        self.write_bool(person.progress.is_some());
+        if let Some(ref mark) = person.progress {
+            self.write_f64(mark.time);
+            self.write_f64(mark.distance);
+            self.write_u16(mark.target_index);
+            self.write_u8(mark.floor);
+        }
+        self.write_f64(person.spawned_at);
+        self.write_f64(person.distance_walked);
+        self.write_usize(person.target_times.len());
+        for time in person.target_times.iter() {
+            self.write_f64(*time);
+        }
+        self.write_f64(person.stuck_time);
+    }
+
+    pub fn write_people(&mut self, people: &Vec<Person>) {
+        self.write_usize(people.len());
+        for person in people.iter() {
+            self.write_person(person);
+        }
+    }
+}
+
+pub struct CheckpointReader<'a> {
+    input: &'a mut Read,
+}
+
+impl<'a> CheckpointReader<'a> {
+    pub fn new(input: &'a mut Read) -> CheckpointReader<'a> {
+        let mut reader = CheckpointReader{ input: input };
+        let mut magic = [0_u8; 4];
+        reader.read_bytes(&mut magic);
+        if &magic != CHECKPOINT_MAGIC {
+            panic!("Not a checkpoint file");
+        }
+        let version = reader.read_u8();
+        if version != CHECKPOINT_VERSION {
+            panic!("Unsupported checkpoint version: {}", version);
+        }
+        reader
+    }
+
+    fn read_bytes(&mut self, bytes: &mut [u8]) {
+        self.input.read_exact(bytes).ok().expect("Can't read checkpoint");
+    }
+
+    pub fn read_u8(&mut self) -> u8 {
+        let mut bytes = [0_u8; 1];
+        self.read_bytes(&mut bytes);
+        bytes[0]
+    }
+
+    pub fn read_bool(&mut self) -> bool {
+        self.read_u8() != 0
+    }
+
+    pub fn read_u16(&mut self) -> u16 {
+        self.read_u64() as u16
+    }
+
+    pub fn read_u32(&mut self) -> u32 {
+        self.read_u64() as u32
+    }
+
+    pub fn read_usize(&mut self) -> usize {
+        self.read_u64() as usize
+    }
+
+    pub fn read_u64(&mut self) -> u64 {
+        let mut bytes = [0_u8; 8];
+        self.read_bytes(&mut bytes);
+        bytes.iter().enumerate().fold(0_u64, |value, (i, byte)| value | (*byte as u64) << (8 * i))
+    }
+
+    pub fn read_f64(&mut self) -> f64 {
+        f64::from_bits(self.read_u64())
+    }
+
+    pub fn read_optional_f64(&mut self) -> Option<f64> {
+        if self.read_bool() {
+            Some(self.read_f64())
+        } else {
+            None
+        }
+    }
+
+    pub fn read_point(&mut self) -> Point {
+        let x = self.read_f64();
+        let y = self.read_f64();
+        Point::new(x, y)
+    }
+
+    pub fn read_vector(&mut self) -> Vector {
+        let x = self.read_f64();
+        let y = self.read_f64();
+        Vector::new(x, y)
+    }
+
+    pub fn read_line(&mut self) -> Line {
+        let from = self.read_point();
+        let to = self.read_point();
+        Line::new(from, to)
+    }
+
+    pub fn read_area(&mut self) -> Area {
+        let p0 = self.read_point();
+        let p1 = self.read_point();
+        let sequence_no = self.read_u8();
+        let floor = self.read_u8();
+        let mut area = Area::new(p0, p1, sequence_no, floor);
+        area.enabled = self.read_bool();
+        area
+    }
+
+    pub fn read_statistic_item(&mut self) -> NumStatisticItem {
+        NumStatisticItem{ min: self.read_f64(), max: self.read_f64(), sum: self.read_f64(),
+                          sum_of_squares: self.read_f64(), count: self.read_u32() }
+    }
+
+    pub fn read_person(&mut self) -> Person {
+        let id = self.read_u32();
+        let coordinates = self.read_point();
+        let heading = self.read_f64();
+        let velocity = self.read_vector();
+        let path_id = self.read_u8();
+        let profile_id = self.read_u8();
+        let radius = self.read_f64();
+        let floor = self.read_u8();
+        let current_target_area = self.read_area();
+        let current_target_index = self.read_u16();
+        let forces_params = PersonForcesParams{ target_speed: self.read_f64(), repulsion_coeff: self.read_f64(),
+                                                forward_fov: self.read_f64(), backward_fov: self.read_f64(),
+                                                noise_level: self.read_f64() };
+        let kinematics = PersonKinematics{ turn_rate: self.read_f64(), max_speed: self.read_optional_f64(),
+                                           max_acceleration: self.read_optional_f64() };
+        let service_state = match self.read_u8() {
+            0 => ServiceState::Walking,
+            1 => ServiceState::Queued{ service_index: self.read_usize(), queued_at: self.read_f64() },
+            2 => ServiceState::Called{ service_index: self.read_usize() },
+            3 => ServiceState::InService{ service_index: self.read_usize(), remaining: self.read_f64() },
+            4 => ServiceState::Served,
+            state => panic!("Unknown service state in checkpoint: {}", state)
+        };
+        Person{ id: id, coordinates: coordinates, heading: heading, velocity: velocity, path_id: path_id,
+                profile_id: profile_id, radius: radius, floor: floor, current_target_area: current_target_area,
+                current_target_index: current_target_index, forces_params: forces_params, kinematics: kinematics,
+                service_state: service_state,
+                visibility: self.read_f64(), hazard_exposure: self.read_f64(), noise: self.read_vector(),
+                progress: self.read_progress_mark(), spawned_at: self.read_f64(), distance_walked: self.read_f64(),
+                target_times: self.read_f64_vec(), stuck_time: self.read_f64() }
+    }
+
+    fn read_f64_vec(&mut self) -> Vec<f64> {
+        let count = self.read_usize();
+        (0..count).map(|_i| self.read_f64()).collect()
+    }
+
+    fn read_progress_mark(&mut self) -> Option<ProgressMark> {
+        if self.read_bool() {
+            Some(ProgressMark{ time: self.read_f64(), distance: self.read_f64(),
+                               target_index: self.read_u16(), floor: self.read_u8() })
+        } else {
+            None
+        }
+    }
+
+    pub fn read_people(&mut self) -> Vec<Person> {
+        let count = self.read_usize();
+        (0..count).map(|_i| self.read_person()).collect()
+    }
+}
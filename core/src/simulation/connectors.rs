@@ -0,0 +1,237 @@
+use ::configuration::ConnectorKind;
+
+use ::simulation::person::Person;
+use ::simulation::scene::Area;
+use ::simulation::checkpoint::CheckpointReader;
+use ::simulation::checkpoint::CheckpointWriter;
+
+use ::utils::linelg::Point;
+
+// lift waits for more people after the first one boarded
+const LIFT_DOORS_TIME: f64 = 5_f64;
+
+#[derive(Debug,Clone)]
+enum LiftState {
+    Loading{ departure_in: Option<f64> },
+    Moving{ remaining: f64 },
+    Returning{ remaining: f64 },
+}
+
+pub struct Connector {
+    pub kind: ConnectorKind,
+    pub entry: Area,
+    pub exit: Area,
+    traversal_time: f64,
+    capacity: u16,
+    // people inside connector with time left to reach exit
+    in_transit: Vec<(Person, f64)>,
+    // people who reached exit but had no free place there yet
+    waiting_at_exit: Vec<Person>,
+    lift_state: LiftState,
+}
+
+impl Connector {
+    pub fn new(configuration: &::configuration::Connector) -> Connector {
+        if configuration.capacity == 0 {
+            // nobody could ever board, people routed through it would wait forever
+            panic!("Connector capacity must be positive in config: {:?}", configuration);
+        }
+        let entry = Area::new(
+            Point::new(configuration.from_x0 as f64, configuration.from_y0 as f64),
+            Point::new(configuration.from_x1 as f64, configuration.from_y1 as f64),
+            0, configuration.from_floor
+        );
+        let exit = Area::new(
+            Point::new(configuration.to_x0 as f64, configuration.to_y0 as f64),
+            Point::new(configuration.to_x1 as f64, configuration.to_y1 as f64),
+            0, configuration.to_floor
+        );
+        Connector{ kind: configuration.kind.clone(), entry: entry, exit: exit,
+                   traversal_time: configuration.traversal_time, capacity: configuration.capacity,
+                   in_transit: Vec::new(), waiting_at_exit: Vec::new(), lift_state: LiftState::Loading{ departure_in: None } }
+    }
+
+    pub fn people_count(&self) -> usize {
+        self.in_transit.len() + self.waiting_at_exit.len()
+    }
+
+    // people inside, both in transit and waiting at exit
+    pub fn people(&self) -> Vec<&Person> {
+        self.in_transit.iter().map(|&(ref person, _remaining)| person).chain(self.waiting_at_exit.iter()).collect()
+    }
+
+    pub fn is_entry(&self, area: &Area) -> bool {
+        area.floor == self.entry.floor && area.has_bounds(&self.entry.p0, &self.entry.p1)
+    }
+
+    pub fn board(&mut self, person: Person) -> Result<(), Person> {
+        match self.kind {
+            ConnectorKind::Stairs => {
+                if self.people_count() >= self.capacity as usize {
+                    return Err(person);
+                }
+                self.in_transit.push((person, self.traversal_time));
+                Ok(())
+            },
+            ConnectorKind::Lift => match self.lift_state {
+                LiftState::Loading{ departure_in } if self.in_transit.len() < self.capacity as usize => {
+                    self.in_transit.push((person, self.traversal_time));
+                    if self.in_transit.len() >= self.capacity as usize {
+                        self.lift_state = LiftState::Moving{ remaining: self.traversal_time };
+                    } else if departure_in.is_none() {
+                        self.lift_state = LiftState::Loading{ departure_in: Some(LIFT_DOORS_TIME) };
+                    }
+                    Ok(())
+                },
+                // lift is away or full, wait for it at the entry
+                _ => Err(person)
+            }
+        }
+    }
+
+    // returns people who reached the exit during this tick
+    pub fn update(&mut self, tick: f64) -> Vec<Person> {
+        let mut arrived = ::std::mem::replace(&mut self.waiting_at_exit, Vec::new());
+        match self.kind {
+            ConnectorKind::Stairs => {
+                let in_transit = ::std::mem::replace(&mut self.in_transit, Vec::new());
+                for (person, remaining) in in_transit.into_iter() {
+                    if remaining - tick <= 0_f64 {
+                        arrived.push(person);
+                    } else {
+                        self.in_transit.push((person, remaining - tick));
+                    }
+                }
+            },
+            ConnectorKind::Lift => {
+                let (next_state, lift_arrived) = match self.lift_state {
+                    LiftState::Loading{ departure_in: Some(departure_in) } if departure_in - tick <= 0_f64 =>
+                        (LiftState::Moving{ remaining: self.traversal_time }, Vec::new()),
+                    LiftState::Loading{ departure_in: Some(departure_in) } =>
+                        (LiftState::Loading{ departure_in: Some(departure_in - tick) }, Vec::new()),
+                    LiftState::Loading{ departure_in: None } =>
+                        (LiftState::Loading{ departure_in: None }, Vec::new()),
+                    LiftState::Moving{ remaining } if remaining - tick <= 0_f64 => {
+                        let lift_arrived = self.in_transit.drain(..).map(|(person, _remaining)| person).collect();
+                        (LiftState::Returning{ remaining: self.traversal_time }, lift_arrived)
+                    },
+                    LiftState::Moving{ remaining } =>
+                        (LiftState::Moving{ remaining: remaining - tick }, Vec::new()),
+                    LiftState::Returning{ remaining } if remaining - tick <= 0_f64 =>
+                        (LiftState::Loading{ departure_in: None }, Vec::new()),
+                    LiftState::Returning{ remaining } =>
+                        (LiftState::Returning{ remaining: remaining - tick }, Vec::new()),
+                };
+                self.lift_state = next_state;
+                arrived.extend(lift_arrived);
+            }
+        }
+        arrived
+    }
+
+    // person who couldn't be placed at exit stays inside till the next tick
+    pub fn hold_at_exit(&mut self, person: Person) {
+        self.waiting_at_exit.push(person);
+    }
+
+    pub fn save_state(&self, writer: &mut CheckpointWriter) {
+        writer.write_usize(self.in_transit.len());
+        for &(ref person, remaining) in self.in_transit.iter() {
+            writer.write_person(person);
+            writer.write_f64(remaining);
+        }
+        writer.write_people(&self.waiting_at_exit);
+        match self.lift_state {
+            LiftState::Loading{ departure_in: None } => writer.write_u8(0),
+            LiftState::Loading{ departure_in: Some(departure_in) } => {
+                writer.write_u8(1);
+                writer.write_f64(departure_in);
+            },
+            LiftState::Moving{ remaining } => {
+                writer.write_u8(2);
+                writer.write_f64(remaining);
+            },
+            LiftState::Returning{ remaining } => {
+                writer.write_u8(3);
+                writer.write_f64(remaining);
+            },
+        }
+    }
+
+    pub fn restore_state(&mut self, reader: &mut CheckpointReader) {
+        let in_transit_count = reader.read_usize();
+        self.in_transit = (0..in_transit_count).map(|_i| (reader.read_person(), reader.read_f64())).collect();
+        self.waiting_at_exit = reader.read_people();
+        self.lift_state = match reader.read_u8() {
+            0 => LiftState::Loading{ departure_in: None },
+            1 => LiftState::Loading{ departure_in: Some(reader.read_f64()) },
+            2 => LiftState::Moving{ remaining: reader.read_f64() },
+            3 => LiftState::Returning{ remaining: reader.read_f64() },
+            state => panic!("Unknown lift state in checkpoint: {}", state)
+        };
+    }
+}
+
+#[cfg(test)]
+fn test_connector(kind: ConnectorKind, capacity: u16) -> Connector {
+    Connector::new(&::configuration::Connector{ kind: kind, from_floor: 0, from_x0: 0, from_y0: 0, from_x1: 10, from_y1: 10,
+                                                to_floor: 1, to_x0: 0, to_y0: 0, to_x1: 10, to_y1: 10,
+                                                traversal_time: 1_f64, capacity: capacity })
+}
+
+#[cfg(test)]
+fn test_person(path_id: u8) -> Person {
+    let area = Area::new(Point::new(0_f64, 0_f64), Point::new(10_f64, 10_f64), 0, 0);
+    Person{ id: 0, coordinates: Point::new(5_f64, 5_f64), heading: 0_f64, velocity: ::utils::linelg::Vector::zero(),
+            path_id: path_id, profile_id: 0, radius: 4_f64, floor: 0, current_target_area: area, current_target_index: 0,
+            forces_params: ::simulation::forces::PersonForcesParams{ target_speed: 20_f64, repulsion_coeff: 1_f64,
+                                                                     forward_fov: 5_f64, backward_fov: 5_f64, noise_level: 0_f64 },
+            kinematics: ::simulation::kinematics::PersonKinematics{ turn_rate: 10_f64, max_speed: None, max_acceleration: None },
+            service_state: ::simulation::services::ServiceState::Walking, visibility: 1_f64, hazard_exposure: 0_f64,
+            noise: ::utils::linelg::Vector::zero(), progress: None, spawned_at: 0_f64, distance_walked: 0_f64,
+            target_times: Vec::new(), stuck_time: 0_f64 }
+}
+
+#[test]
+fn test_stairs_capacity() {
+    let mut stairs = test_connector(ConnectorKind::Stairs, 2);
+    assert!(stairs.board(test_person(0)).is_ok());
+    stairs.update(0.5_f64);
+    assert!(stairs.board(test_person(1)).is_ok());
+    // stairs are full, third person gets back to wait at the entry
+    assert_eq!(stairs.board(test_person(2)).err().map(|person| person.path_id), Some(2));
+    let arrived: Vec<u8> = stairs.update(0.5_f64).iter().map(|person| person.path_id).collect();
+    assert_eq!(arrived, vec![0]);
+    assert!(stairs.board(test_person(2)).is_ok());
+    // people who can't be placed at the exit still take place on stairs
+    let arrived = stairs.update(0.5_f64);
+    assert_eq!(arrived.len(), 1);
+    for person in arrived.into_iter() {
+        stairs.hold_at_exit(person);
+    }
+    assert_eq!(stairs.people_count(), 2);
+    assert!(stairs.board(test_person(3)).is_err());
+}
+
+#[test]
+fn test_lift_departs_full_or_after_doors_close() {
+    let mut lift = test_connector(ConnectorKind::Lift, 2);
+    assert!(lift.board(test_person(0)).is_ok());
+    assert!(lift.board(test_person(1)).is_ok());
+    // full lift leaves right away and nobody boards until it is back
+    assert!(lift.board(test_person(2)).is_err());
+    assert_eq!(lift.update(1_f64).len(), 2);
+    assert!(lift.board(test_person(2)).is_err());
+    lift.update(1_f64);
+    assert!(lift.board(test_person(2)).is_ok());
+    assert!(lift.update(LIFT_DOORS_TIME - 0.5_f64).is_empty());
+    assert!(lift.update(0.5_f64).is_empty());
+    assert!(lift.board(test_person(3)).is_err());
+    assert_eq!(lift.update(1_f64).len(), 1);
+}
+
+#[test]
+#[should_panic]
+fn test_zero_capacity_rejected() {
+    test_connector(ConnectorKind::Stairs, 0);
+}
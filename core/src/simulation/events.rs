@@ -0,0 +1,46 @@
+use ::simulation::person::Person;
+
+use ::utils::linelg::Point;
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum SimulationEventKind {
+    Spawn,
+    // no free place in spawn area, location is its center
+    SpawnFailed{ spawn_id: u8 },
+    TargetReached{ target_index: u16 },
+    Exit,
+    // total force, or chosen velocity under orca model, is too small to move person, he is probably stuck
+    SmallForce{ force: f64 },
+    // person got less than min progress closer to his target during stuck window, meters
+    Stuck{ progress: f64 },
+    // stuck person was taken off the scene
+    Removed,
+}
+
+#[derive(Debug,Clone)]
+pub struct SimulationEvent {
+    pub time: f64,
+    pub kind: SimulationEventKind,
+    pub person_id: Option<u32>,
+    pub floor: u8,
+    // in scene pixels
+    pub location: Point,
+}
+
+impl SimulationEvent {
+    pub fn for_person(time: f64, kind: SimulationEventKind, person: &Person) -> SimulationEvent {
+        SimulationEvent{ time: time, kind: kind, person_id: Some(person.id), floor: person.floor, location: person.coordinates.clone() }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            SimulationEventKind::Spawn => "spawn",
+            SimulationEventKind::SpawnFailed{ .. } => "spawn_failed",
+            SimulationEventKind::TargetReached{ .. } => "target_reached",
+            SimulationEventKind::Exit => "exit",
+            SimulationEventKind::SmallForce{ .. } => "small_force",
+            SimulationEventKind::Stuck{ .. } => "stuck",
+            SimulationEventKind::Removed => "removed",
+        }
+    }
+}
@@ -1,3 +1,10 @@
+extern crate anymap;
+
+use self::anymap::AnyMap;
+
+use ::configuration::DistributionValue;
+use ::configuration::NoiseMode;
+
 use ::simulation::forces::Forceable;
 
 use ::simulation::person::Person;
@@ -5,15 +12,46 @@ use ::simulation::scene::Scene;
 
 use ::utils::linelg::Vector;
 
+const DEFAULT_MAGNITUDE: f64 = 0.1_f64;
+const DEFAULT_CORRELATION_TIME: f64 = 1_f64;
+
+// random force in isotropic direction, drawn anew every tick or changing smoothly in time
 #[derive(Debug)]
-pub struct FluctuationForce;
+pub struct FluctuationForce {
+    power: DistributionValue,
+    mode: NoiseMode,
+    // seconds, relaxation time of ornstein-uhlenbeck process
+    correlation_time: f64,
+}
+
+impl FluctuationForce {
+    pub fn new(configuration: &AnyMap) -> FluctuationForce {
+        let magnitude = optional_config!(configuration, ForcesFluctuationMagnitude).unwrap_or(DEFAULT_MAGNITUDE);
+        let power = optional_config!(configuration, ForcesFluctuationPower)
+            .unwrap_or(DistributionValue::UniformDistributionValue{ from: 0_f64, to: magnitude });
+        FluctuationForce{ power: power,
+                          mode: optional_config!(configuration, ForcesFluctuationMode).unwrap_or(NoiseMode::White),
+                          correlation_time: optional_config!(configuration, ForcesFluctuationCorrelationTime).unwrap_or(DEFAULT_CORRELATION_TIME) }
+    }
+
+    // noise person feels during the next tick
+    pub fn next_noise(&self, noise: Vector, noise_level: f64, tick: f64) -> Vector {
+        let angle = ::utils::distributions::generate_uniform(0_f64, 2_f64 * ::std::f64::consts::PI);
+        let power = ::utils::distributions::generate(&self.power).max(0_f64);
+        let kick = Vector::new(angle.cos(), angle.sin()) * power * noise_level;
+        match self.mode {
+            NoiseMode::White => kick,
+            NoiseMode::OrnsteinUhlenbeck => {
+                // exact discretization, noise keeps the same spread as white one but decorrelates over correlation time
+                let decay = (- tick / self.correlation_time).exp();
+                noise * decay + kick * (1_f64 - decay * decay).sqrt()
+            }
+        }
+    }
+}
 
 impl Forceable for FluctuationForce {
-    fn force_for_person(&self, _person: &Person, _scene: &Scene) -> Vector {
-        let direction = Vector::new(::utils::distributions::generate_uniform(0.0, 1.0),
-                                    ::utils::distributions::generate_uniform(0.0, 1.0));
-        let power = ::utils::distributions::generate_uniform(0.0, 0.1);
-        let force = direction.normalized() * power;
-        force
+    fn force_for_person(&self, person: &Person, _scene: &Scene) -> Vector {
+        person.noise
     }
 }
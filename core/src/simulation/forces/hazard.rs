@@ -0,0 +1,26 @@
+use ::simulation::forces::Forceable;
+
+use ::simulation::person::Person;
+use ::simulation::scene::Scene;
+
+use ::utils::linelg::Vector;
+
+#[derive(Debug)]
+pub struct HazardForce;
+
+impl Forceable for HazardForce {
+    fn force_for_person(&self, person: &Person, scene: &Scene) -> Vector {
+        let hazard = match scene.hazard {
+            Some(ref hazard) => hazard,
+            None => return Vector::zero()
+        };
+        // people move away from higher concentration
+        let force = - hazard.gradient_at(person.floor, &person.coordinates) * hazard.repulsion_coeff;
+        let force_power = force.length().min(4_f64);
+        if force_power != 0_f64 {
+            force.normalized() * force_power
+        } else {
+            force
+        }
+    }
+}
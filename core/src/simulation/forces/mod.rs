@@ -3,29 +3,38 @@ extern crate anymap;
 mod repulsion;
 mod target;
 mod fluctuation;
+mod hazard;
 
 use self::anymap::AnyMap;
 
 use self::repulsion::RepulsionForce;
 use self::target::TargetForce;
 use self::fluctuation::FluctuationForce;
+use self::hazard::HazardForce;
 
 use ::simulation::person::Person;
 use ::simulation::scene::Scene;
+use ::simulation::profiles::Profile;
+
+use ::std::fmt::Debug;
 
 use ::configuration::DistributionValue;
+use ::configuration::ForceKind;
 use ::utils::linelg::Vector;
 
-pub trait Forceable {
+// implement it to add own forces with Simulation::add_force
+pub trait Forceable: Debug {
     fn force_for_person(&self, person: &Person, scene: &Scene) -> Vector;
 }
 
 pub struct Forces {
-    used_forces: Vec<Force>,
+    // active forces with their weights
+    used_forces: Vec<(Force, f64)>,
     target_speed: DistributionValue,
     repulsion_coeff: DistributionValue,
     forward_fov: DistributionValue,
     backward_fov: DistributionValue,
+    noise_level: Option<DistributionValue>,
 }
 
 #[derive(Debug,Clone)]
@@ -34,13 +43,29 @@ pub struct PersonForcesParams {
     pub repulsion_coeff: f64,
     pub forward_fov: f64,
     pub backward_fov: f64,
+    // scales fluctuation force
+    pub noise_level: f64,
 }
 
 #[derive(Debug)]
 pub enum Force {
     Target(TargetForce),
     Repulsion(RepulsionForce),
-    Fluctuation(FluctuationForce)
+    Fluctuation(FluctuationForce),
+    Hazard(HazardForce),
+    Custom{ name: String, force: Box<Forceable> }
+}
+
+impl Force {
+    pub fn name(&self) -> &str {
+        match self {
+            &Force::Target(_) => "target",
+            &Force::Repulsion(_) => "repulsion",
+            &Force::Fluctuation(_) => "fluctuation",
+            &Force::Hazard(_) => "hazard",
+            &Force::Custom{ ref name, .. } => name
+        }
+    }
 }
 
 impl Forceable for Force {
@@ -48,7 +73,9 @@ impl Forceable for Force {
         match self {
             &Force::Target(ref force) => force.force_for_person(person, scene),
             &Force::Repulsion(ref force) => force.force_for_person(person, scene),
-            &Force::Fluctuation(ref force) => force.force_for_person(person, scene)
+            &Force::Fluctuation(ref force) => force.force_for_person(person, scene),
+            &Force::Hazard(ref force) => force.force_for_person(person, scene),
+            &Force::Custom{ ref force, .. } => force.force_for_person(person, scene)
         }
     }
 }
@@ -61,34 +88,82 @@ impl Forces {
         let forward_fov = config!(configuration, FovForward);
         let backward_fov = config!(configuration, FovBackward);
 
-        let used_forces = vec![
-            Force::Target(TargetForce),
-            Force::Repulsion(RepulsionForce),
-            Force::Fluctuation(FluctuationForce),
+        let composition = optional_config!(configuration, ForceComposition).unwrap_or(Vec::new());
+        let hazard_configured = optional_config!(configuration, HazardSources).is_some();
+        let all_forces = vec![
+            (ForceKind::Target, Force::Target(TargetForce), true),
+            (ForceKind::Repulsion, Force::Repulsion(RepulsionForce::new(configuration)), true),
+            (ForceKind::Fluctuation, Force::Fluctuation(FluctuationForce::new(configuration)), true),
+            (ForceKind::Hazard, Force::Hazard(HazardForce), hazard_configured),
         ];
+
+        let mut used_forces = Vec::new();
+        for (kind, force, enabled_by_default) in all_forces.into_iter() {
+            let (enabled, weight) = match composition.iter().find(|item| item.kind == kind) {
+                Some(item) => (item.enabled.unwrap_or(enabled_by_default), item.weight.unwrap_or(1_f64)),
+                None => (enabled_by_default, 1_f64)
+            };
+            if enabled {
+                used_forces.push((force, weight));
+            }
+        }
+        if !hazard_configured && used_forces.iter().any(|&(ref force, _)| force.name() == "hazard") {
+            panic!("Hazard force is enabled, but hazard section is missing");
+        }
         Forces{ used_forces: used_forces, target_speed: target_speed, repulsion_coeff: repulsion_coeff,
-                forward_fov: forward_fov, backward_fov: backward_fov }
+                forward_fov: forward_fov, backward_fov: backward_fov,
+                noise_level: optional_config!(configuration, ForcesFluctuationLevel) }
     }
 
     pub fn total_force_for_person(&self, person: &Person, scene: &Scene) -> Vector {
         let mut total_force = Vector::zero();
-        for force in self.used_forces.iter() {
-            total_force = total_force + force.force_for_person(person, scene);
+        for &(ref force, weight) in self.used_forces.iter() {
+            total_force = total_force + force.force_for_person(person, scene) * weight;
         }
         total_force
     }
 
-    pub fn generate_person_forces_param(&self) -> PersonForcesParams {
+    // everything but repulsion, for models that resolve interactions on their own
+    pub fn preferred_velocity_for_person(&self, person: &Person, scene: &Scene) -> Vector {
+        let mut preferred_velocity = Vector::zero();
+        for &(ref force, weight) in self.used_forces.iter() {
+            match force {
+                &Force::Repulsion(_) => (),
+                _ => preferred_velocity = preferred_velocity + force.force_for_person(person, scene) * weight
+            }
+        }
+        preferred_velocity
+    }
+
+    pub fn update_noise(&self, person: &mut Person, tick: f64) {
+        for &(ref force, _weight) in self.used_forces.iter() {
+            if let &Force::Fluctuation(ref fluctuation) = force {
+                person.noise = fluctuation.next_noise(person.noise, person.forces_params.noise_level, tick);
+            }
+        }
+    }
+
+    pub fn add_force(&mut self, force: Force, weight: f64) {
+        self.used_forces.push((force, weight));
+    }
+
+    pub fn active_forces(&self) -> Vec<(&str, f64)> {
+        self.used_forces.iter().map(|&(ref force, weight)| (force.name(), weight)).collect()
+    }
+
+    pub fn generate_person_forces_param(&self, profile: &Profile) -> PersonForcesParams {
         let mut res = PersonForcesParams{
-            target_speed: ::utils::distributions::generate(&self.target_speed),
-            repulsion_coeff: ::utils::distributions::generate(&self.repulsion_coeff),
-            forward_fov: ::utils::distributions::generate(&self.forward_fov),
-            backward_fov: ::utils::distributions::generate(&self.backward_fov),
+            target_speed: ::utils::distributions::generate(profile.target_speed.as_ref().unwrap_or(&self.target_speed)),
+            repulsion_coeff: ::utils::distributions::generate(profile.repulsion_coeff.as_ref().unwrap_or(&self.repulsion_coeff)),
+            forward_fov: ::utils::distributions::generate(profile.forward_fov.as_ref().unwrap_or(&self.forward_fov)),
+            backward_fov: ::utils::distributions::generate(profile.backward_fov.as_ref().unwrap_or(&self.backward_fov)),
+            noise_level: self.noise_level.as_ref().map_or(1_f64, |noise_level| ::utils::distributions::generate(noise_level)),
         };
         res.target_speed = res.target_speed.max(0.1);
         res.repulsion_coeff = res.repulsion_coeff.max(0.01);
         res.forward_fov = res.forward_fov.max(0.01);
         res.backward_fov = res.backward_fov.max(0.01);
+        res.noise_level = res.noise_level.max(0_f64);
         res
     }
 }
@@ -1,5 +1,9 @@
+extern crate anymap;
+
 use ::std::fmt::Debug;
 
+use self::anymap::AnyMap;
+
 use ::simulation::forces::Forceable;
 
 use ::simulation::person::Person;
@@ -9,24 +13,39 @@ use ::utils::linelg::distance::DistanceTo;
 use ::utils::linelg::Vector;
 use ::utils::linelg::Point;
 
+// some magic numbers
+const DEFAULT_DISTANCE_SQR_THRESHOLD: f64 = 125_f64;
+const DEFAULT_REPULSION_ELLIPSE_R_X: f64 = 1.0_f64;
+const DEFAULT_REPULSION_ELLIPSE_R_Y: f64 = 2.0_f64;
+const DEFAULT_FORCE_CAP: f64 = 4_f64;
+
 #[derive(Debug)]
-pub struct RepulsionForce;
+pub struct RepulsionForce {
+    distance_sqr_threshold: f64,
+    ellipse_r_x: f64,
+    ellipse_r_y: f64,
+    force_cap: f64,
+}
 
 impl RepulsionForce {
-    fn repulsion_from_obstacle<T: Debug>(&self, person: &Person, obstacle: &T, scene_scale: f64) -> Vector where Point: DistanceTo<T> {
-        // some magic numbers
-        const DISTANCE_SQR_THRESHOLD: f64 = 125_f64;
-        const REPULSION_ELLIPSE_R_X: f64 = 1.0_f64;
-        const REPULSION_ELLIPSE_R_Y: f64 = 2.0_f64;
+    pub fn new(configuration: &AnyMap) -> RepulsionForce {
+        RepulsionForce{
+            distance_sqr_threshold: optional_config!(configuration, ForcesRepulsionDistanceSqrThreshold).unwrap_or(DEFAULT_DISTANCE_SQR_THRESHOLD),
+            ellipse_r_x: optional_config!(configuration, ForcesRepulsionEllipseRx).unwrap_or(DEFAULT_REPULSION_ELLIPSE_R_X),
+            ellipse_r_y: optional_config!(configuration, ForcesRepulsionEllipseRy).unwrap_or(DEFAULT_REPULSION_ELLIPSE_R_Y),
+            force_cap: optional_config!(configuration, ForcesRepulsionCap).unwrap_or(DEFAULT_FORCE_CAP),
+        }
+    }
 
+    fn repulsion_from_obstacle<T: Debug>(&self, person: &Person, obstacle: &T, contact_distance: f64, scene_scale: f64) -> Vector where Point: DistanceTo<T> {
         let nearest_point = person.coordinates.nearest_point(obstacle);
         let direction = nearest_point - person.coordinates;
         let direction_length_sqr_in_meters = direction.length_sqr() * scene_scale;
-        if direction_length_sqr_in_meters < DISTANCE_SQR_THRESHOLD && direction.length_sqr() != 0.0 {
+        if direction_length_sqr_in_meters < self.distance_sqr_threshold && direction.length_sqr() != 0.0 {
             let angle = direction.y.atan2(direction.x);
-            let ellipse_coeff = ::utils::linelg::ellipse_sqr_radius_at_angle(REPULSION_ELLIPSE_R_X, REPULSION_ELLIPSE_R_Y, angle);
-            // let distance_coeff = 1_f64 / ((direction_length_sqr_in_meters.sqrt() - ::simulation::scene::APPROX_PERSON_RADIUS) * 5_f64);
-            let distance_coeff = (- 1_f64 / 1_f64 * (direction_length_sqr_in_meters.sqrt() - ::simulation::scene::APPROX_PERSON_RADIUS) + 3_f64).max(0_f64).min(3_f64);
+            let ellipse_coeff = ::utils::linelg::ellipse_sqr_radius_at_angle(self.ellipse_r_x, self.ellipse_r_y, angle);
+            // let distance_coeff = 1_f64 / ((direction_length_sqr_in_meters.sqrt() - contact_distance) * 5_f64);
+            let distance_coeff = (- 1_f64 / 1_f64 * (direction_length_sqr_in_meters.sqrt() - contact_distance) + 3_f64).max(0_f64).min(3_f64);
             let fov_coeff = person.fov_coeff(nearest_point);
             - direction.normalized() * distance_coeff * fov_coeff * ellipse_coeff.sqrt()
         } else {
@@ -40,13 +59,14 @@ impl Forceable for RepulsionForce {
     fn force_for_person(&self, person: &Person, scene: &Scene) -> Vector {
         let repulsion_coeff = person.forces_params.repulsion_coeff;
         let mut force = Vector::zero();
-        for obstacle in scene.geometry.iter() {
-            force = force + self.repulsion_from_obstacle(&person, obstacle, scene.scale);
+        for obstacle in scene.floors[person.floor as usize].geometry.iter() {
+            force = force + self.repulsion_from_obstacle(&person, obstacle, person.radius, scene.scale);
         }
-        for other_person in scene.people.iter() {
-            force = force + self.repulsion_from_obstacle(&person, &other_person.coordinates, scene.scale) / 2_f64;
+        for other_person in scene.people.iter().filter(|other_person| other_person.floor == person.floor) {
+            let contact_distance = (person.radius + other_person.radius) / 2_f64;
+            force = force + self.repulsion_from_obstacle(&person, &other_person.coordinates, contact_distance, scene.scale) / 2_f64;
         }
-        let force_power = force.length().min(4_f64);
+        let force_power = force.length().min(self.force_cap);
         if force_power != 0_f64 {
             force = force.normalized() * force_power;
         }
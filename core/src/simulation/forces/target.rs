@@ -9,9 +9,13 @@ use ::utils::linelg::Vector;
 pub struct TargetForce;
 
 impl Forceable for TargetForce {
-    fn force_for_person(&self, person: &Person, _scene: &Scene) -> Vector {
-        let target_speed = person.forces_params.target_speed;
+    fn force_for_person(&self, person: &Person, scene: &Scene) -> Vector {
+        let target_speed = person.forces_params.target_speed * scene.speed_factor_for(person);
         let direction = person.current_target_point() - person.coordinates;
+        if direction.length() == 0_f64 {
+            // person stands inside his target, e.g. waiting in queue
+            return Vector::zero();
+        }
         let force = direction.normalized() * target_speed;
         force
     }
@@ -0,0 +1,232 @@
+extern crate anymap;
+
+use self::anymap::AnyMap;
+
+use ::configuration::HazardSource;
+
+use ::simulation::checkpoint::CheckpointReader;
+use ::simulation::checkpoint::CheckpointWriter;
+
+use ::utils::linelg::Line;
+use ::utils::linelg::Point;
+use ::utils::linelg::Vector;
+
+// explicit diffusion step is stable while spread per tick stays below this
+const MAX_SPREAD_PER_TICK: f64 = 0.25_f64;
+// hazard never stops people or blinds them completely
+const MIN_SPEED_FACTOR: f64 = 0.1_f64;
+const MIN_VISIBILITY: f64 = 0.1_f64;
+
+struct HazardGrid {
+    columns: usize,
+    rows: usize,
+    values: Vec<f64>,
+    // walls between cell and its right/lower neighbour
+    blocked_right: Vec<bool>,
+    blocked_down: Vec<bool>,
+}
+
+impl HazardGrid {
+    fn new(columns: usize, rows: usize) -> HazardGrid {
+        HazardGrid{ columns: columns, rows: rows, values: vec![0_f64; columns * rows],
+                    blocked_right: vec![false; columns * rows], blocked_down: vec![false; columns * rows] }
+    }
+
+    fn value(&self, column: usize, row: usize) -> f64 {
+        self.values[row * self.columns + column]
+    }
+}
+
+pub struct Hazard {
+    grids: Vec<HazardGrid>,
+    sources: Vec<HazardSource>,
+    // cell size in pixels
+    cell_size: f64,
+    diffusion: f64,
+    decay: f64,
+    speed_reduction: f64,
+    fov_reduction: f64,
+    pub repulsion_coeff: f64,
+    scale: f64,
+}
+
+impl Hazard {
+    pub fn new(configuration: &AnyMap, floors_geometry: Vec<&Vec<Line>>, width: u16, height: u16, scale: f64) -> Option<Hazard> {
+        let sources = match optional_config!(configuration, HazardSources) {
+            Some(sources) => sources,
+            None => return None
+        };
+        let cell_size = config!(configuration, HazardCellSize) / scale;
+        let columns = (width as f64 / cell_size).ceil() as usize;
+        let rows = (height as f64 / cell_size).ceil() as usize;
+
+        let mut hazard = Hazard{ grids: Vec::new(), sources: sources, cell_size: cell_size,
+                                 diffusion: config!(configuration, HazardDiffusion),
+                                 decay: config!(configuration, HazardDecay),
+                                 speed_reduction: config!(configuration, HazardSpeedReduction),
+                                 fov_reduction: config!(configuration, HazardFovReduction),
+                                 repulsion_coeff: config!(configuration, HazardRepulsionCoeff),
+                                 scale: scale };
+        for (floor, geometry) in floors_geometry.iter().enumerate() {
+            hazard.grids.push(HazardGrid::new(columns, rows));
+            hazard.update_walls(floor as u8, geometry);
+        }
+        Some(hazard)
+    }
+
+    pub fn update_walls(&mut self, floor: u8, geometry: &Vec<Line>) {
+        let cell_size = self.cell_size;
+        let ref mut grid = self.grids[floor as usize];
+        for row in 0..grid.rows {
+            for column in 0..grid.columns {
+                let center = Hazard::cell_center(cell_size, column, row);
+                let right = Line::new(center, Hazard::cell_center(cell_size, column + 1, row));
+                let down = Line::new(center, Hazard::cell_center(cell_size, column, row + 1));
+                grid.blocked_right[row * grid.columns + column] = geometry.iter().any(|wall| wall.intersects(&right));
+                grid.blocked_down[row * grid.columns + column] = geometry.iter().any(|wall| wall.intersects(&down));
+            }
+        }
+    }
+
+    pub fn update(&mut self, tick: f64, current_time: f64) {
+        let spread = (self.diffusion * tick).min(MAX_SPREAD_PER_TICK);
+        let decay_factor = (1_f64 - self.decay * tick).max(0_f64);
+        for grid in self.grids.iter_mut() {
+            let mut new_values = grid.values.clone();
+            for row in 0..grid.rows {
+                for column in 0..grid.columns {
+                    let index = row * grid.columns + column;
+                    // exchange with right and lower neighbours, every pair is handled once
+                    if column + 1 < grid.columns && !grid.blocked_right[index] {
+                        let flow = spread * (grid.values[index] - grid.values[index + 1]);
+                        new_values[index] -= flow;
+                        new_values[index + 1] += flow;
+                    }
+                    if row + 1 < grid.rows && !grid.blocked_down[index] {
+                        let flow = spread * (grid.values[index] - grid.values[index + grid.columns]);
+                        new_values[index] -= flow;
+                        new_values[index + grid.columns] += flow;
+                    }
+                }
+            }
+            grid.values = new_values.iter().map(|value| value * decay_factor).collect();
+        }
+
+        for source in self.sources.iter().filter(|source| source.start_time <= current_time) {
+            let point = Point::new(source.x as f64, source.y as f64);
+            if let Some((column, row)) = self.cell_for(&point) {
+                let ref mut grid = self.grids[source.floor as usize];
+                grid.values[row * grid.columns + column] += source.emission_rate * tick;
+            }
+        }
+    }
+
+    pub fn concentration_at(&self, floor: u8, point: &Point) -> f64 {
+        match self.cell_for(point) {
+            Some((column, row)) => self.grids[floor as usize].value(column, row),
+            None => 0_f64
+        }
+    }
+
+    // concentration change per meter, walls are not taken into account
+    pub fn gradient_at(&self, floor: u8, point: &Point) -> Vector {
+        let (column, row) = match self.cell_for(point) {
+            Some(cell) => cell,
+            None => return Vector::zero()
+        };
+        let ref grid = self.grids[floor as usize];
+        let left = grid.value(column.saturating_sub(1), row);
+        let right = grid.value((column + 1).min(grid.columns - 1), row);
+        let up = grid.value(column, row.saturating_sub(1));
+        let down = grid.value(column, (row + 1).min(grid.rows - 1));
+        let cell_size_in_meters = self.cell_size * self.scale;
+        Vector::new(right - left, down - up) / (2_f64 * cell_size_in_meters)
+    }
+
+    pub fn speed_factor(&self, concentration: f64) -> f64 {
+        (1_f64 - self.speed_reduction * concentration).max(MIN_SPEED_FACTOR)
+    }
+
+    pub fn visibility(&self, concentration: f64) -> f64 {
+        (1_f64 - self.fov_reduction * concentration).max(MIN_VISIBILITY)
+    }
+
+    // walls are restored together with scene geometry
+    pub fn save_state(&self, writer: &mut CheckpointWriter) {
+        for grid in self.grids.iter() {
+            for value in grid.values.iter() {
+                writer.write_f64(*value);
+            }
+        }
+    }
+
+    pub fn restore_state(&mut self, reader: &mut CheckpointReader) {
+        for grid in self.grids.iter_mut() {
+            for value in grid.values.iter_mut() {
+                *value = reader.read_f64();
+            }
+        }
+    }
+
+    fn cell_for(&self, point: &Point) -> Option<(usize, usize)> {
+        if point.x < 0_f64 || point.y < 0_f64 {
+            return None;
+        }
+        let column = (point.x / self.cell_size) as usize;
+        let row = (point.y / self.cell_size) as usize;
+        let ref grid = self.grids[0];
+        if column < grid.columns && row < grid.rows {
+            Some((column, row))
+        } else {
+            None
+        }
+    }
+
+    fn cell_center(cell_size: f64, column: usize, row: usize) -> Point {
+        Point::new((column as f64 + 0.5_f64) * cell_size, (row as f64 + 0.5_f64) * cell_size)
+    }
+}
+
+#[cfg(test)]
+fn test_hazard(diffusion: f64, walls: &Vec<Line>) -> Hazard {
+    use ::configuration::*;
+    let mut configuration = AnyMap::new();
+    configuration.insert(HazardSources(vec![HazardSource{ floor: 0, x: 25, y: 50, start_time: 0_f64, emission_rate: 1_f64 }]));
+    configuration.insert(HazardCellSize(0.5_f64));
+    configuration.insert(HazardDiffusion(diffusion));
+    configuration.insert(HazardDecay(0_f64));
+    configuration.insert(HazardSpeedReduction(0.5_f64));
+    configuration.insert(HazardFovReduction(0.5_f64));
+    configuration.insert(HazardRepulsionCoeff(1_f64));
+    // 5x5 meters with cells of 10 pixels
+    Hazard::new(&configuration, vec![walls], 100, 100, 0.05_f64).unwrap()
+}
+
+#[test]
+fn test_wall_blocks_diffusion() {
+    let wall = vec![Line::new_from_raw(50_f64, 0_f64, 50_f64, 100_f64)];
+    let mut walled = test_hazard(1_f64, &wall);
+    let mut open = test_hazard(1_f64, &Vec::new());
+    for i in 0..200 {
+        walled.update(0.1_f64, i as f64 * 0.1_f64);
+        open.update(0.1_f64, i as f64 * 0.1_f64);
+    }
+    assert!(walled.concentration_at(0, &Point::new(45_f64, 50_f64)) > 0_f64);
+    assert_eq!(walled.concentration_at(0, &Point::new(55_f64, 50_f64)), 0_f64);
+    assert!(open.concentration_at(0, &Point::new(55_f64, 50_f64)) > 0_f64);
+}
+
+#[test]
+fn test_fast_diffusion_limited_per_tick() {
+    let mut hazard = test_hazard(100_f64, &Vec::new());
+    for i in 0..50 {
+        hazard.update(0.1_f64, i as f64 * 0.1_f64);
+        let ref values = hazard.grids[0].values;
+        // unlimited step would overshoot and make concentrations negative
+        assert!(values.iter().all(|&value| value >= 0_f64));
+        let total: f64 = values.iter().sum();
+        assert!((total - (i + 1) as f64 * 0.1_f64).abs() < 1e-9_f64);
+    }
+    let source = hazard.concentration_at(0, &Point::new(25_f64, 50_f64));
+    assert!(source > hazard.concentration_at(0, &Point::new(35_f64, 50_f64)));
+}
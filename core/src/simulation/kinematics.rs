@@ -0,0 +1,59 @@
+extern crate anymap;
+
+use self::anymap::AnyMap;
+
+use ::configuration::DistributionValue;
+use ::configuration::HeadingModel;
+
+// degrees per second, 10 degrees per tick of 0.1 second
+const DEFAULT_TURN_RATE: f64 = 100_f64;
+const DEFAULT_INSTANT_TURN_THRESHOLD: f64 = 10_f64;
+const DEFAULT_MAX_HEADING_DIFF_TO_TARGET: f64 = 180_f64;
+// pixels per second, speed while turning if max speed isn't configured
+pub const DEFAULT_TURNING_SPEED_CAP: f64 = 4_f64;
+// relative speed of walking sideways, backwards is even slower
+pub const SIDE_STEP_SPEED_FACTOR: f64 = 0.6_f64;
+
+#[derive(Debug,Clone)]
+pub struct PersonKinematics {
+    // radians per second
+    pub turn_rate: f64,
+    // pixels per second
+    pub max_speed: Option<f64>,
+    // pixels per second squared
+    pub max_acceleration: Option<f64>,
+}
+
+pub struct Kinematics {
+    turn_rate: Option<DistributionValue>,
+    max_speed: Option<DistributionValue>,
+    max_acceleration: Option<DistributionValue>,
+    pub heading_model: HeadingModel,
+    // radians
+    pub instant_turn_threshold: f64,
+    pub max_heading_diff_to_target: f64,
+    scale: f64,
+}
+
+impl Kinematics {
+    pub fn new(configuration: &AnyMap, scale: f64) -> Kinematics {
+        Kinematics{ turn_rate: optional_config!(configuration, KinematicsTurnRate),
+                    max_speed: optional_config!(configuration, KinematicsMaxSpeed),
+                    max_acceleration: optional_config!(configuration, KinematicsMaxAcceleration),
+                    heading_model: optional_config!(configuration, KinematicsHeadingModel).unwrap_or(HeadingModel::Walking),
+                    instant_turn_threshold: optional_config!(configuration, KinematicsInstantTurnThreshold)
+                        .unwrap_or(DEFAULT_INSTANT_TURN_THRESHOLD).to_radians(),
+                    max_heading_diff_to_target: optional_config!(configuration, KinematicsMaxHeadingDiffToTarget)
+                        .unwrap_or(DEFAULT_MAX_HEADING_DIFF_TO_TARGET).to_radians(),
+                    scale: scale }
+    }
+
+    pub fn generate_person_kinematics(&self) -> PersonKinematics {
+        let turn_rate = self.turn_rate.as_ref().map_or(DEFAULT_TURN_RATE, |turn_rate| ::utils::distributions::generate(turn_rate));
+        // configured in meters, people move in pixels
+        let max_speed = self.max_speed.as_ref().map(|max_speed| ::utils::distributions::generate(max_speed).max(0.1) / self.scale);
+        let max_acceleration = self.max_acceleration.as_ref()
+            .map(|max_acceleration| ::utils::distributions::generate(max_acceleration).max(0.1) / self.scale);
+        PersonKinematics{ turn_rate: turn_rate.max(1_f64).to_radians(), max_speed: max_speed, max_acceleration: max_acceleration }
+    }
+}
@@ -3,79 +3,442 @@ extern crate time as system_time;
 
 pub mod person;
 pub mod scene;
-mod forces;
-mod time;
-mod statistics;
+pub mod profiles;
+pub mod connectors;
+pub mod services;
+pub mod hazard;
+mod orca;
+pub mod forces;
+pub mod time;
+pub mod statistics;
+pub mod observer;
+pub mod events;
+pub mod checkpoint;
+pub mod population;
+pub mod placement;
+pub mod kinematics;
+pub mod stuck;
+pub mod termination;
+pub mod voronoi;
+mod pacing;
+mod schedule;
 
 use self::anymap::AnyMap;
 
+use std::fs::File;
+use std::io::prelude::*;
+
+use self::forces::Force;
+use self::forces::Forceable;
 use self::forces::Forces;
 use self::scene::Scene;
+use self::scene::SceneEvent;
 use self::time::Time;
 use self::statistics::Statistics;
+use self::schedule::Schedule;
+use self::services::ServiceState;
+use self::orca::Orca;
+use self::stuck::StuckDetector;
+use self::termination::Termination;
+use self::termination::TerminationReason;
+use self::pacing::Pacing;
+use self::observer::SimulationObserver;
+use self::events::SimulationEvent;
+use self::events::SimulationEventKind;
+use self::checkpoint::CheckpointReader;
+use self::checkpoint::CheckpointWriter;
 
 use ::output::Output;
+use ::output::events::EventLog;
+use ::output::trips::TripLog;
+use ::output::trajectory::TrajectoryLog;
+use ::output::measurements::Measurements;
 use ::configuration::SimType;
+use ::configuration::InteractionModel;
+use ::configuration::ScheduleAction;
+use ::configuration::StuckStrategy;
+use ::utils::linelg::Line;
+use ::utils::linelg::Point;
 
 pub struct Simulation {
     configuration: AnyMap,
     sim_type: SimType,
+    termination_rule: SimType,
+    interaction_model: InteractionModel,
     forces: Forces,
+    orca: Orca,
+    stuck_detector: Option<StuckDetector>,
+    termination: Termination,
+    schedule: Schedule,
     pub statistics: Statistics,
     pub scene: Scene,
     pub time: Time,
+    observers: Vec<Box<SimulationObserver>>,
+    // events of current tick not yet passed to observers
+    pending_events: Vec<SimulationEvent>,
+    started: bool,
+    finished: bool,
+    // restored simulation has its people already
+    restored: bool,
+    checkpoint_save: Option<(String, f64)>,
 }
 
 impl Simulation {
     pub fn new(configuration: AnyMap) -> Simulation {
+        if let Some(seed) = optional_config!(configuration, RandomSeed) {
+            ::utils::distributions::seed(seed as u64);
+        }
         let sim_type = config!(configuration, SimTypeCfgWrap);
         let time = Time::new(&configuration);
         let forces = Forces::new(&configuration);
         let scene = Scene::new(&configuration);
         let statistics = Statistics::new(&configuration);
-        Simulation{ sim_type: sim_type, configuration: configuration, statistics: statistics, forces: forces, scene: scene, time: time }
+        let schedule = Schedule::new(&configuration);
+        let interaction_model = optional_config!(configuration, InteractionModelCfgWrap).unwrap_or(InteractionModel::SocialForce);
+        let orca = Orca::new(&configuration);
+        let stuck_detector = StuckDetector::new(&configuration, scene.scale);
+        let termination = Termination::new(&configuration);
+        let checkpoint_save = optional_config!(configuration, CheckpointSaveFilename)
+            .map(|filename| (filename, optional_config!(configuration, CheckpointSaveTime).unwrap_or(0_f64)));
+        let checkpoint_restore = optional_config!(configuration, CheckpointRestoreFilename);
+        let mut simulation = Simulation{ termination_rule: sim_type.clone(), sim_type: sim_type, interaction_model: interaction_model, statistics: statistics,
+                    forces: forces, orca: orca, stuck_detector: stuck_detector, termination: termination, schedule: schedule, scene: scene, time: time, configuration: configuration,
+                    observers: Vec::new(), pending_events: Vec::new(), started: false, finished: false,
+                    restored: false, checkpoint_save: checkpoint_save };
+        if let Some(filename) = checkpoint_restore {
+            info!("Restoring simulation from checkpoint {}", filename);
+            let mut file = File::open(&filename).ok().expect("Can't open checkpoint file");
+            simulation.restore_checkpoint(&mut file);
+        }
+        simulation
     }
 
+    // runs whole simulation writing its state to stdout
     pub fn main_loop(&mut self) {
         info!("Starting main simulation loop");
-        let mut output = Output::new(&self.configuration);
-        debug!("Sending init message to output");
-        output.send_init();
-
-        match self.sim_type {
-            SimType::Escape => {
-                info!("Simulation is in Escape mode, doing initial spawn ...");
-                self.scene.spawn_people(&self.forces, self.time.tick);
-                debug!("Spawned {} people", self.scene.people.len());
-            },
-            _ => ()
+        let output = Output::new(&self.configuration);
+        self.add_observer(Box::new(output));
+        let configuration = &self.configuration;
+        if let Some(events_filename) = optional_config!(configuration, EventsFilename) {
+            self.add_observer(Box::new(EventLog::new(&events_filename)));
+        }
+        let configuration = &self.configuration;
+        if let Some(trips_filename) = optional_config!(configuration, TripsFilename) {
+            self.add_observer(Box::new(TripLog::new(&trips_filename)));
+        }
+        if let Some(trajectory) = TrajectoryLog::new(&self.configuration, self.time.tick) {
+            self.add_observer(Box::new(trajectory));
+        }
+        if let Some(measurements) = Measurements::new(&self.configuration) {
+            self.add_observer(Box::new(measurements));
         }
 
+        let mut pacing = Pacing::new(&self.configuration);
         let mut sum_running_time : f64 = 0.0_f64;
 
-        while !self.is_simulation_finished() {
+        pacing.start(self.time.current_time);
+        while !self.is_finished() {
             let t1 = system_time::precise_time_ns();
-            self.update_state();
-            output.dump_state(self);
-            self.time.next_tick();
+            self.step();
             let t2 = system_time::precise_time_ns();
             sum_running_time += (t2 - t1) as f64;
+            pacing.wait(self.time.current_time);
         }
 
-        output.dump_statistics(self);
+        self.finish();
         let avg_tick = sum_running_time / self.time.current_time * self.time.tick;
         info!("Avg tick took {} ns", avg_tick.round());
+        info!("Achieved speed ratio {:.2}x", pacing.speed_ratio(self.time.current_time));
         info!("Simulation done.");
     }
 
-    fn is_simulation_finished(&self) -> bool {
-        return match self.sim_type {
+    pub fn add_observer(&mut self, observer: Box<SimulationObserver>) {
+        self.observers.push(observer);
+    }
+
+    // advances simulation by one tick
+    pub fn step(&mut self) {
+        if !self.started {
+            self.start();
+        }
+        self.apply_scheduled_events();
+        self.update_state();
+        self.dispatch_events();
+        self.notify_observers(|observer, simulation| observer.on_tick(simulation));
+        self.time.next_tick();
+        self.termination.update(self.time.current_time, self.statistics.travel_time.count);
+        self.save_checkpoint_if_due();
+    }
+
+    // everything changing during simulation, restored simulation must be created from the same configuration
+    pub fn save_checkpoint(&self, out: &mut Write) {
+        let mut writer = CheckpointWriter::new(out);
+        let rng_state = ::utils::distributions::rng_state();
+        writer.write_u64(rng_state[0]);
+        writer.write_u64(rng_state[1]);
+        writer.write_u8(match self.termination_rule {
+            SimType::Flow => 1,
+            SimType::Escape => 2
+        });
+        writer.write_bool(self.started || self.restored);
+        self.time.save_state(&mut writer);
+        self.schedule.save_state(&mut writer);
+        self.statistics.save_state(&mut writer);
+        self.termination.save_state(&mut writer);
+        self.scene.save_state(&mut writer);
+    }
+
+    pub fn restore_checkpoint(&mut self, input: &mut Read) {
+        let mut reader = CheckpointReader::new(input);
+        let rng_state = [reader.read_u64(), reader.read_u64()];
+        ::utils::distributions::set_rng_state(rng_state);
+        self.termination_rule = match reader.read_u8() {
+            1 => SimType::Flow,
+            2 => SimType::Escape,
+            rule => panic!("Unknown termination rule in checkpoint: {}", rule)
+        };
+        self.restored = reader.read_bool();
+        self.time.restore_state(&mut reader);
+        self.schedule.restore_state(&mut reader);
+        self.statistics.restore_state(&mut reader);
+        self.termination.restore_state(&mut reader);
+        self.scene.restore_state(&mut reader);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.termination_reason().is_some()
+    }
+
+    fn termination_reason(&self) -> Option<TerminationReason> {
+        if !self.started {
+            // people of escape mode are not spawned yet
+            return None;
+        }
+        let rule_met = match self.termination_rule {
             SimType::Flow => self.time.is_passed(),
-            SimType::Escape => self.scene.people.len() == 0
+            SimType::Escape => self.scene.people_count() == 0
+        };
+        if rule_met {
+            return Some(match self.termination_rule {
+                SimType::Flow => TerminationReason::EndTime,
+                SimType::Escape => TerminationReason::AllExited
+            });
+        }
+        self.termination.check(self.time.current_time, self.statistics.travel_time.count, self.scene.spawned_count())
+    }
+
+    // accounts people still on scene in statistics and notifies observers, nothing happens on repeated calls
+    pub fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        self.statistics.termination_reason = Some(self.termination_reason().unwrap_or(TerminationReason::Stopped));
+        self.statistics.update_from_remaining_people(self.scene.people.iter().chain(self.scene.people_in_connectors().into_iter()));
+        self.notify_observers(|observer, simulation| observer.on_finish(simulation));
+    }
+
+    // forces acting on people with their weights, repulsion is replaced by velocity obstacles in orca model
+    pub fn active_forces(&self) -> Vec<(&str, f64)> {
+        let active_forces = self.forces.active_forces();
+        match self.interaction_model {
+            InteractionModel::SocialForce => active_forces,
+            InteractionModel::Orca => active_forces.into_iter().filter(|&(name, _weight)| name != "repulsion").collect()
+        }
+    }
+
+    // custom force acts on every person in addition to configured ones
+    pub fn add_force(&mut self, name: &str, force: Box<Forceable>, weight: f64) {
+        self.forces.add_force(Force::Custom{ name: name.to_string(), force: force }, weight);
+    }
+
+    fn start(&mut self) {
+        self.started = true;
+        self.termination.start();
+        self.notify_observers(|observer, simulation| observer.on_start(simulation));
+        if self.restored {
+            return;
+        }
+
+        let configuration = &self.configuration;
+        if let Some(population_filename) = optional_config!(configuration, SpawnPopulationFilename) {
+            info!("Loading initial population from {} ...", population_filename);
+            let population = ::simulation::population::read_population(&population_filename);
+            self.scene.add_population(&self.forces, population, self.time.current_time);
+            debug!("Loaded {} people", self.scene.people.len());
+            // population replaces initial spawn of escape mode
+            return;
+        }
+
+        match self.sim_type {
+            SimType::Escape => {
+                info!("Simulation is in Escape mode, doing initial spawn ...");
+                self.scene.spawn_initial_people(&self.forces, self.time.tick, self.time.current_time);
+                debug!("Spawned {} people", self.scene.people.len());
+            },
+            _ => ()
+        }
+    }
+
+    fn save_checkpoint_if_due(&mut self) {
+        let due = match self.checkpoint_save {
+            Some((_, time)) => self.time.current_time > time - self.time.tick / 2_f64,
+            None => false
+        };
+        if due {
+            let (filename, _time) = self.checkpoint_save.take().unwrap();
+            info!("Saving checkpoint at {} to {}", self.time.current_time, filename);
+            let mut file = File::create(&filename).ok().expect("Can't create checkpoint file");
+            self.save_checkpoint(&mut file);
+        }
+    }
+
+    fn dispatch_events(&mut self) {
+        let time = self.time.current_time;
+        for event in self.scene.take_events().into_iter() {
+            let simulation_event = match event {
+                SceneEvent::Spawned(person) => {
+                    self.notify_observers(|observer, simulation| observer.on_spawn(simulation, &person));
+                    SimulationEvent::for_person(time, SimulationEventKind::Spawn, &person)
+                },
+                SceneEvent::SpawnFailed{ spawn_id, floor, location } =>
+                    SimulationEvent{ time: time, kind: SimulationEventKind::SpawnFailed{ spawn_id: spawn_id },
+                                     person_id: None, floor: floor, location: location },
+                SceneEvent::TargetReached{ person, target_index } => {
+                    self.notify_observers(|observer, simulation| observer.on_target_reached(simulation, &person, target_index));
+                    SimulationEvent::for_person(time, SimulationEventKind::TargetReached{ target_index: target_index }, &person)
+                },
+                SceneEvent::Exited(person) => {
+                    self.notify_observers(|observer, simulation| observer.on_exit(simulation, &person));
+                    SimulationEvent::for_person(time, SimulationEventKind::Exit, &person)
+                },
+                SceneEvent::Removed(person) => {
+                    self.notify_observers(|observer, simulation| observer.on_remove(simulation, &person));
+                    SimulationEvent::for_person(time, SimulationEventKind::Removed, &person)
+                },
+            };
+            self.pending_events.push(simulation_event);
+        }
+
+        let events = ::std::mem::replace(&mut self.pending_events, Vec::new());
+        for event in events.iter() {
+            self.notify_observers(|observer, simulation| observer.on_event(simulation, event));
+        }
+    }
+
+    fn notify_observers<F>(&mut self, mut notify: F) where F: FnMut(&mut SimulationObserver, &Simulation) {
+        // observers are taken out so they can look at the whole simulation
+        let mut observers = ::std::mem::replace(&mut self.observers, Vec::new());
+        for observer in observers.iter_mut() {
+            notify(&mut **observer, self);
+        }
+        self.observers = observers;
+    }
+
+    fn apply_scheduled_events(&mut self) {
+        for action in self.schedule.take_due_actions(self.time.current_time, self.time.tick).into_iter() {
+            info!("Applying scheduled event at {}: {:?}", self.time.current_time, action);
+            match action {
+                ScheduleAction::AddWall(wall) =>
+                    self.scene.add_wall(Line::new_from_raw(wall.x0 as f64, wall.y0 as f64, wall.x1 as f64, wall.y1 as f64), wall.floor),
+                ScheduleAction::RemoveWall(wall) =>
+                    self.scene.remove_wall(&Line::new_from_raw(wall.x0 as f64, wall.y0 as f64, wall.x1 as f64, wall.y1 as f64), wall.floor),
+                ScheduleAction::EnableTargetArea{ x0, y0, x1, y1, floor } =>
+                    self.scene.set_target_area_enabled(&Point::new(x0 as f64, y0 as f64), &Point::new(x1 as f64, y1 as f64), floor, true),
+                ScheduleAction::DisableTargetArea{ x0, y0, x1, y1, floor } =>
+                    self.scene.set_target_area_enabled(&Point::new(x0 as f64, y0 as f64), &Point::new(x1 as f64, y1 as f64), floor, false),
+                ScheduleAction::ChangeSpawnRate{ spawn_id, rate } =>
+                    self.scene.set_spawn_rate(spawn_id, rate, self.time.tick),
+                ScheduleAction::SwitchTerminationRule(rule) =>
+                    self.termination_rule = rule,
+            }
         }
     }
 
     fn update_state(&mut self) {
+        for person in self.scene.people.iter_mut() {
+            self.forces.update_noise(person, self.time.tick);
+        }
+        match self.interaction_model {
+            InteractionModel::SocialForce => self.move_people_by_forces(),
+            InteractionModel::Orca => self.move_people_by_orca()
+        }
+        self.resolve_stuck_people();
+
+        match self.sim_type {
+            SimType::Escape => (),
+            _ => {
+                let waiting_times = self.scene.spawn_people(&self.forces, self.time.tick, self.time.current_time);
+                self.statistics.update_from_spawn_areas(waiting_times, self.scene.spawn_backlog_lengths());
+            }
+        }
+        self.scene.update_connectors(self.time.tick);
+        self.scene.update_hazard(self.time.tick, self.time.current_time);
+        let waiting_times = self.scene.update_services(self.time.tick, self.time.current_time);
+        self.statistics.update_from_service_points(waiting_times, self.scene.service_queue_lengths());
+        let reached_destination_people = self.scene.process_reached_destination_people(self.time.current_time);
+        self.statistics.update_from_reached_destination_people(reached_destination_people, self.time.current_time, self.scene.scale);
+    }
+
+    fn resolve_stuck_people(&mut self) {
+        let detector = match self.stuck_detector {
+            Some(ref detector) => detector,
+            None => return
+        };
+        let current_time = self.time.current_time;
+        let mut stuck_people = Vec::new();
+        for (index, person) in self.scene.people.iter_mut().enumerate() {
+            if let Some(progress) = detector.check(person, current_time) {
+                stuck_people.push(index);
+                self.pending_events.push(SimulationEvent::for_person(current_time,
+                                                                     SimulationEventKind::Stuck{ progress: progress * self.scene.scale }, person));
+            }
+        }
+
+        let mut removed_people = Vec::new();
+        // from the end so removal doesn't shift indices of the rest
+        for &index in stuck_people.iter().rev() {
+            match detector.strategy {
+                StuckStrategy::Report => (),
+                StuckStrategy::Perturb => {
+                    self.scene.perturb_person(index, detector.perturbation);
+                },
+                StuckStrategy::Reroute => {
+                    if !self.scene.reroute_person(index) {
+                        self.scene.perturb_person(index, detector.perturbation);
+                    }
+                },
+                StuckStrategy::Remove => {
+                    removed_people.push(self.scene.people[index].clone());
+                    self.scene.remove_person(index);
+                }
+            }
+        }
+        self.statistics.update_from_stuck_people(stuck_people.len(), &removed_people);
+    }
+
+    fn move_people_by_orca(&mut self) {
+        let mut velocities = Vec::new();
+        velocities.reserve(self.scene.people.len());
+        for person in self.scene.people.iter() {
+            velocities.push(self.orca.velocity_for_person(person, &self.scene, &self.forces, self.time.tick));
+        }
+        for (person, velocity) in self.scene.people.iter_mut().zip(velocities.iter()) {
+            // chosen velocity stands for total force, nearly zero one means the person is blocked
+            if !person.is_waiting_for_service() && velocity.length() < 0.01_f64 {
+                warn!("Small orca velocity: {}", velocity.length());
+                self.pending_events.push(SimulationEvent::for_person(self.time.current_time,
+                                                                     SimulationEventKind::SmallForce{ force: velocity.length() }, person));
+            }
+            match person.service_state {
+                ServiceState::InService{ .. } => person.stand_still(),
+                // turning and acceleration limits apply to chosen velocity as to total force
+                _ => person.move_by(*velocity, self.time.tick, &self.scene.kinematics)
+            }
+        }
+    }
+
+    fn move_people_by_forces(&mut self) {
         let mut total_forces_for_person = Vec::new();
         total_forces_for_person.reserve(self.scene.people.len());
         for person in self.scene.people.iter() {
@@ -84,18 +447,21 @@ impl Simulation {
             total_forces_for_person.push(total_force);
         }
         for (person, total_force) in self.scene.people.iter_mut().zip(total_forces_for_person.iter()) {
+            if person.is_waiting_for_service() {
+                // queued people only shuffle to their places, people being served stay still
+                match person.service_state {
+                    ServiceState::InService{ .. } => person.stand_still(),
+                    _ => person.move_by(*total_force, self.time.tick, &self.scene.kinematics)
+                }
+                continue;
+            }
             if total_force.length() < 0.01_f64 {
                 warn!("Small total force: {}", total_force.length());
+                self.pending_events.push(SimulationEvent::for_person(self.time.current_time,
+                                                                     SimulationEventKind::SmallForce{ force: total_force.length() }, person));
             }
-            person.move_by(*total_force, self.time.tick);
-        }
-
-        match self.sim_type {
-            SimType::Escape => (),
-            _ => self.scene.spawn_people(&self.forces, self.time.tick)
+            person.move_by(*total_force, self.time.tick, &self.scene.kinematics);
         }
-        let reached_destination_people = self.scene.process_reached_destination_people();
-        self.statistics.update_from_reached_destination_people(reached_destination_people, self.time.current_time);
     }
 }
 
@@ -0,0 +1,22 @@
+use ::simulation::Simulation;
+use ::simulation::person::Person;
+use ::simulation::events::SimulationEvent;
+
+// receives simulation progress, every callback does nothing by default
+pub trait SimulationObserver {
+    // before the first tick
+    fn on_start(&mut self, _simulation: &Simulation) {}
+    // after state of every tick is computed
+    fn on_tick(&mut self, _simulation: &Simulation) {}
+    fn on_spawn(&mut self, _simulation: &Simulation, _person: &Person) {}
+    // target_index is the number of reached target in person's path
+    fn on_target_reached(&mut self, _simulation: &Simulation, _person: &Person, _target_index: u16) {}
+    // person reached his final target and left the scene
+    fn on_exit(&mut self, _simulation: &Simulation, _person: &Person) {}
+    // stuck person was taken off the scene
+    fn on_remove(&mut self, _simulation: &Simulation, _person: &Person) {}
+    // every event, including ones reported by callbacks above
+    fn on_event(&mut self, _simulation: &Simulation, _event: &SimulationEvent) {}
+    // statistics are final at this point
+    fn on_finish(&mut self, _simulation: &Simulation) {}
+}
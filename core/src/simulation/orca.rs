@@ -0,0 +1,265 @@
+extern crate anymap;
+
+use self::anymap::AnyMap;
+
+use ::simulation::person::Person;
+use ::simulation::scene::Scene;
+use ::simulation::forces::Forces;
+
+use ::utils::linelg::Vector;
+use ::utils::linelg::distance::DistanceTo;
+
+const DEFAULT_TIME_HORIZON: f64 = 2_f64;
+const DEFAULT_OBSTACLE_TIME_HORIZON: f64 = 1_f64;
+const DEFAULT_NEIGHBOR_DISTANCE: f64 = 5_f64;
+const EPSILON: f64 = 0.00001_f64;
+
+// half-plane of permitted velocities, to the left of direction
+#[derive(Debug,Clone)]
+struct OrcaLine {
+    point: Vector,
+    direction: Vector,
+}
+
+// optimal reciprocal collision avoidance (van den Berg et al.), computations are done in meters
+pub struct Orca {
+    time_horizon: f64,
+    obstacle_time_horizon: f64,
+    neighbor_distance: f64,
+}
+
+impl Orca {
+    pub fn new(configuration: &AnyMap) -> Orca {
+        Orca{ time_horizon: optional_config!(configuration, OrcaTimeHorizon).unwrap_or(DEFAULT_TIME_HORIZON),
+              obstacle_time_horizon: optional_config!(configuration, OrcaObstacleTimeHorizon).unwrap_or(DEFAULT_OBSTACLE_TIME_HORIZON),
+              neighbor_distance: optional_config!(configuration, OrcaNeighborDistance).unwrap_or(DEFAULT_NEIGHBOR_DISTANCE) }
+    }
+
+    // new velocity for person in pixels per second
+    pub fn velocity_for_person(&self, person: &Person, scene: &Scene, forces: &Forces, tick: f64) -> Vector {
+        let scale = scene.scale;
+        let preferred_velocity = forces.preferred_velocity_for_person(person, scene);
+        let mut max_speed = (person.forces_params.target_speed * scene.speed_factor_for(person)).max(preferred_velocity.length());
+        if let Some(kinematics_max_speed) = person.kinematics.max_speed {
+            max_speed = max_speed.min(kinematics_max_speed * scale);
+        }
+        let velocity = person.velocity * scale;
+
+        // same body size as in placement and repulsion: people touch when centers are closer than mean of their radiuses
+        let contact_distance = person.radius / 2_f64;
+
+        // obstacle lines go first as they must not be violated
+        let mut lines = Vec::new();
+        let obstacle_range = self.obstacle_time_horizon * max_speed + contact_distance;
+        for wall in scene.floors[person.floor as usize].geometry.iter() {
+            let nearest_point = person.coordinates.nearest_point(wall);
+            let relative_position = (nearest_point - person.coordinates) * scale;
+            if relative_position.length() < obstacle_range {
+                // static obstacle takes no part in avoidance, person takes full responsibility
+                let u = self.avoidance_vector(relative_position, velocity, contact_distance, self.obstacle_time_horizon, tick);
+                lines.push(OrcaLine{ point: velocity + u.0, direction: u.1 });
+            }
+        }
+        let obstacle_lines_count = lines.len();
+
+        for other in scene.people.iter().filter(|other| other.floor == person.floor && other.id != person.id) {
+            let relative_position = (other.coordinates - person.coordinates) * scale;
+            if relative_position.length() > self.neighbor_distance {
+                continue;
+            }
+            let relative_velocity = velocity - other.velocity * scale;
+            let combined_radius = (person.radius + other.radius) / 2_f64;
+            let (u, direction) = self.avoidance_vector(relative_position, relative_velocity, combined_radius, self.time_horizon, tick);
+            lines.push(OrcaLine{ point: velocity + u * 0.5_f64, direction: direction });
+        }
+
+        let mut new_velocity = Vector::zero();
+        let line_fail = linear_program2(&lines, max_speed, preferred_velocity, false, &mut new_velocity);
+        if line_fail < lines.len() {
+            linear_program3(&lines, obstacle_lines_count, line_fail, max_speed, &mut new_velocity);
+        }
+        new_velocity / scale
+    }
+
+    // smallest change of relative velocity to leave velocity obstacle and the direction of its boundary
+    fn avoidance_vector(&self, relative_position: Vector, relative_velocity: Vector, combined_radius: f64, time_horizon: f64, tick: f64) -> (Vector, Vector) {
+        let distance_sqr = relative_position.length_sqr();
+        let combined_radius_sqr = combined_radius * combined_radius;
+
+        if distance_sqr > combined_radius_sqr {
+            let inv_time_horizon = 1_f64 / time_horizon;
+            // vector from cutoff center to relative velocity
+            let w = relative_velocity - relative_position * inv_time_horizon;
+            let w_length_sqr = w.length_sqr();
+            let dot_product = dot(w, relative_position);
+
+            if dot_product < 0_f64 && dot_product * dot_product > combined_radius_sqr * w_length_sqr {
+                // project on cutoff circle
+                let w_length = w_length_sqr.sqrt();
+                let unit_w = w / w_length;
+                (unit_w * (combined_radius * inv_time_horizon - w_length), Vector::new(unit_w.y, -unit_w.x))
+            } else {
+                // project on legs
+                let leg = (distance_sqr - combined_radius_sqr).sqrt();
+                let direction = if det(relative_position, w) > 0_f64 {
+                    Vector::new(relative_position.x * leg - relative_position.y * combined_radius,
+                                relative_position.x * combined_radius + relative_position.y * leg) / distance_sqr
+                } else {
+                    - Vector::new(relative_position.x * leg + relative_position.y * combined_radius,
+                                  - relative_position.x * combined_radius + relative_position.y * leg) / distance_sqr
+                };
+                (direction * dot(relative_velocity, direction) - relative_velocity, direction)
+            }
+        } else {
+            // already colliding, resolve within one tick
+            let inv_tick = 1_f64 / tick;
+            let w = relative_velocity - relative_position * inv_tick;
+            let w_length = w.length();
+            if w_length == 0_f64 {
+                return (Vector::zero(), Vector::new(1_f64, 0_f64));
+            }
+            let unit_w = w / w_length;
+            (unit_w * (combined_radius * inv_tick - w_length), Vector::new(unit_w.y, -unit_w.x))
+        }
+    }
+}
+
+fn dot(a: Vector, b: Vector) -> f64 {
+    ::utils::linelg::dot(&a, &b)
+}
+
+fn det(a: Vector, b: Vector) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+fn linear_program1(lines: &Vec<OrcaLine>, line_no: usize, radius: f64, opt_velocity: Vector, direction_opt: bool, result: &mut Vector) -> bool {
+    let ref line = lines[line_no];
+    let dot_product = dot(line.point, line.direction);
+    let discriminant = dot_product * dot_product + radius * radius - line.point.length_sqr();
+    if discriminant < 0_f64 {
+        // max speed circle fully invalidates this line
+        return false;
+    }
+
+    let sqrt_discriminant = discriminant.sqrt();
+    let mut t_left = - dot_product - sqrt_discriminant;
+    let mut t_right = - dot_product + sqrt_discriminant;
+    for other in lines[..line_no].iter() {
+        let denominator = det(line.direction, other.direction);
+        let numerator = det(other.direction, line.point - other.point);
+        if denominator.abs() <= EPSILON {
+            // lines are parallel
+            if numerator < 0_f64 {
+                return false;
+            }
+            continue;
+        }
+        let t = numerator / denominator;
+        if denominator >= 0_f64 {
+            t_right = t_right.min(t);
+        } else {
+            t_left = t_left.max(t);
+        }
+        if t_left > t_right {
+            return false;
+        }
+    }
+
+    let t = if direction_opt {
+        if dot(opt_velocity, line.direction) > 0_f64 { t_right } else { t_left }
+    } else {
+        ::utils::linelg::clamp(dot(line.direction, opt_velocity - line.point), t_left, t_right)
+    };
+    *result = line.point + line.direction * t;
+    true
+}
+
+fn linear_program2(lines: &Vec<OrcaLine>, radius: f64, opt_velocity: Vector, direction_opt: bool, result: &mut Vector) -> usize {
+    *result = if direction_opt {
+        opt_velocity * radius
+    } else if opt_velocity.length_sqr() > radius * radius {
+        opt_velocity.normalized() * radius
+    } else {
+        opt_velocity
+    };
+
+    for (i, line) in lines.iter().enumerate() {
+        if det(line.direction, line.point - *result) > 0_f64 {
+            // result does not satisfy constraint i
+            let previous_result = *result;
+            if !linear_program1(lines, i, radius, opt_velocity, direction_opt, result) {
+                *result = previous_result;
+                return i;
+            }
+        }
+    }
+    lines.len()
+}
+
+// infeasible program, velocity which least violates agents constraints is chosen
+fn linear_program3(lines: &Vec<OrcaLine>, obstacle_lines_count: usize, begin_line: usize, radius: f64, result: &mut Vector) {
+    let mut distance = 0_f64;
+    for i in begin_line..lines.len() {
+        if det(lines[i].direction, lines[i].point - *result) <= distance {
+            continue;
+        }
+        let mut projected_lines: Vec<OrcaLine> = lines[..obstacle_lines_count].to_vec();
+        for j in obstacle_lines_count..i {
+            let determinant = det(lines[i].direction, lines[j].direction);
+            let point = if determinant.abs() <= EPSILON {
+                if dot(lines[i].direction, lines[j].direction) > 0_f64 {
+                    // lines point in the same direction
+                    continue;
+                }
+                (lines[i].point + lines[j].point) * 0.5_f64
+            } else {
+                lines[i].point + lines[i].direction * (det(lines[j].direction, lines[i].point - lines[j].point) / determinant)
+            };
+            projected_lines.push(OrcaLine{ point: point, direction: (lines[j].direction - lines[i].direction).normalized() });
+        }
+
+        let previous_result = *result;
+        let opt_direction = Vector::new(- lines[i].direction.y, lines[i].direction.x);
+        if linear_program2(&projected_lines, radius, opt_direction, true, result) < projected_lines.len() {
+            // should not happen in principle, result is by definition already in feasible region
+            *result = previous_result;
+        }
+        distance = det(lines[i].direction, lines[i].point - *result);
+    }
+}
+
+#[cfg(test)]
+fn test_step(orca: &Orca, positions: &[Vector; 2], velocities: &[Vector; 2], goals: &[Vector; 2], combined_radius: f64, tick: f64) -> [Vector; 2] {
+    let mut new_velocities = [Vector::zero(), Vector::zero()];
+    for i in 0..2 {
+        let other = 1 - i;
+        let preferred_velocity = (goals[i] - positions[i]).normalized() * 1.3_f64;
+        let (u, direction) = orca.avoidance_vector(positions[other] - positions[i], velocities[i] - velocities[other],
+                                                   combined_radius, orca.time_horizon, tick);
+        let lines = vec![OrcaLine{ point: velocities[i] + u * 0.5_f64, direction: direction }];
+        let line_fail = linear_program2(&lines, 1.5_f64, preferred_velocity, false, &mut new_velocities[i]);
+        if line_fail < lines.len() {
+            linear_program3(&lines, 0, line_fail, 1.5_f64, &mut new_velocities[i]);
+        }
+    }
+    new_velocities
+}
+
+#[test]
+fn test_head_on_pass_without_overlap() {
+    let orca = Orca::new(&AnyMap::new());
+    // paths are a few centimeters apart, perfectly symmetric agents would just stop in front of each other
+    let goals = [Vector::new(10_f64, 0_f64), Vector::new(0_f64, 0.05_f64)];
+    let mut positions = [Vector::new(0_f64, 0_f64), Vector::new(10_f64, 0.05_f64)];
+    let mut velocities = [Vector::new(1.3_f64, 0_f64), Vector::new(-1.3_f64, 0_f64)];
+    let combined_radius = 0.4_f64;
+    let tick = 0.1_f64;
+    for _i in 0..150 {
+        velocities = test_step(&orca, &positions, &velocities, &goals, combined_radius, tick);
+        positions = [positions[0] + velocities[0] * tick, positions[1] + velocities[1] * tick];
+        assert!((positions[1] - positions[0]).length() >= combined_radius - EPSILON);
+    }
+    // both passed each other and got close to their goals
+    assert!((goals[0] - positions[0]).length() < 0.5_f64);
+    assert!((goals[1] - positions[1]).length() < 0.5_f64);
+}
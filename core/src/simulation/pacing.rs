@@ -0,0 +1,59 @@
+extern crate anymap;
+extern crate time as system_time;
+
+use std::io::prelude::*;
+use std::time::Duration;
+
+use self::anymap::AnyMap;
+
+use ::configuration::RunMode;
+
+// keeps simulated time in step with wall clock in real time mode
+pub struct Pacing {
+    mode: RunMode,
+    speed_factor: f64,
+    started_at: u64,
+    start_time: f64,
+}
+
+impl Pacing {
+    pub fn new(configuration: &AnyMap) -> Pacing {
+        let speed_factor = optional_config!(configuration, RunSpeedFactor).unwrap_or(1_f64);
+        if speed_factor <= 0_f64 {
+            panic!("Run speed factor must be positive, got {}", speed_factor);
+        }
+        Pacing{ mode: optional_config!(configuration, RunModeCfgWrap).unwrap_or(RunMode::Batch),
+                speed_factor: speed_factor, started_at: 0, start_time: 0_f64 }
+    }
+
+    pub fn start(&mut self, current_time: f64) {
+        self.started_at = system_time::precise_time_ns();
+        // restored simulation doesn't start from zero
+        self.start_time = current_time;
+    }
+
+    // sleeps until wall clock catches up with simulated time
+    pub fn wait(&self, current_time: f64) {
+        match self.mode {
+            RunMode::Batch => (),
+            RunMode::RealTime => {
+                // animator should get every tick as soon as it is due
+                ::std::io::stdout().flush().ok().expect("Can't write to stdout");
+                let due = (current_time - self.start_time) / self.speed_factor;
+                let ahead = due - self.wall_elapsed();
+                if ahead > 0_f64 {
+                    ::std::thread::sleep(Duration::new(ahead.trunc() as u64, (ahead.fract() * 1e9_f64) as u32));
+                }
+            }
+        }
+    }
+
+    // simulated seconds per wall clock second
+    pub fn speed_ratio(&self, current_time: f64) -> f64 {
+        (current_time - self.start_time) / self.wall_elapsed()
+    }
+
+    fn wall_elapsed(&self) -> f64 {
+        (system_time::precise_time_ns() - self.started_at) as f64 / 1e9_f64
+    }
+}
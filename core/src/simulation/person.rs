@@ -1,57 +1,145 @@
 use ::utils::linelg::Vector;
 use ::utils::linelg::Point;
 
+use ::configuration::HeadingModel;
+
 use ::simulation::forces::PersonForcesParams;
-use ::simulation::scene::Path;
+use ::simulation::kinematics::Kinematics;
+use ::simulation::kinematics::PersonKinematics;
+use ::simulation::kinematics::DEFAULT_TURNING_SPEED_CAP;
+use ::simulation::kinematics::SIDE_STEP_SPEED_FACTOR;
 use ::simulation::scene::Area;
+use ::simulation::services::ServiceState;
+use ::simulation::stuck::ProgressMark;
 
 #[derive(Debug,Clone)]
 pub struct Person {
+    pub id: u32,
     pub coordinates: Point,
     pub heading: f64,
+    // pixels per second
+    pub velocity: Vector,
     pub path_id: u8,
+    pub profile_id: u8,
+    pub radius: f64,
+    pub floor: u8,
     pub current_target_area: Area,
     pub current_target_index: u16,
     pub forces_params: PersonForcesParams,
+    pub kinematics: PersonKinematics,
+    pub service_state: ServiceState,
+    // 1 in clear air, lower in smoke
+    pub visibility: f64,
+    pub hazard_exposure: f64,
+    // current fluctuation force
+    pub noise: Vector,
+    // none until stuck detection starts watching person
+    pub progress: Option<ProgressMark>,
+    pub spawned_at: f64,
+    // pixels
+    pub distance_walked: f64,
+    // when every reached target was reached, the last one is exit
+    pub target_times: Vec<f64>,
+    // sum of windows person was flagged as stuck in
+    pub stuck_time: f64,
 }
 
 impl Person {
-    #[allow(non_snake_case)]
-    pub fn move_by(&mut self, total_force: Vector, t: f64) {
-        let MAX_HEADING_DIFF_TO_TARGET: f64 = 180_f64.to_radians();
-        let INSTANT_HEADING_CHANGE_THRESHOLD: f64 = 10_f64.to_radians();
-        let TURN_RATE: f64 = 10_f64.to_radians();
+    pub fn move_by(&mut self, total_force: Vector, t: f64, kinematics: &Kinematics) {
+        let velocity = match kinematics.heading_model {
+            HeadingModel::Walking => self.turn_walking_direction(total_force, t, kinematics),
+            HeadingModel::Body => self.turn_body(total_force, t)
+        };
+        let velocity = self.limit_velocity(velocity, t);
+        self.coordinates = self.coordinates + velocity * t;
+        self.distance_walked += velocity.length() * t;
+        self.velocity = velocity;
+    }
 
+    // person faces where he walks and can't turn faster than his turn rate
+    fn turn_walking_direction(&mut self, total_force: Vector, t: f64, kinematics: &Kinematics) -> Vector {
         let target_vector = self.current_target_point() - self.coordinates;
         let target_heading = ::utils::headings::vector_heading(target_vector);
 
         let mut new_heading = ::utils::headings::vector_heading(total_force);
         let heading_change = ::utils::headings::heading_diff(new_heading, self.heading);
 
-        let adjusted_total_force = if heading_change.abs() > INSTANT_HEADING_CHANGE_THRESHOLD {
+        let adjusted_total_force = if heading_change.abs() > kinematics.instant_turn_threshold {
+            let turn = self.kinematics.turn_rate * t;
             new_heading = if heading_change > 0_f64 {
-                self.heading + TURN_RATE
+                self.heading + turn
             } else {
-                self.heading - TURN_RATE
+                self.heading - turn
             };
             if new_heading < 0_f64 {
                 new_heading += 2_f64 * ::std::f64::consts::PI;
             }
 
-            let mut weaken_coeff = (heading_change.abs() - INSTANT_HEADING_CHANGE_THRESHOLD).cos();
+            let mut weaken_coeff = (heading_change.abs() - kinematics.instant_turn_threshold).cos();
             let heading_diff_to_target = ::utils::headings::heading_diff(target_heading, new_heading);
-            if heading_diff_to_target.abs() > MAX_HEADING_DIFF_TO_TARGET {
-                // cap max rotation at MAX_HEADING_DIFF_TO_TARGET
-                new_heading = heading_diff_to_target.signum() * MAX_HEADING_DIFF_TO_TARGET;
-                let chop_diff = heading_diff_to_target.abs() - MAX_HEADING_DIFF_TO_TARGET;
+            if heading_diff_to_target.abs() > kinematics.max_heading_diff_to_target {
+                // cap max rotation at max_heading_diff_to_target
+                new_heading = heading_diff_to_target.signum() * kinematics.max_heading_diff_to_target;
+                let chop_diff = heading_diff_to_target.abs() - kinematics.max_heading_diff_to_target;
                 weaken_coeff = weaken_coeff * chop_diff.cos() / 1000_f64;
             }
-            Vector::new(new_heading.cos(), new_heading.sin()) * (total_force.length() * weaken_coeff).min(4_f64)
+            let turning_speed_cap = self.kinematics.max_speed.unwrap_or(DEFAULT_TURNING_SPEED_CAP);
+            Vector::new(new_heading.cos(), new_heading.sin()) * (total_force.length() * weaken_coeff).min(turning_speed_cap)
         } else {
             total_force
         };
-        self.coordinates = self.coordinates + adjusted_total_force * t;
         self.heading = new_heading;
+        adjusted_total_force
+    }
+
+    // person walks along total force while his body turns towards it with his turn rate
+    fn turn_body(&mut self, total_force: Vector, t: f64) -> Vector {
+        if total_force.length() == 0_f64 {
+            return total_force;
+        }
+        let walking_heading = ::utils::headings::vector_heading(total_force);
+        let body_turn = ::utils::headings::heading_diff(walking_heading, self.heading);
+        let max_turn = self.kinematics.turn_rate * t;
+        if body_turn.abs() <= max_turn {
+            self.heading = walking_heading;
+        } else {
+            self.heading += body_turn.signum() * max_turn;
+            if self.heading < 0_f64 {
+                self.heading += 2_f64 * ::std::f64::consts::PI;
+            } else if self.heading >= 2_f64 * ::std::f64::consts::PI {
+                self.heading -= 2_f64 * ::std::f64::consts::PI;
+            }
+        }
+
+        // stepping sideways is slower than walking forward, stepping backwards is slower still
+        let facing = ::utils::headings::heading_diff(walking_heading, self.heading).cos();
+        let speed_factor = if facing >= 0_f64 {
+            SIDE_STEP_SPEED_FACTOR + (1_f64 - SIDE_STEP_SPEED_FACTOR) * facing
+        } else {
+            SIDE_STEP_SPEED_FACTOR * (1_f64 + facing / 2_f64)
+        };
+        total_force * speed_factor
+    }
+
+    fn limit_velocity(&self, velocity: Vector, t: f64) -> Vector {
+        let mut velocity = velocity;
+        if let Some(max_acceleration) = self.kinematics.max_acceleration {
+            let change = velocity - self.velocity;
+            let max_change = max_acceleration * t;
+            if change.length() > max_change {
+                velocity = self.velocity + change / change.length() * max_change;
+            }
+        }
+        if let Some(max_speed) = self.kinematics.max_speed {
+            if velocity.length() > max_speed {
+                velocity = velocity / velocity.length() * max_speed;
+            }
+        }
+        velocity
+    }
+
+    pub fn stand_still(&mut self) {
+        self.velocity = Vector::zero();
     }
 
     pub fn current_target_point(&self) -> Point {
@@ -69,13 +157,25 @@ impl Person {
             ::utils::linelg::ellipse_sqr_radius_at_angle(SIDE_FOV, self.forces_params.backward_fov, -angle).sqrt()
         };
         let normalization_coeff = SIDE_FOV.max(self.forces_params.forward_fov).max(self.forces_params.backward_fov);
-        let fov_coeff = ellipse_coeff / normalization_coeff / 2_f64;
+        // field of view shrinks with visibility
+        let fov_coeff = ellipse_coeff / normalization_coeff / 2_f64 * self.visibility;
         fov_coeff
     }
 
-    pub fn reached_destination(&self, path: &Path) -> bool {
-        let target = &path.target_areas[self.current_target_index as usize];
-        self.coordinates.x > target.p0.x && self.coordinates.x < target.p1.x &&
-            self.coordinates.y > target.p0.y && self.coordinates.y < target.p1.y
+    pub fn is_waiting_for_service(&self) -> bool {
+        match self.service_state {
+            ServiceState::Walking | ServiceState::Served => false,
+            _ => true
+        }
+    }
+
+    // inside target, though it may be disabled or be connector entry one still has to wait in
+    pub fn is_in_target_area(&self) -> bool {
+        let ref target = self.current_target_area;
+        target.floor == self.floor && target.contains(&self.coordinates)
+    }
+
+    pub fn reached_destination(&self) -> bool {
+        self.current_target_area.enabled && self.is_in_target_area()
     }
 }
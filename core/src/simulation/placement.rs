@@ -0,0 +1,55 @@
+use ::utils::linelg::Point;
+use ::utils::linelg::Vector;
+
+// candidates tried around every placed point before it is retired
+const CANDIDATES_PER_POINT: usize = 30;
+// random points tried when there is nothing to grow from
+const SEED_ATTEMPTS: usize = 30;
+
+// poisson-disc sampling (Bridson) with per point radius: new points are tried in a ring around
+// already placed ones, so area is filled densely without overlaps
+pub struct PoissonDiscSampler {
+    p0: Point,
+    p1: Point,
+    // placed points new ones may still be placed around, with their contact radius
+    active: Vec<(Point, f64)>,
+}
+
+impl PoissonDiscSampler {
+    pub fn new(p0: Point, p1: Point) -> PoissonDiscSampler {
+        PoissonDiscSampler{ p0: p0, p1: p1, active: Vec::new() }
+    }
+
+    // radius is half of minimal distance between points, is_free checks candidate against obstacles and other points
+    pub fn next_point<F>(&mut self, radius: f64, is_free: F) -> Option<Point> where F: Fn(&Point) -> bool {
+        while !self.active.is_empty() {
+            let active_index = (::utils::distributions::generate_uniform(0_f64, self.active.len() as f64) as usize).min(self.active.len() - 1);
+            let (center, center_radius) = self.active[active_index];
+            let min_distance = center_radius + radius;
+            for _i in 0..CANDIDATES_PER_POINT {
+                let angle = ::utils::distributions::generate_uniform(0_f64, 2_f64 * ::std::f64::consts::PI);
+                let distance = ::utils::distributions::generate_uniform(min_distance, 2_f64 * min_distance);
+                let candidate = center + Vector::new(angle.cos(), angle.sin()) * distance;
+                if self.contains(&candidate) && is_free(&candidate) {
+                    self.active.push((candidate, radius));
+                    return Some(candidate);
+                }
+            }
+            self.active.swap_remove(active_index);
+        }
+
+        for _i in 0..SEED_ATTEMPTS {
+            let candidate = Point::new(::utils::distributions::generate_uniform(self.p0.x, self.p1.x),
+                                       ::utils::distributions::generate_uniform(self.p0.y, self.p1.y));
+            if is_free(&candidate) {
+                self.active.push((candidate, radius));
+                return Some(candidate);
+            }
+        }
+        None
+    }
+
+    fn contains(&self, p: &Point) -> bool {
+        p.x > self.p0.x && p.x < self.p1.x && p.y > self.p0.y && p.y < self.p1.y
+    }
+}
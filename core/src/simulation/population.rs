@@ -0,0 +1,74 @@
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+
+// person of initial population, omitted values are generated as for spawned people
+#[derive(Debug,Clone)]
+pub struct PopulationEntry {
+    // meters, as in trajectory output and observed data
+    pub x: f64,
+    pub y: f64,
+    pub floor: u8,
+    // radians, towards current target when omitted
+    pub heading: Option<f64>,
+    pub path_id: u8,
+    pub target_index: u16,
+    pub profile_id: Option<u8>,
+    pub radius: Option<f64>,
+    pub target_speed: Option<f64>,
+    pub repulsion_coeff: Option<f64>,
+    pub forward_fov: Option<f64>,
+    pub backward_fov: Option<f64>,
+    pub noise_level: Option<f64>,
+}
+
+const COLUMNS: [&'static str; 13] = ["x", "y", "floor", "heading", "path", "target_index", "profile",
+                                     "radius", "target_speed", "repulsion_coeff", "forward_fov", "backward_fov", "noise_level"];
+
+// csv file with header naming columns, columns may go in any order and all but x, y and path may be omitted
+pub fn read_population(filename: &str) -> Vec<PopulationEntry> {
+    let file = File::open(filename).ok().expect("Can't open population file");
+    let mut lines = BufReader::new(file).lines().enumerate()
+        .map(|(line_no, line)| (line_no + 1, line.ok().expect("Can't read population file")))
+        .filter(|&(_, ref line)| !line.trim().is_empty() && !line.trim().starts_with('#'));
+
+    let header: Vec<String> = match lines.next() {
+        Some((_, line)) => line.split(',').map(|column| column.trim().to_string()).collect(),
+        None => return Vec::new()
+    };
+    for column in header.iter() {
+        if !COLUMNS.contains(&column.as_ref()) {
+            panic!("Unknown column in population file: {}", column);
+        }
+    }
+
+    let mut population = Vec::new();
+    for (line_no, line) in lines {
+        let values: Vec<&str> = line.split(',').map(|value| value.trim()).collect();
+        let value = |name: &str| -> Option<f64> {
+            header.iter().position(|column| column == name)
+                .and_then(|index| values.get(index))
+                .and_then(|value| if value.is_empty() { None } else { Some(value) })
+                .map(|value| value.parse::<f64>().ok().expect(format!("Invalid {} on line {} of population file", name, line_no).as_ref()))
+        };
+        let required = |name: &str| -> f64 {
+            value(name).expect(format!("Missing {} on line {} of population file", name, line_no).as_ref())
+        };
+        population.push(PopulationEntry{
+            x: required("x"),
+            y: required("y"),
+            floor: value("floor").unwrap_or(0_f64) as u8,
+            heading: value("heading"),
+            path_id: required("path") as u8,
+            target_index: value("target_index").unwrap_or(0_f64) as u16,
+            profile_id: value("profile").map(|profile_id| profile_id as u8),
+            radius: value("radius"),
+            target_speed: value("target_speed"),
+            repulsion_coeff: value("repulsion_coeff"),
+            forward_fov: value("forward_fov"),
+            backward_fov: value("backward_fov"),
+            noise_level: value("noise_level"),
+        });
+    }
+    population
+}
@@ -0,0 +1,91 @@
+extern crate anymap;
+
+use self::anymap::AnyMap;
+
+use ::configuration::DistributionValue;
+use ::configuration::ProfileDefinition;
+use ::configuration::SpawnProfileMix;
+
+pub const DEFAULT_PERSON_RADIUS: f64 = 0.4_f64;
+const DEFAULT_PROFILE_NAME: &'static str = "adult";
+
+#[derive(Debug,Clone)]
+pub struct Profile {
+    pub id: u8,
+    pub name: String,
+    radius: DistributionValue,
+    pub target_speed: Option<DistributionValue>,
+    pub repulsion_coeff: Option<DistributionValue>,
+    pub forward_fov: Option<DistributionValue>,
+    pub backward_fov: Option<DistributionValue>,
+}
+
+impl Profile {
+    fn new(definition: &ProfileDefinition) -> Profile {
+        let radius = match definition.radius {
+            Some(ref radius) => radius.clone(),
+            None => DistributionValue::UniformDistributionValue{ from: DEFAULT_PERSON_RADIUS, to: DEFAULT_PERSON_RADIUS }
+        };
+        Profile{ id: definition.id, name: definition.name.clone(), radius: radius,
+                 target_speed: definition.target_speed.clone(), repulsion_coeff: definition.repulsion_coeff.clone(),
+                 forward_fov: definition.forward_fov.clone(), backward_fov: definition.backward_fov.clone() }
+    }
+
+    fn default() -> Profile {
+        Profile{ id: 0, name: DEFAULT_PROFILE_NAME.to_string(),
+                 radius: DistributionValue::UniformDistributionValue{ from: DEFAULT_PERSON_RADIUS, to: DEFAULT_PERSON_RADIUS },
+                 target_speed: None, repulsion_coeff: None, forward_fov: None, backward_fov: None }
+    }
+
+    pub fn generate_radius(&self) -> f64 {
+        ::utils::distributions::generate(&self.radius).max(0.1)
+    }
+}
+
+pub struct Profiles {
+    profiles: Vec<Profile>,
+    mixes: Vec<SpawnProfileMix>,
+}
+
+impl Profiles {
+    pub fn new(configuration: &AnyMap) -> Profiles {
+        let profiles = match optional_config!(configuration, ProfileDefinitions) {
+            Some(definitions) => {
+                let mut profiles: Vec<Profile> = definitions.iter().map(|definition| Profile::new(definition)).collect();
+                profiles.sort_by(|a, b| a.id.cmp(&b.id));
+                profiles
+            },
+            None => vec![Profile::default()]
+        };
+        let mixes = optional_config!(configuration, SpawnProfileMixes).unwrap_or(Vec::new());
+        for mix in mixes.iter() {
+            if !profiles.iter().any(|profile| profile.id == mix.profile_id) {
+                panic!("Spawn area {} mixes in unknown profile {}", mix.spawn_id, mix.profile_id);
+            }
+        }
+        Profiles{ profiles: profiles, mixes: mixes }
+    }
+
+    pub fn get(&self, id: u8) -> &Profile {
+        self.profiles.iter().find(|profile| profile.id == id)
+            .expect(format!("Trying to access unknown profile {}", id).as_ref())
+    }
+
+    pub fn choose_for_spawn_area(&self, spawn_id: u8) -> &Profile {
+        let mix: Vec<&SpawnProfileMix> = self.mixes.iter().filter(|mix| mix.spawn_id == spawn_id).collect();
+        let total_ratio = mix.iter().fold(0_f64, |sum, item| sum + item.ratio);
+        if mix.is_empty() || total_ratio <= 0_f64 {
+            // no mix configured for this spawn area, fall back to the first profile
+            return &self.profiles[0];
+        }
+
+        let mut choice = ::utils::distributions::generate_uniform(0_f64, total_ratio);
+        for item in mix.iter() {
+            if choice < item.ratio {
+                return self.get(item.profile_id);
+            }
+            choice -= item.ratio;
+        }
+        self.get(mix[mix.len() - 1].profile_id)
+    }
+}
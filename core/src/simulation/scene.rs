@@ -1,56 +1,170 @@
 extern crate anymap;
 
+use std::collections::VecDeque;
+
 use self::anymap::AnyMap;
 
 use ::simulation::person::Person;
 use ::simulation::forces::Forces;
+use ::simulation::profiles::Profiles;
+use ::simulation::connectors::Connector;
+use ::simulation::services::ServicePoint;
+use ::simulation::services::ServiceState;
+use ::simulation::hazard::Hazard;
+use ::simulation::kinematics::Kinematics;
+use ::simulation::population::PopulationEntry;
+use ::simulation::placement::PoissonDiscSampler;
+use ::simulation::checkpoint::CheckpointReader;
+use ::simulation::checkpoint::CheckpointWriter;
 
 use ::utils::linelg::Line;
 use ::utils::linelg::Point;
 use ::utils::linelg::Rectangle;
-use ::utils::linelg::distance::DistanceTo; 
-pub const APPROX_PERSON_RADIUS: f64 = 0.4_f64;
+use ::utils::linelg::Polygon;
+use ::utils::linelg::Vector;
+use ::utils::linelg::distance::DistanceTo;
+
+use ::configuration::InitialPopulation;
 
 pub struct Scene {
     pub people: Vec<Person>,
-    pub geometry: Vec<Line>,
+    pub floors: Vec<Floor>,
     paths: Vec<Path>,
+    profiles: Profiles,
+    connectors: Vec<Connector>,
+    // connector hops between every pair of floors
+    floor_hops: Vec<Vec<Option<u8>>>,
+    services: Vec<ServicePoint>,
+    pub hazard: Option<Hazard>,
+    pub kinematics: Kinematics,
+    next_person_id: u32,
+    // happened since last take_events call
+    events: Vec<SceneEvent>,
     pub scale: f64,
     pub width: u16,
     pub height: u16,
 }
 
+#[derive(Debug,Clone)]
+pub enum SceneEvent {
+    Spawned(Person),
+    SpawnFailed{ spawn_id: u8, floor: u8, location: Point },
+    TargetReached{ person: Person, target_index: u16 },
+    Exited(Person),
+    // taken off the scene without reaching his targets
+    Removed(Person),
+}
+
 pub struct Path {
     pub id: u8,
     spawn_area: SpawnArea,
-    pub target_areas: Vec<Area>,
+    // alternative areas for every target in sequence
+    pub target_areas: Vec<Vec<Area>>,
+}
+
+impl Path {
+    pub fn target_area_for(&self, target_index: u16, point: &Point, floor: u8) -> Area {
+        let alternatives = &self.target_areas[target_index as usize];
+        // alternatives on the same floor are preferred
+        let on_floor = alternatives.iter().any(|area| area.enabled && area.floor == floor);
+        let nearest_area = nearest_area(alternatives.iter().filter(|area| area.enabled && (!on_floor || area.floor == floor)), point);
+        match nearest_area {
+            Some(area) => area.clone(),
+            // every alternative is disabled, wait for any of them to be enabled
+            None => alternatives[0].clone()
+        }
+    }
+}
+
+// floors are indexes of scene files, element pointing past them would crash deep inside simulation
+fn check_floor<T: ::std::fmt::Debug>(element: &T, floor: u8, floors_count: usize) {
+    if floor as usize >= floors_count {
+        panic!("Floor {} is out of {} scene floors in config: {:?}", floor, floors_count, element);
+    }
+}
+
+fn nearest_area<'a, I: Iterator<Item = &'a Area>>(areas: I, point: &Point) -> Option<&'a Area> {
+    let mut nearest_area: Option<&Area> = None;
+    for area in areas {
+        nearest_area = match nearest_area {
+            Some(nearest) if point.distance_sqr(&nearest.nearest_point(point)) <= point.distance_sqr(&area.nearest_point(point)) => Some(nearest),
+            _ => Some(area)
+        };
+    }
+    nearest_area
+}
+
+pub struct Floor {
+    pub geometry: Vec<Line>,
+    terrain_zones: Vec<TerrainZone>,
 }
 
 struct SpawnArea {
     area: Area,
     rate: f64,
     ticks_to_next_spawn: u16,
+    // people placed at once at escape start
+    initial_population: Option<InitialPopulation>,
+    // arrivals which found no free place, they enter in order as soon as place frees up
+    backlog: VecDeque<BlockedArrival>,
+}
+
+#[derive(Debug,Clone)]
+struct BlockedArrival {
+    arrived_at: f64,
+    profile_id: u8,
+    radius: f64,
+}
+
+struct TerrainZone {
+    polygon: Polygon,
+    speed_factor: f64,
+    // used when moving against zone direction (e.g. down the stairs)
+    reverse_speed_factor: f64,
+    direction: Vector,
+}
+
+impl TerrainZone {
+    fn speed_factor_for(&self, heading: f64) -> f64 {
+        let heading_direction = Vector::new(heading.cos(), heading.sin());
+        if ::utils::linelg::dot(&heading_direction, &self.direction) >= 0_f64 {
+            self.speed_factor
+        } else {
+            self.reverse_speed_factor
+        }
+    }
 }
 
 #[derive(Debug,Clone)]
 pub struct Area {
     pub p0: Point, pub p1: Point,
     pub sequence_no: u8,
+    pub floor: u8,
+    pub enabled: bool,
     rectangle: Rectangle,
 }
 
 impl Area {
-    fn new(p0: Point, p1: Point, sequence_no: u8) -> Area {
+    pub fn new(p0: Point, p1: Point, sequence_no: u8, floor: u8) -> Area {
         let rectangle = Rectangle::new_from_raw(
             p0.x, p0.y, p1.x, p1.y,
         );
-        Area{p0: p0, p1: p1, sequence_no: sequence_no, rectangle: rectangle}
+        Area{p0: p0, p1: p1, sequence_no: sequence_no, floor: floor, enabled: true, rectangle: rectangle}
+    }
+
+    pub fn has_bounds(&self, p0: &Point, p1: &Point) -> bool {
+        self.p0 == *p0 && self.p1 == *p1
     }
 
     pub fn nearest_point(&self, other: &Point) -> Point {
         other.nearest_point(&self.rectangle)
     }
 
+    pub fn contains(&self, p: &Point) -> bool {
+        p.x > self.p0.x && p.x < self.p1.x &&
+            p.y > self.p0.y && p.y < self.p1.y
+    }
+
     fn random_inside(&self) -> Point {
         Point::new(
             ::utils::distributions::generate_uniform(self.p0.x, self.p1.x),
@@ -66,15 +180,53 @@ impl Scene {
         let scene_height = config!(configuration, SceneHeight);
         let scene_scale = config!(configuration, SceneScale);
 
+        let floors_count = config!(configuration, SceneFilenames).len();
         let scene_walls = config!(configuration, SceneWalls);
         let scene_spawn_areas = config!(configuration, SceneSpawnAreas);
         let scene_target_areas = config!(configuration, SceneTargetAreas);
+        let scene_terrain_zones = optional_config!(configuration, SceneTerrainZones).unwrap_or(Vec::new());
         let spawn_rate = config!(configuration, SpawnRate);
+        let connectors_config = optional_config!(configuration, Connectors).unwrap_or(Vec::new());
 
-        let parsed_geometry = Scene::parse_walls(scene_walls);
-        let parsed_paths = Scene::parse_paths(scene_spawn_areas, scene_target_areas, spawn_rate);
+        for wall in scene_walls.iter() {
+            check_floor(wall, wall.floor, floors_count);
+        }
+        for spawn_area in scene_spawn_areas.iter() {
+            check_floor(spawn_area, spawn_area.floor, floors_count);
+        }
+        for target_area in scene_target_areas.iter() {
+            check_floor(target_area, target_area.floor, floors_count);
+        }
+        for connector in connectors_config.iter() {
+            check_floor(connector, connector.from_floor, floors_count);
+            check_floor(connector, connector.to_floor, floors_count);
+        }
+        for source in optional_config!(configuration, HazardSources).unwrap_or(Vec::new()).iter() {
+            check_floor(source, source.floor, floors_count);
+        }
+
+        let mut floors = Vec::new();
+        for floor in 0..floors_count {
+            let floor_walls = scene_walls.iter().filter(|wall| wall.floor as usize == floor).cloned().collect();
+            let floor_terrain_zones = scene_terrain_zones.iter().filter(|zone| zone.floor as usize == floor).cloned().collect();
+            floors.push(Floor{ geometry: Scene::parse_walls(floor_walls),
+                               terrain_zones: Scene::parse_terrain_zones(floor_terrain_zones) });
+        }
+        let initial_populations = optional_config!(configuration, SpawnAreaPopulations).unwrap_or(Vec::new());
+        let parsed_paths = Scene::parse_paths(scene_spawn_areas, scene_target_areas, spawn_rate, initial_populations);
+        let profiles = Profiles::new(configuration);
+        let connectors: Vec<Connector> = connectors_config.iter().map(|connector| Connector::new(connector)).collect();
+        let floor_hops = Scene::count_floor_hops(floors_count, &connectors);
+        let mut service_definitions = optional_config!(configuration, ServicePointDefinitions).unwrap_or(Vec::new());
+        service_definitions.sort_by(|a, b| a.id.cmp(&b.id));
+        let services = service_definitions.iter().map(|definition| ServicePoint::new(definition, scene_scale)).collect();
+        let hazard = Hazard::new(configuration, floors.iter().map(|floor| &floor.geometry).collect(),
+                                 scene_width, scene_height, scene_scale);
 
-        Scene{ people: Vec::new(), geometry: parsed_geometry, paths: parsed_paths,
+        Scene{ people: Vec::new(), floors: floors, paths: parsed_paths, profiles: profiles,
+               connectors: connectors, floor_hops: floor_hops, services: services, hazard: hazard,
+               kinematics: Kinematics::new(configuration, scene_scale), next_person_id: 0,
+               events: Vec::new(),
                scale: scene_scale, width: scene_width, height: scene_height }
     }
 
@@ -86,18 +238,67 @@ impl Scene {
         geometry
     }
 
-    fn parse_paths(spawn_areas: Vec<::configuration::SceneSpawnArea>, target_areas: Vec<::configuration::SceneTargetArea>, spawn_rate: f64) -> Vec<Path> {
+    // people riding connectors are off every floor till they get out
+    pub fn people_in_connectors(&self) -> Vec<&Person> {
+        self.connectors.iter().flat_map(|connector| connector.people().into_iter()).collect()
+    }
+
+    fn count_floor_hops(floors_count: usize, connectors: &Vec<Connector>) -> Vec<Vec<Option<u8>>> {
+        // breadth-first search from every floor over connectors
+        let mut floor_hops = Vec::new();
+        for from_floor in 0..floors_count {
+            let mut hops = vec![None; floors_count];
+            hops[from_floor] = Some(0_u8);
+            let mut frontier = vec![from_floor];
+            let mut hop = 0_u8;
+            while !frontier.is_empty() {
+                hop += 1;
+                let mut next_frontier = Vec::new();
+                for floor in frontier.iter() {
+                    for connector in connectors.iter().filter(|connector| connector.entry.floor as usize == *floor) {
+                        let to_floor = connector.exit.floor as usize;
+                        if to_floor < floors_count && hops[to_floor].is_none() {
+                            hops[to_floor] = Some(hop);
+                            next_frontier.push(to_floor);
+                        }
+                    }
+                }
+                frontier = next_frontier;
+            }
+            floor_hops.push(hops);
+        }
+        floor_hops
+    }
+
+    fn parse_terrain_zones(scene_terrain_zones: Vec<::configuration::SceneTerrainZone>) -> Vec<TerrainZone> {
+        let mut terrain_zones = Vec::new();
+        for scene_terrain_zone in scene_terrain_zones.iter() {
+            let points = scene_terrain_zone.points.iter().map(|&(x, y)| Point::new(x as f64, y as f64)).collect();
+            let direction = scene_terrain_zone.direction;
+            terrain_zones.push(TerrainZone{ polygon: Polygon::new(points),
+                                            speed_factor: scene_terrain_zone.speed_factor,
+                                            reverse_speed_factor: scene_terrain_zone.reverse_speed_factor,
+                                            direction: Vector::new(direction.cos(), direction.sin()) });
+        }
+        terrain_zones
+    }
+
+    fn parse_paths(spawn_areas: Vec<::configuration::SceneSpawnArea>, target_areas: Vec<::configuration::SceneTargetArea>, spawn_rate: f64,
+                   initial_populations: Vec<::configuration::SpawnAreaPopulation>) -> Vec<Path> {
         let mut paths = Vec::new();
         for scene_spawn_area in spawn_areas.iter() {
             let id = scene_spawn_area.id;
             let area = Area::new(
                 Point::new(scene_spawn_area.x0 as f64, scene_spawn_area.y0 as f64),
                 Point::new(scene_spawn_area.x1 as f64, scene_spawn_area.y1 as f64),
-                0
+                0, scene_spawn_area.floor
             );
             let spawn_area = SpawnArea{ area: area,
                                         rate: spawn_rate,
-                                        ticks_to_next_spawn: 1 };
+                                        ticks_to_next_spawn: 1,
+                                        initial_population: initial_populations.iter()
+                                            .find(|population| population.spawn_id == id).map(|population| population.population.clone()),
+                                        backlog: VecDeque::new() };
 
             let mut parsed_target_areas : Vec<Area> = Vec::new();
             for scene_target_area in target_areas.iter() {
@@ -105,70 +306,334 @@ impl Scene {
                     let target_area = Area::new(
                         Point::new(scene_target_area.x0 as f64, scene_target_area.y0 as f64),
                         Point::new(scene_target_area.x1 as f64, scene_target_area.y1 as f64),
-                        scene_target_area.sequence_no
+                        scene_target_area.sequence_no, scene_target_area.floor
                     );
                     parsed_target_areas.push(target_area)
                 }
             }
             parsed_target_areas.sort_by(|a, b| a.sequence_no.cmp(&b.sequence_no));
 
-            paths.push(Path{ id: id, spawn_area: spawn_area, target_areas: parsed_target_areas });
+            // areas sharing sequence number are alternatives for the same target
+            let mut grouped_target_areas : Vec<Vec<Area>> = Vec::new();
+            for target_area in parsed_target_areas.into_iter() {
+                let same_target = match grouped_target_areas.last() {
+                    Some(alternatives) => alternatives[0].sequence_no == target_area.sequence_no,
+                    None => false
+                };
+                if same_target {
+                    grouped_target_areas.last_mut().unwrap().push(target_area);
+                } else {
+                    grouped_target_areas.push(vec![target_area]);
+                }
+            }
+
+            paths.push(Path{ id: id, spawn_area: spawn_area, target_areas: grouped_target_areas });
         }
         paths
     }
 
-    pub fn spawn_people(&mut self, forces: &Forces, tick: f64) {
+    // returns waiting times of people who entered the scene during this tick
+    pub fn spawn_people(&mut self, forces: &Forces, tick: f64, current_time: f64) -> Vec<(usize, f64)> {
+        let mut waiting_times = Vec::new();
+        for path_index in 0..self.paths.len() {
+            loop {
+                let blocked_arrival = match self.paths[path_index].spawn_area.backlog.front() {
+                    Some(blocked_arrival) => blocked_arrival.clone(),
+                    None => break
+                };
+                match self.find_free_place(&self.paths[path_index].spawn_area.area, blocked_arrival.radius) {
+                    Some(point) => {
+                        self.paths[path_index].spawn_area.backlog.pop_front();
+                        self.add_spawned_person(forces, path_index, blocked_arrival.profile_id, blocked_arrival.radius, point, current_time);
+                        waiting_times.push((path_index, current_time - blocked_arrival.arrived_at));
+                    },
+                    None => break
+                }
+            }
+        }
+
+        for path_index in self.due_spawn_paths(tick, false).into_iter() {
+            let (profile_id, radius) = {
+                let profile = self.profiles.choose_for_spawn_area(self.paths[path_index].id);
+                (profile.id, profile.generate_radius())
+            };
+            // nobody overtakes people waiting in backlog
+            let place = if self.paths[path_index].spawn_area.backlog.is_empty() {
+                self.find_free_place(&self.paths[path_index].spawn_area.area, radius)
+            } else {
+                None
+            };
+            match place {
+                Some(point) => {
+                    self.add_spawned_person(forces, path_index, profile_id, radius, point, current_time);
+                    waiting_times.push((path_index, 0_f64));
+                },
+                None => {
+                    debug!("No free place in spawn area {}, person waits in backlog", self.paths[path_index].id);
+                    self.paths[path_index].spawn_area.backlog.push_back(BlockedArrival{ arrived_at: current_time, profile_id: profile_id, radius: radius });
+                    self.add_spawn_failed_event(path_index);
+                }
+            }
+        }
+        waiting_times
+    }
+
+    pub fn spawn_backlog_lengths(&self) -> Vec<usize> {
+        self.paths.iter().map(|path| path.spawn_area.backlog.len()).collect()
+    }
+
+    // areas with head count or density are filled, others get their first person as usual
+    pub fn spawn_initial_people(&mut self, forces: &Forces, tick: f64, current_time: f64) {
+        for path_index in 0..self.paths.len() {
+            let initial_population = self.paths[path_index].spawn_area.initial_population.clone();
+            if let Some(initial_population) = initial_population {
+                self.fill_spawn_area(forces, path_index, &initial_population, current_time);
+            }
+        }
+        for path_index in self.due_spawn_paths(tick, true).into_iter() {
+            self.spawn_in_path(forces, path_index, current_time);
+        }
+    }
+
+    fn due_spawn_paths(&mut self, tick: f64, skip_populated: bool) -> Vec<usize> {
         let mut paths_needed_spawn = Vec::new();
         let paths_count = self.paths.len();
 
         for (path, index) in self.paths.iter_mut().zip(0 .. paths_count) {
             let ref mut spawn_area = path.spawn_area;
+            if spawn_area.rate <= 0_f64 || (skip_populated && spawn_area.initial_population.is_some()) {
+                continue;
+            }
             spawn_area.ticks_to_next_spawn -= 1;
             if spawn_area.ticks_to_next_spawn == 0 {
                 paths_needed_spawn.push(index);
                 spawn_area.ticks_to_next_spawn = (1_f64 / spawn_area.rate / tick).ceil() as u16;
             }
         }
+        paths_needed_spawn
+    }
+
+    fn spawn_in_path(&mut self, forces: &Forces, path_index: usize, current_time: f64) {
+        let (profile_id, radius) = {
+            let profile = self.profiles.choose_for_spawn_area(self.paths[path_index].id);
+            (profile.id, profile.generate_radius())
+        };
+
+        let place = self.find_free_place(&self.paths[path_index].spawn_area.area, radius);
+        match place {
+            Some(point) => self.add_spawned_person(forces, path_index, profile_id, radius, point, current_time),
+            None => {
+                warn!("Couldn't find a place for a new person in 10 attempts, skipping ...");
+                self.add_spawn_failed_event(path_index);
+            }
+        }
+    }
+
+    fn fill_spawn_area(&mut self, forces: &Forces, path_index: usize, initial_population: &InitialPopulation, current_time: f64) {
+        let area = self.paths[path_index].spawn_area.area.clone();
+        let count = match *initial_population {
+            InitialPopulation::Count(count) => count,
+            InitialPopulation::Density(density) => {
+                let area_in_meters = (area.p1.x - area.p0.x) * (area.p1.y - area.p0.y) * self.scale * self.scale;
+                (density * area_in_meters).round() as u32
+            }
+        };
 
-        for path_index in paths_needed_spawn.iter() {
-            self.spawn_in_path(forces, *path_index);
+        let mut sampler = PoissonDiscSampler::new(area.p0, area.p1);
+        let mut placed = 0;
+        for _i in 0..count {
+            let (profile_id, radius) = {
+                let profile = self.profiles.choose_for_spawn_area(self.paths[path_index].id);
+                (profile.id, profile.generate_radius())
+            };
+            // people touch when their centers are closer than mean of their radiuses
+            let place = sampler.next_point(radius / 2_f64 / self.scale, |point| self.is_free(point, radius, area.floor) && self.is_away_from_walls(point, radius, area.floor));
+            match place {
+                Some(point) => {
+                    self.add_spawned_person(forces, path_index, profile_id, radius, point, current_time);
+                    placed += 1;
+                },
+                None => self.add_spawn_failed_event(path_index)
+            }
+        }
+        if placed < count {
+            warn!("Spawn area {} fits only {} of {} people", self.paths[path_index].id, placed, count);
         }
     }
 
-    fn spawn_in_path(&mut self, forces: &Forces, path_index: usize) {
+    fn add_spawned_person(&mut self, forces: &Forces, path_index: usize, profile_id: u8, radius: f64, point: Point, current_time: f64) {
+        let path = &self.paths[path_index];
+        let mut new_person = Person{
+            id: self.next_person_id,
+            coordinates: point.clone(),
+            heading: 0_f64,
+            velocity: Vector::zero(),
+            path_id: path.id,
+            profile_id: profile_id,
+            radius: radius,
+            floor: path.spawn_area.area.floor,
+            current_target_index: 0,
+            current_target_area: path.spawn_area.area.clone(),
+            forces_params: forces.generate_person_forces_param(self.profiles.get(profile_id)),
+            kinematics: self.kinematics.generate_person_kinematics(),
+            service_state: ServiceState::Walking,
+            visibility: 1_f64,
+            hazard_exposure: 0_f64,
+            noise: Vector::zero(),
+            progress: None,
+            spawned_at: current_time,
+            distance_walked: 0_f64,
+            target_times: Vec::new(),
+            stuck_time: 0_f64
+        };
+        self.next_person_id += 1;
+        new_person.current_target_area = self.route_target_area(&new_person);
+        let heading = new_person.current_target_area.p0 - point;
+        new_person.heading = heading.y.atan2(heading.x);
+        self.events.push(SceneEvent::Spawned(new_person.clone()));
+        self.people.push(new_person);
+    }
+
+    fn add_spawn_failed_event(&mut self, path_index: usize) {
         let path = &self.paths[path_index];
+        let ref area = path.spawn_area.area;
+        self.events.push(SceneEvent::SpawnFailed{ spawn_id: path.id, floor: area.floor, location: (area.p0 + area.p1) / 2_f64 });
+    }
+
+    fn is_away_from_walls(&self, p: &Point, radius: f64, floor: u8) -> bool {
+        let contact_distance = radius / 2_f64 / self.scale;
+        self.floors[floor as usize].geometry.iter().all(|wall| p.distance(wall) >= contact_distance)
+    }
+
+    // places people at given positions, they are not checked for overlapping
+    pub fn add_population(&mut self, forces: &Forces, population: Vec<PopulationEntry>, current_time: f64) {
+        for entry in population.into_iter() {
+            if entry.path_id as usize >= self.paths.len() {
+                panic!("Population references unknown path {}", entry.path_id);
+            }
+            if entry.target_index as usize >= self.paths[entry.path_id as usize].target_areas.len() {
+                panic!("Population references unknown target {} of path {}", entry.target_index, entry.path_id);
+            }
+            if entry.floor as usize >= self.floors.len() {
+                panic!("Population references unknown floor {}", entry.floor);
+            }
+
+            let (profile_id, radius, mut forces_params) = {
+                let profile = match entry.profile_id {
+                    Some(profile_id) => self.profiles.get(profile_id),
+                    None => self.profiles.choose_for_spawn_area(entry.path_id)
+                };
+                (profile.id, profile.generate_radius(), forces.generate_person_forces_param(profile))
+            };
+            forces_params.target_speed = entry.target_speed.unwrap_or(forces_params.target_speed);
+            forces_params.repulsion_coeff = entry.repulsion_coeff.unwrap_or(forces_params.repulsion_coeff);
+            forces_params.forward_fov = entry.forward_fov.unwrap_or(forces_params.forward_fov);
+            forces_params.backward_fov = entry.backward_fov.unwrap_or(forces_params.backward_fov);
+            forces_params.noise_level = entry.noise_level.unwrap_or(forces_params.noise_level);
+
+            let point = Point::new(entry.x / self.scale, entry.y / self.scale);
+            let mut person = Person{
+                id: self.next_person_id,
+                coordinates: point,
+                heading: 0_f64,
+                velocity: Vector::zero(),
+                path_id: entry.path_id,
+                profile_id: profile_id,
+                radius: entry.radius.unwrap_or(radius),
+                floor: entry.floor,
+                current_target_index: entry.target_index,
+                current_target_area: self.paths[entry.path_id as usize].spawn_area.area.clone(),
+                forces_params: forces_params,
+                kinematics: self.kinematics.generate_person_kinematics(),
+                service_state: ServiceState::Walking,
+                visibility: 1_f64,
+                hazard_exposure: 0_f64,
+                noise: Vector::zero(),
+                progress: None,
+                spawned_at: current_time,
+                distance_walked: 0_f64,
+                target_times: Vec::new(),
+                stuck_time: 0_f64
+            };
+            self.next_person_id += 1;
+            person.current_target_area = self.route_target_area(&person);
+            person.heading = match entry.heading {
+                Some(heading) => heading,
+                None => {
+                    let heading = person.current_target_area.nearest_point(&point) - point;
+                    heading.y.atan2(heading.x)
+                }
+            };
+            self.events.push(SceneEvent::Spawned(person.clone()));
+            self.people.push(person);
+        }
+    }
 
+    // moves person to a random free place at most distance away, never through walls
+    pub fn perturb_person(&mut self, index: usize, distance: f64) -> bool {
+        let (coordinates, radius, floor) = {
+            let ref person = self.people[index];
+            (person.coordinates.clone(), person.radius, person.floor)
+        };
+        for _i in 1..10 {
+            let angle = ::utils::distributions::generate_uniform(0_f64, 2_f64 * ::std::f64::consts::PI);
+            let step = ::utils::distributions::generate_uniform(distance / 2_f64, distance);
+            let try_point = coordinates + Vector::new(angle.cos(), angle.sin()) * step;
+            let step_line = Line::new(coordinates.clone(), try_point.clone());
+            if self.floors[floor as usize].geometry.iter().any(|wall| wall.intersects(&step_line)) ||
+                !self.is_away_from_walls(&try_point, radius, floor) {
+                continue;
+            }
+            let free = self.people.iter().enumerate()
+                .filter(|&(other_index, other)| other_index != index && other.floor == floor)
+                .all(|(_other_index, other)| other.coordinates.distance_sqr(&try_point) >= ((other.radius + radius) / 2_f64 / self.scale).powi(2));
+            if free {
+                self.people[index].coordinates = try_point;
+                return true;
+            }
+        }
+        false
+    }
+
+    // sends person to another enabled alternative of his current target on his floor
+    pub fn reroute_person(&mut self, index: usize) -> bool {
+        let alternatives: Vec<Area> = {
+            let ref person = self.people[index];
+            let ref current = person.current_target_area;
+            self.paths[person.path_id as usize].target_areas[person.current_target_index as usize].iter()
+                .filter(|area| area.enabled && area.floor == person.floor && !area.has_bounds(&current.p0, &current.p1))
+                .cloned().collect()
+        };
+        if alternatives.is_empty() {
+            return false;
+        }
+        let choice = (::utils::distributions::generate_uniform(0_f64, alternatives.len() as f64) as usize).min(alternatives.len() - 1);
+        self.people[index].current_target_area = alternatives[choice].clone();
+        true
+    }
+
+    pub fn remove_person(&mut self, index: usize) {
+        let person = self.people.remove(index);
+        self.events.push(SceneEvent::Removed(person));
+    }
+
+    fn find_free_place(&self, area: &Area, radius: f64) -> Option<Point> {
         let mut coordinates: Option<Point> = None;
         for _i in 1..10 {
-            let try_point = path.spawn_area.area.random_inside();
-            if self.is_free(&try_point) {
+            let try_point = area.random_inside();
+            if self.is_free(&try_point, radius, area.floor) {
                 coordinates = Some(try_point);
                 break;
             }
         }
-
-        match coordinates {
-            Some(point) => {
-                let current_target_area = path.target_areas[0].clone();
-                let heading = current_target_area.p0 - point;
-                let new_person = Person{
-                    coordinates: point.clone(),
-                    heading: heading.y.atan2(heading.x),
-                    path_id: path.id,
-                    current_target_index: 0,
-                    current_target_area: current_target_area,
-                    forces_params: forces.generate_person_forces_param()
-                };
-                self.people.push(new_person);
-            },
-            None => warn!("Couldn't find a place for a new person in 10 attempts, skipping ...")
-        }
+        coordinates
     }
 
-    fn is_free(&self, p: &Point) -> bool {
+    fn is_free(&self, p: &Point, radius: f64, floor: u8) -> bool {
         let mut free = true;
-        for person in self.people.iter() {
-            if person.coordinates.distance_sqr(p) < (APPROX_PERSON_RADIUS / self.scale).powi(2) {
+        for person in self.people.iter().filter(|person| person.floor == floor) {
+            let contact_distance = (person.radius + radius) / 2_f64;
+            if person.coordinates.distance_sqr(p) < (contact_distance / self.scale).powi(2) {
                 free = false;
                 break;
             }
@@ -176,32 +641,306 @@ impl Scene {
         free
     }
 
-    pub fn process_reached_destination_people(&mut self) -> Vec<Person> {
-        let cloned_people = self.people.clone();
+    // area person should walk to now: his current target or connector towards target floor
+    fn route_target_area(&self, person: &Person) -> Area {
+        let ref path = self.paths[person.path_id as usize];
+        let target_area = path.target_area_for(person.current_target_index, &person.coordinates, person.floor);
+        if target_area.floor == person.floor {
+            return target_area;
+        }
+
+        let ref floor_hops = self.floor_hops;
+        let hops_to_target = |connector: &&Connector| floor_hops[connector.exit.floor as usize][target_area.floor as usize];
+        let best_hops = self.connectors.iter()
+            .filter(|connector| connector.entry.floor == person.floor)
+            .filter_map(|connector| hops_to_target(&connector)).min();
+        let connector_entries = self.connectors.iter()
+            .filter(|connector| connector.entry.floor == person.floor && best_hops.is_some() && hops_to_target(connector) == best_hops)
+            .map(|connector| &connector.entry);
+        match nearest_area(connector_entries, &person.coordinates) {
+            Some(entry) => entry.clone(),
+            None => {
+                warn!("No connector leads from floor {} to floor {}", person.floor, target_area.floor);
+                target_area
+            }
+        }
+    }
+
+    pub fn process_reached_destination_people(&mut self, current_time: f64) -> Vec<Person> {
+        let people = ::std::mem::replace(&mut self.people, Vec::new());
         let mut reached_destination_people = Vec::new();
-        self.people = cloned_people.into_iter().filter_map(|mut person|
-            if person.reached_destination(&self.paths[person.path_id as usize]) {
-                person.current_target_index += 1;
-                let ref path = self.paths[person.path_id as usize];
-                if (person.current_target_index as usize) < path.target_areas.len() {
-                    person.current_target_area = path.target_areas[person.current_target_index as usize].clone();
-                    // person has next target, do not filter him
-                    Some(person)
-                } else {
-                    // person reached his final target, save him for returning & filter out from people
-                    reached_destination_people.push(person);
-                    None
+        for mut person in people.into_iter() {
+            let served = person.service_state == ServiceState::Served;
+            if !served && (!person.reached_destination() || person.is_waiting_for_service() || self.is_service_area(&person.current_target_area)) {
+                // person in on the way to his next target or waits for service, do not filter him
+                self.people.push(person);
+                continue;
+            }
+            person.service_state = ServiceState::Walking;
+
+            let target_floor = self.paths[person.path_id as usize]
+                .target_area_for(person.current_target_index, &person.coordinates, person.floor).floor;
+            if target_floor != person.floor {
+                // person reached connector to another floor, he waits at entry while connector is full
+                match self.connectors.iter().position(|connector| connector.is_entry(&person.current_target_area)) {
+                    Some(connector_index) => match self.connectors[connector_index].board(person) {
+                        Ok(()) => (),
+                        Err(person) => self.people.push(person)
+                    },
+                    None => self.people.push(person)
                 }
+                continue;
+            }
+
+            person.target_times.push(current_time);
+            self.events.push(SceneEvent::TargetReached{ person: person.clone(), target_index: person.current_target_index });
+            person.current_target_index += 1;
+            if (person.current_target_index as usize) < self.paths[person.path_id as usize].target_areas.len() {
+                person.current_target_area = self.route_target_area(&person);
+                // person has next target, do not filter him
+                self.people.push(person);
             } else {
-                // person in on the way to his next target, do not filter him
-                Some(person)
+                // person reached his final target, save him for returning & filter out from people
+                self.events.push(SceneEvent::Exited(person.clone()));
+                reached_destination_people.push(person);
             }
-        ).collect();
+        }
 
         return reached_destination_people;
     }
 
-    pub fn get_density_map(&self) -> Vec<Vec<f64>> {
+    pub fn take_events(&mut self) -> Vec<SceneEvent> {
+        ::std::mem::replace(&mut self.events, Vec::new())
+    }
+
+    pub fn update_connectors(&mut self, tick: f64) {
+        for connector_index in 0..self.connectors.len() {
+            let arrived = self.connectors[connector_index].update(tick);
+            for mut person in arrived.into_iter() {
+                let exit = self.connectors[connector_index].exit.clone();
+                match self.find_free_place(&exit, person.radius) {
+                    Some(point) => {
+                        person.coordinates = point;
+                        person.floor = exit.floor;
+                        person.current_target_area = self.route_target_area(&person);
+                        self.people.push(person);
+                    },
+                    None => self.connectors[connector_index].hold_at_exit(person)
+                }
+            }
+        }
+    }
+
+    fn is_service_area(&self, area: &Area) -> bool {
+        self.services.iter().any(|service| service.is_service_area(area))
+    }
+
+    // returns waiting times of people who were called to a server during this tick
+    pub fn update_services(&mut self, tick: f64, current_time: f64) -> Vec<(usize, f64)> {
+        let mut waiting_times = Vec::new();
+        for person in self.people.iter_mut() {
+            match person.service_state.clone() {
+                ServiceState::Walking => {
+                    let service_index = match self.services.iter().position(|service| service.is_service_area(&person.current_target_area)) {
+                        Some(service_index) => service_index,
+                        None => continue
+                    };
+                    let ref mut service = self.services[service_index];
+                    if person.reached_destination() && service.is_idle() {
+                        service.take_server();
+                        waiting_times.push((service_index, 0_f64));
+                        person.service_state = ServiceState::InService{ service_index: service_index, remaining: service.generate_dwell_time() };
+                    } else if person.reached_destination() || (person.floor == service.area.floor && service.should_join_queue(&person.coordinates)) {
+                        service.join_queue(person.id);
+                        person.service_state = ServiceState::Queued{ service_index: service_index, queued_at: current_time };
+                    }
+                },
+                ServiceState::Called{ service_index } => {
+                    if person.reached_destination() {
+                        let remaining = self.services[service_index].generate_dwell_time();
+                        person.service_state = ServiceState::InService{ service_index: service_index, remaining: remaining };
+                    }
+                },
+                ServiceState::InService{ service_index, remaining } => {
+                    if remaining - tick <= 0_f64 {
+                        self.services[service_index].release_server();
+                        person.service_state = ServiceState::Served;
+                    } else {
+                        person.service_state = ServiceState::InService{ service_index: service_index, remaining: remaining - tick };
+                    }
+                },
+                _ => ()
+            }
+        }
+
+        // free servers call people from queue heads, others move up to their places in queue
+        for (service_index, service) in self.services.iter_mut().enumerate() {
+            while let Some(person_id) = service.call_next() {
+                if let Some(person) = self.people.iter_mut().find(|person| person.id == person_id) {
+                    if let ServiceState::Queued{ queued_at, .. } = person.service_state {
+                        waiting_times.push((service_index, current_time - queued_at));
+                    }
+                    person.service_state = ServiceState::Called{ service_index: service_index };
+                    person.current_target_area = service.area.clone();
+                }
+            }
+            for (slot, person_id) in service.queued_people().iter().enumerate() {
+                if let Some(person) = self.people.iter_mut().find(|person| person.id == *person_id) {
+                    person.current_target_area = service.slot_area(slot);
+                }
+            }
+        }
+        waiting_times
+    }
+
+    pub fn service_queue_lengths(&self) -> Vec<usize> {
+        self.services.iter().map(|service| service.queue_length()).collect()
+    }
+
+    // people walking the scene and people inside connectors
+    pub fn people_count(&self) -> usize {
+        self.connectors.iter().fold(self.people.len(), |count, connector| count + connector.people_count())
+    }
+
+    // everyone who ever entered the scene
+    pub fn spawned_count(&self) -> u32 {
+        self.next_person_id
+    }
+
+    pub fn speed_factor_for(&self, person: &Person) -> f64 {
+        let mut speed_factor = 1_f64;
+        for terrain_zone in self.floors[person.floor as usize].terrain_zones.iter() {
+            if terrain_zone.polygon.contains(&person.coordinates) {
+                speed_factor *= terrain_zone.speed_factor_for(person.heading);
+            }
+        }
+        if let Some(ref hazard) = self.hazard {
+            speed_factor *= hazard.speed_factor(hazard.concentration_at(person.floor, &person.coordinates));
+        }
+        speed_factor
+    }
+
+    pub fn update_hazard(&mut self, tick: f64, current_time: f64) {
+        if let Some(ref mut hazard) = self.hazard {
+            hazard.update(tick, current_time);
+            for person in self.people.iter_mut() {
+                let concentration = hazard.concentration_at(person.floor, &person.coordinates);
+                person.hazard_exposure += concentration * tick;
+                person.visibility = hazard.visibility(concentration);
+            }
+        }
+    }
+
+    pub fn save_state(&self, writer: &mut CheckpointWriter) {
+        writer.write_u32(self.next_person_id);
+        writer.write_people(&self.people);
+        for floor in self.floors.iter() {
+            writer.write_usize(floor.geometry.len());
+            for wall in floor.geometry.iter() {
+                writer.write_line(wall);
+            }
+        }
+        for path in self.paths.iter() {
+            writer.write_f64(path.spawn_area.rate);
+            writer.write_u16(path.spawn_area.ticks_to_next_spawn);
+            writer.write_usize(path.spawn_area.backlog.len());
+            for blocked_arrival in path.spawn_area.backlog.iter() {
+                writer.write_f64(blocked_arrival.arrived_at);
+                writer.write_u8(blocked_arrival.profile_id);
+                writer.write_f64(blocked_arrival.radius);
+            }
+            for area in path.target_areas.iter().flat_map(|alternatives| alternatives.iter()) {
+                writer.write_bool(area.enabled);
+            }
+        }
+        for connector in self.connectors.iter() {
+            connector.save_state(writer);
+        }
+        for service in self.services.iter() {
+            service.save_state(writer);
+        }
+        if let Some(ref hazard) = self.hazard {
+            hazard.save_state(writer);
+        }
+    }
+
+    pub fn restore_state(&mut self, reader: &mut CheckpointReader) {
+        self.next_person_id = reader.read_u32();
+        self.people = reader.read_people();
+        for floor in self.floors.iter_mut() {
+            let walls_count = reader.read_usize();
+            floor.geometry = (0..walls_count).map(|_i| reader.read_line()).collect();
+        }
+        for path in self.paths.iter_mut() {
+            path.spawn_area.rate = reader.read_f64();
+            path.spawn_area.ticks_to_next_spawn = reader.read_u16();
+            let backlog_length = reader.read_usize();
+            path.spawn_area.backlog = (0..backlog_length).map(|_i|
+                BlockedArrival{ arrived_at: reader.read_f64(), profile_id: reader.read_u8(), radius: reader.read_f64() }
+            ).collect();
+            for area in path.target_areas.iter_mut().flat_map(|alternatives| alternatives.iter_mut()) {
+                area.enabled = reader.read_bool();
+            }
+        }
+        for connector in self.connectors.iter_mut() {
+            connector.restore_state(reader);
+        }
+        for service in self.services.iter_mut() {
+            service.restore_state(reader);
+        }
+        if let Some(ref mut hazard) = self.hazard {
+            hazard.restore_state(reader);
+            for (floor_index, floor) in self.floors.iter().enumerate() {
+                hazard.update_walls(floor_index as u8, &floor.geometry);
+            }
+        }
+        self.events.clear();
+    }
+
+    pub fn add_wall(&mut self, wall: Line, floor: u8) {
+        self.floors[floor as usize].geometry.push(wall);
+        if let Some(ref mut hazard) = self.hazard {
+            hazard.update_walls(floor, &self.floors[floor as usize].geometry);
+        }
+    }
+
+    pub fn remove_wall(&mut self, wall: &Line, floor: u8) {
+        let reversed_wall = -*wall;
+        self.floors[floor as usize].geometry.retain(|line| *line != *wall && *line != reversed_wall);
+        if let Some(ref mut hazard) = self.hazard {
+            hazard.update_walls(floor, &self.floors[floor as usize].geometry);
+        }
+    }
+
+    pub fn set_target_area_enabled(&mut self, p0: &Point, p1: &Point, floor: u8, enabled: bool) {
+        for path in self.paths.iter_mut() {
+            for alternatives in path.target_areas.iter_mut() {
+                for area in alternatives.iter_mut().filter(|area| area.floor == floor && area.has_bounds(p0, p1)) {
+                    area.enabled = enabled;
+                }
+            }
+        }
+
+        // people choose between enabled alternatives of their current targets again
+        let people = ::std::mem::replace(&mut self.people, Vec::new());
+        for mut person in people.into_iter() {
+            person.current_target_area = self.route_target_area(&person);
+            self.people.push(person);
+        }
+    }
+
+    pub fn set_spawn_rate(&mut self, spawn_id: Option<u8>, rate: f64, tick: f64) {
+        for path in self.paths.iter_mut().filter(|path| spawn_id.map_or(true, |id| id == path.id)) {
+            let ref mut spawn_area = path.spawn_area;
+            spawn_area.rate = rate;
+            if rate > 0_f64 {
+                let ticks_to_next_spawn = (1_f64 / rate / tick).ceil() as u16;
+                spawn_area.ticks_to_next_spawn = spawn_area.ticks_to_next_spawn.min(ticks_to_next_spawn).max(1);
+            }
+        }
+    }
+
+    pub fn get_density_map(&self, floor: u8) -> Vec<Vec<f64>> {
         const KERNEL_C : f64 = 2_f64;
 
         let mut res = Vec::new();
@@ -216,7 +955,7 @@ impl Scene {
         }
 
         let effective_c = (KERNEL_C / self.scale).round() as i32;
-        for person in &self.people {
+        for person in self.people.iter().filter(|person| person.floor == floor) {
             for i in person.coordinates.y as i32 - 3 * effective_c .. person.coordinates.y as i32 + 3 * effective_c {
                 for j in person.coordinates.x as i32 - 3 * effective_c .. person.coordinates.x as i32 + 3 * effective_c {
                     if i > 0 && i < self.height as i32 &&
@@ -0,0 +1,41 @@
+extern crate anymap;
+
+use self::anymap::AnyMap;
+
+use ::configuration::ScheduleAction;
+use ::configuration::ScheduleEvent;
+
+use ::simulation::checkpoint::CheckpointReader;
+use ::simulation::checkpoint::CheckpointWriter;
+
+pub struct Schedule {
+    events: Vec<ScheduleEvent>,
+    next_event: usize,
+}
+
+impl Schedule {
+    pub fn new(configuration: &AnyMap) -> Schedule {
+        let mut events = optional_config!(configuration, ScheduleEvents).unwrap_or(Vec::new());
+        // stable sort keeps events with the same time in the described order
+        events.sort_by(|a, b| a.time.partial_cmp(&b.time).expect("Invalid schedule event time"));
+        Schedule{ events: events, next_event: 0 }
+    }
+
+    pub fn take_due_actions(&mut self, current_time: f64, tick: f64) -> Vec<ScheduleAction> {
+        let mut due_actions = Vec::new();
+        // event is due on the tick nearest to its time
+        while self.next_event < self.events.len() && self.events[self.next_event].time < current_time + tick / 2_f64 {
+            due_actions.push(self.events[self.next_event].action.clone());
+            self.next_event += 1;
+        }
+        due_actions
+    }
+
+    pub fn save_state(&self, writer: &mut CheckpointWriter) {
+        writer.write_usize(self.next_event);
+    }
+
+    pub fn restore_state(&mut self, reader: &mut CheckpointReader) {
+        self.next_event = reader.read_usize();
+    }
+}
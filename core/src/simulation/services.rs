@@ -0,0 +1,183 @@
+use std::collections::VecDeque;
+
+use ::configuration::DistributionValue;
+use ::configuration::ServicePointDefinition;
+
+use ::simulation::scene::Area;
+use ::simulation::checkpoint::CheckpointReader;
+use ::simulation::checkpoint::CheckpointWriter;
+
+use ::utils::linelg::Point;
+use ::utils::linelg::Vector;
+use ::utils::linelg::distance::DistanceTo;
+
+// distance between people standing in queue, meters
+const QUEUE_SPACING: f64 = 0.8_f64;
+// person approaching service point joins queue when he is that close to its tail, meters
+const QUEUE_JOIN_DISTANCE: f64 = 2_f64;
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum ServiceState {
+    Walking,
+    Queued{ service_index: usize, queued_at: f64 },
+    // server is free, person walks from queue to service point
+    Called{ service_index: usize },
+    InService{ service_index: usize, remaining: f64 },
+    Served,
+}
+
+pub struct ServicePoint {
+    pub name: String,
+    pub area: Area,
+    servers: u16,
+    // servers taken by called people and people being served
+    busy_servers: u16,
+    dwell_time: DistributionValue,
+    // ids of queued people, first one is called next
+    queue: VecDeque<u32>,
+    queue_head: Point,
+    queue_direction: Vector,
+    queue_spacing: f64,
+    join_distance: f64,
+}
+
+impl ServicePoint {
+    pub fn new(definition: &ServicePointDefinition, scale: f64) -> ServicePoint {
+        let area = Area::new(
+            Point::new(definition.x0 as f64, definition.y0 as f64),
+            Point::new(definition.x1 as f64, definition.y1 as f64),
+            0, definition.floor
+        );
+        let queue_direction = Vector::new(definition.queue_direction.cos(), definition.queue_direction.sin());
+        // queue starts at the side of service point it grows from
+        let center = (area.p0 + area.p1) / 2_f64;
+        let half_extent = queue_direction.x.abs() * (area.p1.x - area.p0.x) / 2_f64 +
+            queue_direction.y.abs() * (area.p1.y - area.p0.y) / 2_f64;
+        let queue_head = center + queue_direction * half_extent;
+
+        ServicePoint{ name: definition.name.clone(), area: area, servers: definition.servers.max(1), busy_servers: 0,
+                      dwell_time: definition.dwell_time.clone(), queue: VecDeque::new(),
+                      queue_head: queue_head, queue_direction: queue_direction,
+                      queue_spacing: QUEUE_SPACING / scale, join_distance: QUEUE_JOIN_DISTANCE / scale }
+    }
+
+    pub fn is_service_area(&self, area: &Area) -> bool {
+        area.floor == self.area.floor && area.has_bounds(&self.area.p0, &self.area.p1)
+    }
+
+    pub fn queue_length(&self) -> usize {
+        self.queue.len()
+    }
+
+    // nobody waits and a server is free, person can be served right away
+    pub fn is_idle(&self) -> bool {
+        self.queue.is_empty() && self.busy_servers < self.servers
+    }
+
+    pub fn should_join_queue(&self, p: &Point) -> bool {
+        !self.is_idle() && p.distance(&self.slot_point(self.queue.len())) < self.join_distance
+    }
+
+    pub fn join_queue(&mut self, person_id: u32) {
+        self.queue.push_back(person_id);
+    }
+
+    pub fn take_server(&mut self) {
+        self.busy_servers += 1;
+    }
+
+    pub fn release_server(&mut self) {
+        self.busy_servers -= 1;
+    }
+
+    pub fn call_next(&mut self) -> Option<u32> {
+        if self.busy_servers >= self.servers {
+            return None;
+        }
+        let next = self.queue.pop_front();
+        if next.is_some() {
+            self.busy_servers += 1;
+        }
+        next
+    }
+
+    pub fn queued_people(&self) -> Vec<u32> {
+        self.queue.iter().cloned().collect()
+    }
+
+    pub fn generate_dwell_time(&self) -> f64 {
+        ::utils::distributions::generate(&self.dwell_time).max(0_f64)
+    }
+
+    // small area where queued person with given place in queue stands
+    pub fn slot_area(&self, slot: usize) -> Area {
+        let slot_point = self.slot_point(slot);
+        let half_size = Vector::new(self.queue_spacing / 4_f64, self.queue_spacing / 4_f64);
+        Area::new(slot_point - half_size, slot_point + half_size, 0, self.area.floor)
+    }
+
+    pub fn save_state(&self, writer: &mut CheckpointWriter) {
+        writer.write_u16(self.busy_servers);
+        writer.write_usize(self.queue.len());
+        for person_id in self.queue.iter() {
+            writer.write_u32(*person_id);
+        }
+    }
+
+    pub fn restore_state(&mut self, reader: &mut CheckpointReader) {
+        self.busy_servers = reader.read_u16();
+        let queue_length = reader.read_usize();
+        self.queue = (0..queue_length).map(|_i| reader.read_u32()).collect();
+    }
+
+    fn slot_point(&self, slot: usize) -> Point {
+        self.queue_head + self.queue_direction * (self.queue_spacing * (slot as f64 + 0.5_f64))
+    }
+}
+
+#[cfg(test)]
+fn test_service_point(servers: u16) -> ServicePoint {
+    let definition = ServicePointDefinition{ id: 0, name: String::from("desk"), floor: 0, x0: 40, y0: 10, x1: 60, y1: 20,
+                                             servers: servers, queue_direction: ::std::f64::consts::PI / 2_f64,
+                                             dwell_time: DistributionValue::UniformDistributionValue{ from: 2_f64, to: 4_f64 } };
+    ServicePoint::new(&definition, 0.05_f64)
+}
+
+#[test]
+fn test_queue_served_in_joining_order() {
+    let mut service_point = test_service_point(2);
+    assert!(service_point.is_idle());
+    for person_id in [7, 3, 5, 1].iter() {
+        service_point.join_queue(*person_id);
+    }
+    assert_eq!(service_point.call_next(), Some(7));
+    assert_eq!(service_point.call_next(), Some(3));
+    // both servers are busy until someone is done
+    assert_eq!(service_point.call_next(), None);
+    assert_eq!(service_point.queued_people(), vec![5, 1]);
+    service_point.release_server();
+    assert_eq!(service_point.call_next(), Some(5));
+    assert_eq!(service_point.queue_length(), 1);
+}
+
+#[test]
+fn test_dwell_time_follows_distribution() {
+    let service_point = test_service_point(1);
+    for _i in 0..100 {
+        let dwell_time = service_point.generate_dwell_time();
+        assert!(dwell_time >= 2_f64 && dwell_time <= 4_f64);
+    }
+}
+
+#[test]
+fn test_queue_grows_away_from_service_point() {
+    let mut service_point = test_service_point(1);
+    service_point.take_server();
+    // queue grows down from the bottom side, 0.8 meters is 16 pixels apart
+    assert!(service_point.should_join_queue(&Point::new(50_f64, 30_f64)));
+    assert!(!service_point.should_join_queue(&Point::new(50_f64, 80_f64)));
+    let first = service_point.slot_area(0);
+    let second = service_point.slot_area(1);
+    assert!(first.contains(&Point::new(50_f64, 28_f64)));
+    assert!(second.contains(&Point::new(50_f64, 44_f64)));
+}
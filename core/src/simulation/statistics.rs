@@ -4,9 +4,48 @@ use std::f64;
 
 use self::anymap::AnyMap;
 use ::simulation::person::Person;
+use ::simulation::termination::TerminationReason;
+use ::simulation::checkpoint::CheckpointReader;
+use ::simulation::checkpoint::CheckpointWriter;
 
 pub struct Statistics {
+    // from spawn to reaching the last target
     pub travel_time: NumStatisticItem,
+    // meters walked by people who reached the last target
+    pub travel_distance: NumStatisticItem,
+    // mean speed of every such person, meters per second
+    pub travel_speed: NumStatisticItem,
+    // travel time of people who left the scene from every floor
+    pub travel_time_by_floor: Vec<NumStatisticItem>,
+    pub service_points: Vec<ServicePointStatistics>,
+    // hazard dose of people who left the scene or stayed there till the end
+    pub hazard_exposure: Option<NumStatisticItem>,
+    pub spawn_areas: Vec<SpawnAreaStatistics>,
+    // present when stuck detection is enabled
+    pub stuck: Option<StuckStatistics>,
+    // known once simulation is finished
+    pub termination_reason: Option<TerminationReason>,
+}
+
+pub struct StuckStatistics {
+    // one person may be flagged many times
+    pub flagged: u32,
+    pub removed: u32,
+}
+
+pub struct SpawnAreaStatistics {
+    pub spawn_id: u8,
+    // time from arrival to entering the scene, zero for those who entered at once
+    pub waiting_time: NumStatisticItem,
+    // sampled every tick
+    pub backlog_size: NumStatisticItem,
+}
+
+pub struct ServicePointStatistics {
+    pub name: String,
+    pub waiting_time: NumStatisticItem,
+    // sampled every tick
+    pub queue_length: NumStatisticItem,
 }
 
 pub struct NumStatisticItem {
@@ -50,13 +89,129 @@ impl NumStatisticItem {
 }
 
 impl Statistics {
-    pub fn new(_configuration: &AnyMap) -> Statistics {
-        Statistics{ travel_time: NumStatisticItem::new() }
+    pub fn new(configuration: &AnyMap) -> Statistics {
+        let floors_count = config!(configuration, SceneFilenames).len();
+        let mut service_definitions = optional_config!(configuration, ServicePointDefinitions).unwrap_or(Vec::new());
+        service_definitions.sort_by(|a, b| a.id.cmp(&b.id));
+        let service_points = service_definitions.iter().map(|definition|
+            ServicePointStatistics{ name: definition.name.clone(),
+                                    waiting_time: NumStatisticItem::new(), queue_length: NumStatisticItem::new() }
+        ).collect();
+        let spawn_areas = config!(configuration, SceneSpawnAreas).iter().map(|spawn_area|
+            SpawnAreaStatistics{ spawn_id: spawn_area.id, waiting_time: NumStatisticItem::new(), backlog_size: NumStatisticItem::new() }
+        ).collect();
+        Statistics{ travel_time: NumStatisticItem::new(),
+                    travel_distance: NumStatisticItem::new(),
+                    travel_speed: NumStatisticItem::new(),
+                    travel_time_by_floor: (0..floors_count).map(|_floor| NumStatisticItem::new()).collect(),
+                    service_points: service_points,
+                    hazard_exposure: optional_config!(configuration, HazardSources).map(|_sources| NumStatisticItem::new()),
+                    spawn_areas: spawn_areas,
+                    stuck: match optional_config!(configuration, StuckDetectionEnabled) {
+                        Some(true) => Some(StuckStatistics{ flagged: 0, removed: 0 }),
+                        _ => None
+                    },
+                    termination_reason: None }
+    }
+
+    pub fn update_from_service_points(&mut self, waiting_times: Vec<(usize, f64)>, queue_lengths: Vec<usize>) {
+        for &(service_index, waiting_time) in waiting_times.iter() {
+            self.service_points[service_index].waiting_time.update_from_value(waiting_time);
+        }
+        for (service_point, queue_length) in self.service_points.iter_mut().zip(queue_lengths.iter()) {
+            service_point.queue_length.update_from_value(*queue_length as f64);
+        }
+    }
+
+    pub fn update_from_spawn_areas(&mut self, waiting_times: Vec<(usize, f64)>, backlog_lengths: Vec<usize>) {
+        for &(spawn_area_index, waiting_time) in waiting_times.iter() {
+            self.spawn_areas[spawn_area_index].waiting_time.update_from_value(waiting_time);
+        }
+        for (spawn_area, backlog_length) in self.spawn_areas.iter_mut().zip(backlog_lengths.iter()) {
+            spawn_area.backlog_size.update_from_value(*backlog_length as f64);
+        }
+    }
+
+    pub fn update_from_reached_destination_people(&mut self, people: Vec<Person>, current_time: f64, scale: f64) {
+        for person in people.iter() {
+            let travel_time = current_time - person.spawned_at;
+            let distance = person.distance_walked * scale;
+            self.travel_time.update_from_value(travel_time);
+            self.travel_time_by_floor[person.floor as usize].update_from_value(travel_time);
+            self.travel_distance.update_from_value(distance);
+            if travel_time > 0_f64 {
+                self.travel_speed.update_from_value(distance / travel_time);
+            }
+        }
+        self.update_hazard_exposure(people.iter());
+    }
+
+    pub fn update_from_stuck_people(&mut self, flagged: usize, removed_people: &Vec<Person>) {
+        if let Some(ref mut stuck) = self.stuck {
+            stuck.flagged += flagged as u32;
+            stuck.removed += removed_people.len() as u32;
+        }
+        self.update_hazard_exposure(removed_people.iter());
+    }
+
+    // people left on scene and inside connectors
+    pub fn update_from_remaining_people<'a, I: Iterator<Item = &'a Person>>(&mut self, people: I) {
+        self.update_hazard_exposure(people);
+    }
+
+    pub fn save_state(&self, writer: &mut CheckpointWriter) {
+        writer.write_statistic_item(&self.travel_time);
+        writer.write_statistic_item(&self.travel_distance);
+        writer.write_statistic_item(&self.travel_speed);
+        for item in self.travel_time_by_floor.iter() {
+            writer.write_statistic_item(item);
+        }
+        for service_point in self.service_points.iter() {
+            writer.write_statistic_item(&service_point.waiting_time);
+            writer.write_statistic_item(&service_point.queue_length);
+        }
+        if let Some(ref hazard_exposure) = self.hazard_exposure {
+            writer.write_statistic_item(hazard_exposure);
+        }
+        for spawn_area in self.spawn_areas.iter() {
+            writer.write_statistic_item(&spawn_area.waiting_time);
+            writer.write_statistic_item(&spawn_area.backlog_size);
+        }
+        if let Some(ref stuck) = self.stuck {
+            writer.write_u32(stuck.flagged);
+            writer.write_u32(stuck.removed);
+        }
+    }
+
+    pub fn restore_state(&mut self, reader: &mut CheckpointReader) {
+        self.travel_time = reader.read_statistic_item();
+        self.travel_distance = reader.read_statistic_item();
+        self.travel_speed = reader.read_statistic_item();
+        for item in self.travel_time_by_floor.iter_mut() {
+            *item = reader.read_statistic_item();
+        }
+        for service_point in self.service_points.iter_mut() {
+            service_point.waiting_time = reader.read_statistic_item();
+            service_point.queue_length = reader.read_statistic_item();
+        }
+        if let Some(ref mut hazard_exposure) = self.hazard_exposure {
+            *hazard_exposure = reader.read_statistic_item();
+        }
+        for spawn_area in self.spawn_areas.iter_mut() {
+            spawn_area.waiting_time = reader.read_statistic_item();
+            spawn_area.backlog_size = reader.read_statistic_item();
+        }
+        if let Some(ref mut stuck) = self.stuck {
+            stuck.flagged = reader.read_u32();
+            stuck.removed = reader.read_u32();
+        }
     }
 
-    pub fn update_from_reached_destination_people(&mut self, people: Vec<Person>, current_time: f64) {
-        for _person in people.iter() {
-            self.travel_time.update_from_value(current_time);
+    fn update_hazard_exposure<'a, I: Iterator<Item = &'a Person>>(&mut self, people: I) {
+        if let Some(ref mut hazard_exposure) = self.hazard_exposure {
+            for person in people {
+                hazard_exposure.update_from_value(person.hazard_exposure);
+            }
         }
     }
 }
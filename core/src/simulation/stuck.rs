@@ -0,0 +1,82 @@
+extern crate anymap;
+
+use self::anymap::AnyMap;
+
+use ::configuration::StuckStrategy;
+
+use ::simulation::person::Person;
+
+// seconds
+const DEFAULT_WINDOW: f64 = 5_f64;
+// meters
+const DEFAULT_MIN_PROGRESS: f64 = 0.5_f64;
+const DEFAULT_PERTURBATION: f64 = 0.5_f64;
+
+// distance to target at the start of current window
+#[derive(Debug,Clone)]
+pub struct ProgressMark {
+    pub time: f64,
+    pub distance: f64,
+    pub target_index: u16,
+    pub floor: u8,
+}
+
+// flags people who got no closer to their targets during a window
+pub struct StuckDetector {
+    window: f64,
+    // pixels
+    min_progress: f64,
+    pub strategy: StuckStrategy,
+    // pixels
+    pub perturbation: f64,
+}
+
+impl StuckDetector {
+    pub fn new(configuration: &AnyMap, scale: f64) -> Option<StuckDetector> {
+        if !optional_config!(configuration, StuckDetectionEnabled).unwrap_or(false) {
+            return None;
+        }
+        Some(StuckDetector{ window: optional_config!(configuration, StuckWindow).unwrap_or(DEFAULT_WINDOW),
+                            min_progress: optional_config!(configuration, StuckMinProgress).unwrap_or(DEFAULT_MIN_PROGRESS) / scale,
+                            strategy: optional_config!(configuration, StuckResolution).unwrap_or(StuckStrategy::Report),
+                            perturbation: optional_config!(configuration, StuckPerturbation).unwrap_or(DEFAULT_PERTURBATION) / scale })
+    }
+
+    // returns progress over window if it's over and person is stuck
+    pub fn check(&self, person: &mut Person, current_time: f64) -> Option<f64> {
+        if person.is_waiting_for_service() || person.is_in_target_area() {
+            // queues are slow on purpose, people waiting for connector or for disabled target are already there
+            person.progress = None;
+            return None;
+        }
+
+        let distance = (person.current_target_point() - person.coordinates).length();
+        let new_mark = ProgressMark{ time: current_time, distance: distance,
+                                     target_index: person.current_target_index, floor: person.floor };
+        let mark = match person.progress.take() {
+            Some(mark) => mark,
+            None => {
+                person.progress = Some(new_mark);
+                return None;
+            }
+        };
+        if mark.target_index != person.current_target_index || mark.floor != person.floor {
+            // reaching target is progress enough
+            person.progress = Some(new_mark);
+            return None;
+        }
+        if current_time - mark.time < self.window {
+            person.progress = Some(mark);
+            return None;
+        }
+
+        person.progress = Some(new_mark);
+        let progress = mark.distance - distance;
+        if progress < self.min_progress {
+            person.stuck_time += current_time - mark.time;
+            Some(progress)
+        } else {
+            None
+        }
+    }
+}
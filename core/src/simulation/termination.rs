@@ -0,0 +1,140 @@
+extern crate anymap;
+extern crate time as system_time;
+
+use std::collections::VecDeque;
+
+use self::anymap::AnyMap;
+
+use ::simulation::checkpoint::CheckpointReader;
+use ::simulation::checkpoint::CheckpointWriter;
+
+// flow rate of that many consecutive windows must agree to call it steady
+const STEADY_STATE_WINDOWS: usize = 3;
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum TerminationReason {
+    EndTime,
+    AllExited,
+    MaxTime,
+    Arrivals,
+    ExitFraction,
+    WallClock,
+    SteadyState,
+    // finished from outside before any condition was met
+    Stopped,
+}
+
+impl TerminationReason {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            TerminationReason::EndTime => "end_time",
+            TerminationReason::AllExited => "all_exited",
+            TerminationReason::MaxTime => "max_time",
+            TerminationReason::Arrivals => "arrivals",
+            TerminationReason::ExitFraction => "exit_fraction",
+            TerminationReason::WallClock => "wall_clock",
+            TerminationReason::SteadyState => "steady_state",
+            TerminationReason::Stopped => "stopped",
+        }
+    }
+}
+
+// conditions stopping simulation in addition to its flow or escape rule, any of them is enough
+pub struct Termination {
+    max_time: Option<f64>,
+    max_arrivals: Option<u32>,
+    exit_fraction: Option<f64>,
+    // seconds of real time
+    wall_clock: Option<f64>,
+    // window in seconds and relative tolerance of flow rate
+    steady_state: Option<(f64, f64)>,
+    started_at: Option<u64>,
+    window_start: f64,
+    window_start_arrivals: u32,
+    // people per second in last windows
+    flow_rates: VecDeque<f64>,
+}
+
+impl Termination {
+    pub fn new(configuration: &AnyMap) -> Termination {
+        let steady_state = optional_config!(configuration, TerminationSteadyStateWindow).map(|window|
+            (window, optional_config!(configuration, TerminationSteadyStateTolerance).unwrap_or(0.1_f64))
+        );
+        Termination{ max_time: optional_config!(configuration, TerminationMaxTime),
+                     max_arrivals: optional_config!(configuration, TerminationMaxArrivals),
+                     exit_fraction: optional_config!(configuration, TerminationExitFraction),
+                     wall_clock: optional_config!(configuration, TerminationWallClock),
+                     steady_state: steady_state, started_at: None,
+                     window_start: 0_f64, window_start_arrivals: 0, flow_rates: VecDeque::new() }
+    }
+
+    pub fn start(&mut self) {
+        self.started_at = Some(system_time::precise_time_ns());
+    }
+
+    // arrivals are people who reached their last target
+    pub fn update(&mut self, current_time: f64, arrivals: u32) {
+        if let Some((window, _tolerance)) = self.steady_state {
+            let elapsed = current_time - self.window_start;
+            if elapsed >= window {
+                self.flow_rates.push_back((arrivals - self.window_start_arrivals) as f64 / elapsed);
+                if self.flow_rates.len() > STEADY_STATE_WINDOWS {
+                    self.flow_rates.pop_front();
+                }
+                self.window_start = current_time;
+                self.window_start_arrivals = arrivals;
+            }
+        }
+    }
+
+    pub fn check(&self, current_time: f64, arrivals: u32, spawned: u32) -> Option<TerminationReason> {
+        if self.max_time.map_or(false, |max_time| current_time >= max_time) {
+            return Some(TerminationReason::MaxTime);
+        }
+        if self.max_arrivals.map_or(false, |max_arrivals| arrivals >= max_arrivals) {
+            return Some(TerminationReason::Arrivals);
+        }
+        if spawned > 0 && self.exit_fraction.map_or(false, |fraction| arrivals as f64 >= fraction * spawned as f64) {
+            return Some(TerminationReason::ExitFraction);
+        }
+        if self.is_steady() {
+            return Some(TerminationReason::SteadyState);
+        }
+        let elapsed = self.started_at.map(|started_at| (system_time::precise_time_ns() - started_at) as f64 / 1e9_f64);
+        if let (Some(wall_clock), Some(elapsed)) = (self.wall_clock, elapsed) {
+            if elapsed >= wall_clock {
+                return Some(TerminationReason::WallClock);
+            }
+        }
+        None
+    }
+
+    fn is_steady(&self) -> bool {
+        let tolerance = match self.steady_state {
+            Some((_window, tolerance)) => tolerance,
+            None => return false
+        };
+        if self.flow_rates.len() < STEADY_STATE_WINDOWS {
+            return false;
+        }
+        let avg = self.flow_rates.iter().fold(0_f64, |sum, rate| sum + rate) / self.flow_rates.len() as f64;
+        avg > 0_f64 && self.flow_rates.iter().all(|rate| (rate - avg).abs() <= tolerance * avg)
+    }
+
+    // wall clock budget starts anew for restored simulation
+    pub fn save_state(&self, writer: &mut CheckpointWriter) {
+        writer.write_f64(self.window_start);
+        writer.write_u32(self.window_start_arrivals);
+        writer.write_usize(self.flow_rates.len());
+        for rate in self.flow_rates.iter() {
+            writer.write_f64(*rate);
+        }
+    }
+
+    pub fn restore_state(&mut self, reader: &mut CheckpointReader) {
+        self.window_start = reader.read_f64();
+        self.window_start_arrivals = reader.read_u32();
+        let count = reader.read_usize();
+        self.flow_rates = (0..count).map(|_i| reader.read_f64()).collect();
+    }
+}
@@ -2,6 +2,9 @@ extern crate anymap;
 
 use self::anymap::AnyMap;
 
+use ::simulation::checkpoint::CheckpointReader;
+use ::simulation::checkpoint::CheckpointWriter;
+
 pub struct Time {
     pub current_time: f64,
     pub end_time: f64,
@@ -23,4 +26,12 @@ impl Time {
     pub fn next_tick(&mut self) {
         self.current_time += self.tick;
     }
+
+    pub fn save_state(&self, writer: &mut CheckpointWriter) {
+        writer.write_f64(self.current_time);
+    }
+
+    pub fn restore_state(&mut self, reader: &mut CheckpointReader) {
+        self.current_time = reader.read_f64();
+    }
 }
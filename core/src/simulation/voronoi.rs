@@ -0,0 +1,214 @@
+extern crate anymap;
+
+use std::f64::consts::PI;
+
+use self::anymap::AnyMap;
+
+use ::simulation::scene::Scene;
+
+use ::utils::linelg::Line;
+use ::utils::linelg::Point;
+use ::utils::linelg::Polygon;
+use ::utils::linelg::Rectangle;
+use ::utils::linelg::Vector;
+use ::utils::linelg::distance::DistanceTo;
+
+// cut off cells are polygons with that many sides
+const CUTOFF_SIDES: usize = 16;
+// square pixels, smaller leftovers of clipping are dropped
+const MIN_PIECE_AREA: f64 = 1e-9_f64;
+
+// part of floor closer to the person than to anyone else, without what walls hide from him
+pub struct VoronoiCell {
+    // index in scene people
+    pub person_index: usize,
+    // convex parts of the cell
+    pub pieces: Vec<Polygon>,
+}
+
+impl VoronoiCell {
+    // square pixels
+    pub fn area(&self) -> f64 {
+        self.pieces.iter().fold(0_f64, |sum, piece| sum + piece.area())
+    }
+
+    // square pixels of the cell inside rectangle given by opposite corners
+    pub fn area_inside(&self, p0: &Point, p1: &Point) -> f64 {
+        self.pieces.iter().fold(0_f64, |sum, piece| sum + clip_by_rectangle(piece, p0, p1).area())
+    }
+}
+
+// personal density is the inverse of person's cell area, as in Steffen and Seyfried
+pub struct Voronoi {
+    // meters, cells are limited to this distance from their person
+    cutoff: Option<f64>,
+}
+
+impl Voronoi {
+    pub fn new(configuration: &AnyMap) -> Voronoi {
+        Voronoi{ cutoff: optional_config!(configuration, DensityVoronoiCutoff) }
+    }
+
+    pub fn cells(&self, scene: &Scene, floor: u8) -> Vec<VoronoiCell> {
+        let people: Vec<(usize, Point)> = scene.people.iter().enumerate()
+            .filter(|&(_i, person)| person.floor == floor)
+            .map(|(i, person)| (i, person.coordinates)).collect();
+        cells(&people, &scene.floors[floor as usize].geometry, &Point::new(scene.width as f64, scene.height as f64),
+              self.cutoff.map(|cutoff| cutoff / scene.scale))
+    }
+
+    // people per square meter, indexed as scene people
+    pub fn personal_densities(&self, scene: &Scene) -> Vec<f64> {
+        let mut densities = vec![0_f64; scene.people.len()];
+        for floor in 0..scene.floors.len() {
+            for cell in self.cells(scene, floor as u8).iter() {
+                densities[cell.person_index] = cell_density(cell, scene.scale);
+            }
+        }
+        densities
+    }
+
+    // every pixel gets density of the cell it belongs to, same layout as Scene::get_density_map
+    pub fn density_map(&self, scene: &Scene, floor: u8) -> Vec<Vec<f64>> {
+        let mut res = vec![vec![0_f64; scene.width as usize]; scene.height as usize];
+        for cell in self.cells(scene, floor).iter() {
+            let density = cell_density(cell, scene.scale);
+            for piece in cell.pieces.iter() {
+                let min_x = piece.points.iter().fold(scene.width as f64, |min, p| min.min(p.x)).max(0_f64) as usize;
+                let max_x = piece.points.iter().fold(0_f64, |max, p| max.max(p.x)).ceil().min(scene.width as f64) as usize;
+                let min_y = piece.points.iter().fold(scene.height as f64, |min, p| min.min(p.y)).max(0_f64) as usize;
+                let max_y = piece.points.iter().fold(0_f64, |max, p| max.max(p.y)).ceil().min(scene.height as f64) as usize;
+                for i in min_y..max_y {
+                    for j in min_x..max_x {
+                        if piece.contains(&Point::new(j as f64, i as f64)) {
+                            res[i][j] = density;
+                        }
+                    }
+                }
+            }
+        }
+        res
+    }
+}
+
+// cells of people given with their indexes within scene from origin to bounds, cutoff is in pixels
+fn cells(people: &Vec<(usize, Point)>, walls: &Vec<Line>, bounds: &Point, cutoff: Option<f64>) -> Vec<VoronoiCell> {
+    people.iter().map(|&(person_index, coordinates)| {
+        let mut cell = match cutoff {
+            Some(radius) => {
+                let points = (0..CUTOFF_SIDES).map(|side| {
+                    let angle = 2_f64 * PI * side as f64 / CUTOFF_SIDES as f64;
+                    coordinates + Vector::new(angle.cos(), angle.sin()) * radius
+                }).collect();
+                clip_by_rectangle(&Polygon::new(points), &Point::zero(), bounds)
+            },
+            None => Polygon::new_from_rectangle(&Rectangle::new_from_raw(0_f64, 0_f64, bounds.x, bounds.y))
+        };
+
+        let mut neighbours: Vec<(f64, Point)> = people.iter().filter(|&&(i, _p)| i != person_index)
+            .map(|&(_i, p)| (p.distance(&coordinates), p)).collect();
+        neighbours.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        for &(distance, neighbour) in neighbours.iter() {
+            // farther neighbours can't cut anything from the cell
+            if distance > 2_f64 * max_radius(&cell, &coordinates) {
+                break;
+            }
+            if distance > 0_f64 {
+                let middle = Point::new((coordinates.x + neighbour.x) / 2_f64, (coordinates.y + neighbour.y) / 2_f64);
+                cell = cell.clip(&middle, &(coordinates - neighbour));
+            }
+        }
+
+        let radius = max_radius(&cell, &coordinates);
+        let mut pieces = vec![cell];
+        for wall in walls.iter().filter(|wall| coordinates.distance(*wall) < radius) {
+            pieces = subtract_shadow(pieces, &coordinates, wall);
+        }
+        VoronoiCell{ person_index: person_index, pieces: pieces }
+    }).collect()
+}
+
+fn cell_density(cell: &VoronoiCell, scale: f64) -> f64 {
+    let area = cell.area() * scale * scale;
+    if area > 0_f64 { 1_f64 / area } else { 0_f64 }
+}
+
+fn max_radius(polygon: &Polygon, center: &Point) -> f64 {
+    polygon.points.iter().fold(0_f64, |max, p| max.max(p.distance(center)))
+}
+
+fn clip_by_rectangle(polygon: &Polygon, p0: &Point, p1: &Point) -> Polygon {
+    polygon.clip(p0, &Vector::new(1_f64, 0_f64)).clip(p0, &Vector::new(0_f64, 1_f64))
+        .clip(p1, &Vector::new(-1_f64, 0_f64)).clip(p1, &Vector::new(0_f64, -1_f64))
+}
+
+// removes part of the pieces hidden behind the wall as seen from the point
+fn subtract_shadow(pieces: Vec<Polygon>, point: &Point, wall: &Line) -> Vec<Polygon> {
+    let along = wall.to - wall.from;
+    let to_point = *point - wall.from;
+    if (along.x * to_point.y - along.y * to_point.x).abs() < ::std::f64::EPSILON {
+        // standing in line with the wall, it hides nothing
+        return pieces;
+    }
+
+    // shadow is beyond the wall and between rays from the point through its ends
+    let mut beyond = Vector::new(-along.y, along.x);
+    if beyond.x * to_point.x + beyond.y * to_point.y > 0_f64 {
+        beyond = -beyond;
+    }
+    let towards_end = |from: Point, to: Point| {
+        let ray = from - *point;
+        let normal = Vector::new(-ray.y, ray.x);
+        let other = to - *point;
+        if normal.x * other.x + normal.y * other.y < 0_f64 { -normal } else { normal }
+    };
+    let shadow = [(wall.from, beyond), (*point, towards_end(wall.from, wall.to)), (*point, towards_end(wall.to, wall.from))];
+
+    let mut visible = Vec::new();
+    for piece in pieces.into_iter() {
+        let mut rest = piece;
+        for &(line_point, normal) in shadow.iter() {
+            let outside = rest.clip(&line_point, &-normal);
+            if outside.area() > MIN_PIECE_AREA {
+                visible.push(outside);
+            }
+            rest = rest.clip(&line_point, &normal);
+            if rest.points.len() < 3 {
+                break;
+            }
+        }
+    }
+    visible
+}
+
+#[test]
+fn test_cells_split_area() {
+    let people = vec![(0, Point::new(1_f64, 1_f64)), (1, Point::new(3_f64, 1_f64))];
+    let cells = cells(&people, &Vec::new(), &Point::new(4_f64, 2_f64), None);
+    assert_eq!(cells.len(), 2);
+    assert!((cells[0].area() - 4_f64).abs() < 1e-9_f64);
+    assert!((cells[1].area() - 4_f64).abs() < 1e-9_f64);
+    assert!((cells[0].area_inside(&Point::new(0_f64, 0_f64), &Point::new(2_f64, 1_f64)) - 2_f64).abs() < 1e-9_f64);
+    assert!(cells[1].area_inside(&Point::new(0_f64, 0_f64), &Point::new(1_f64, 1_f64)).abs() < 1e-9_f64);
+}
+
+#[test]
+fn test_cells_shadowed_by_wall() {
+    let people = vec![(0, Point::new(1_f64, 1_f64))];
+    // whole cell beyond wall across the scene is hidden
+    let wall = Line::new_from_raw(2_f64, -1_f64, 2_f64, 3_f64);
+    let cells_behind_wall = cells(&people, &vec![wall], &Point::new(4_f64, 2_f64), None);
+    assert!((cells_behind_wall[0].area() - 4_f64).abs() < 1e-9_f64);
+    // half wall hides only what is behind it as seen from the person
+    let half_wall = Line::new_from_raw(2_f64, 0_f64, 2_f64, 1_f64);
+    let cells_behind_half_wall = cells(&people, &vec![half_wall], &Point::new(4_f64, 2_f64), None);
+    assert!((cells_behind_half_wall[0].area() - 6_f64).abs() < 1e-9_f64);
+}
+
+#[test]
+fn test_cells_cutoff() {
+    let people = vec![(0, Point::new(50_f64, 50_f64))];
+    let cells = cells(&people, &Vec::new(), &Point::new(100_f64, 100_f64), Some(2_f64));
+    let polygon_area = CUTOFF_SIDES as f64 / 2_f64 * 4_f64 * (2_f64 * PI / CUTOFF_SIDES as f64).sin();
+    assert!((cells[0].area() - polygon_area).abs() < 1e-9_f64);
+}
@@ -1,9 +1,65 @@
 extern crate rand;
 
-use self::rand::Rng;
+use std::cell::RefCell;
 
 use configuration::DistributionValue;
 
+thread_local! {
+    // every random value of simulation comes from here, so run can be repeated with the same seed
+    static RNG: RefCell<XorShift> = RefCell::new(XorShift::from_seed(rand::random::<u64>()));
+}
+
+// xorshift128+ generator, its state is small enough to be saved in checkpoints
+struct XorShift {
+    state: [u64; 2],
+}
+
+impl XorShift {
+    fn from_seed(seed: u64) -> XorShift {
+        // splitmix64 spreads seed bits over the whole state
+        let mut x = seed;
+        let mut state = [0_u64; 2];
+        for value in state.iter_mut() {
+            x = x.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            *value = z ^ (z >> 31);
+        }
+        XorShift{ state: state }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut s1 = self.state[0];
+        let s0 = self.state[1];
+        self.state[0] = s0;
+        s1 ^= s1 << 23;
+        self.state[1] = s1 ^ s0 ^ (s1 >> 17) ^ (s0 >> 26);
+        self.state[1].wrapping_add(s0)
+    }
+
+    // uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = XorShift::from_seed(seed));
+}
+
+pub fn rng_state() -> [u64; 2] {
+    RNG.with(|rng| rng.borrow().state)
+}
+
+pub fn set_rng_state(state: [u64; 2]) {
+    RNG.with(|rng| rng.borrow_mut().state = state);
+}
+
+fn random() -> f64 {
+    RNG.with(|rng| rng.borrow_mut().next_f64())
+}
+
 pub fn generate(distribution_info: &DistributionValue) -> f64 {
     match *distribution_info {
         DistributionValue::UniformDistributionValue{from, to} => generate_uniform(from, to),
@@ -13,17 +69,16 @@ pub fn generate(distribution_info: &DistributionValue) -> f64 {
 }
 
 pub fn generate_uniform(from: f64, to: f64) -> f64 {
-    let rand = rand::random::<f64>();
+    let rand = random();
     from + rand * (to - from)
 }
 
 pub fn generate_normal(mean: f64, std_deviation: f64) -> f64 {
     let group_n = 6;
     let mut sum = 0.0f64;
-    let mut rng = rand::thread_rng();
 
     for _i in 0..group_n {
-        sum += rng.gen::<f64>();
+        sum += random();
     }
 
     mean + std_deviation * f64::sqrt(12_f64 / group_n as f64) * (sum - group_n as f64 / 2.0_f64)
@@ -27,6 +27,31 @@ impl Line {
     pub fn length(&self) -> f64 {
         self.to.distance(&self.from)
     }
+
+    // segments touching each other are considered intersecting
+    pub fn intersects(&self, other: &Line) -> bool {
+        let d1 = orientation(&other.from, &other.to, &self.from);
+        let d2 = orientation(&other.from, &other.to, &self.to);
+        let d3 = orientation(&self.from, &self.to, &other.from);
+        let d4 = orientation(&self.from, &self.to, &other.to);
+
+        if ((d1 > 0f64 && d2 < 0f64) || (d1 < 0f64 && d2 > 0f64)) &&
+           ((d3 > 0f64 && d4 < 0f64) || (d3 < 0f64 && d4 > 0f64)) {
+            return true;
+        }
+        (d1 == 0f64 && on_segment(other, &self.from)) || (d2 == 0f64 && on_segment(other, &self.to)) ||
+            (d3 == 0f64 && on_segment(self, &other.from)) || (d4 == 0f64 && on_segment(self, &other.to))
+    }
+}
+
+fn orientation(a: &Point, b: &Point, c: &Point) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+// p is known to be collinear with line
+fn on_segment(line: &Line, p: &Point) -> bool {
+    p.x >= line.from.x.min(line.to.x) && p.x <= line.from.x.max(line.to.x) &&
+        p.y >= line.from.y.min(line.to.y) && p.y <= line.from.y.max(line.to.y)
 }
 
 impl Neg for Line {
@@ -42,3 +67,14 @@ fn test_len_sqr() {
     let l = Line::new_from_raw(0f64, 0f64, 4f64, 0f64);
     assert!(l.length_sqr() == 16f64);
 }
+
+#[test]
+fn test_intersects() {
+    let l = Line::new_from_raw(0f64, 0f64, 4f64, 4f64);
+    assert!(l.intersects(&Line::new_from_raw(0f64, 4f64, 4f64, 0f64)));
+    assert!(l.intersects(&Line::new_from_raw(2f64, 2f64, 4f64, 0f64)));
+    assert!(!l.intersects(&Line::new_from_raw(3f64, 0f64, 4f64, 0f64)));
+    assert!(!l.intersects(&Line::new_from_raw(1f64, 0f64, 5f64, 4f64)));
+    assert!(l.intersects(&Line::new_from_raw(3f64, 3f64, 6f64, 6f64)));
+    assert!(!l.intersects(&Line::new_from_raw(5f64, 5f64, 6f64, 6f64)));
+}
@@ -6,11 +6,13 @@ pub use self::vector::Vector;
 pub use self::point::Point;
 pub use self::line::Line;
 pub use self::rectangle::Rectangle;
+pub use self::polygon::Polygon;
 
 pub mod vector;
 pub mod point;
 pub mod line;
 pub mod rectangle;
+pub mod polygon;
 pub mod distance;
 
 static EPS : f64 = 0.00001f64;
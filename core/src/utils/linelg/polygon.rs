@@ -0,0 +1,88 @@
+use std::f64;
+use utils::linelg::Point;
+use utils::linelg::Rectangle;
+use utils::linelg::Vector;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    pub points: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<Point>) -> Polygon {
+        Polygon { points: points }
+    }
+
+    pub fn new_from_rectangle(rectangle: &Rectangle) -> Polygon {
+        Polygon::new(vec![rectangle.p1, rectangle.p2, rectangle.p3, rectangle.p4])
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        // even-odd rule
+        let mut inside = false;
+        let count = self.points.len();
+        for i in 0..count {
+            let a = self.points[i];
+            let b = self.points[(i + count - 1) % count];
+            if (a.y > p.y) != (b.y > p.y) &&
+               p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    pub fn area(&self) -> f64 {
+        let count = self.points.len();
+        let mut double_area = 0f64;
+        for i in 0..count {
+            let a = self.points[i];
+            let b = self.points[(i + 1) % count];
+            double_area += a.x * b.y - b.x * a.y;
+        }
+        (double_area / 2f64).abs()
+    }
+
+    // part of convex polygon on the side of line through point where normal points to
+    pub fn clip(&self, point: &Point, normal: &Vector) -> Polygon {
+        let side = |p: &Point| (p.x - point.x) * normal.x + (p.y - point.y) * normal.y;
+        let mut points = Vec::new();
+        let count = self.points.len();
+        for i in 0..count {
+            let a = self.points[i];
+            let b = self.points[(i + 1) % count];
+            let (side_a, side_b) = (side(&a), side(&b));
+            if side_a >= 0f64 {
+                points.push(a);
+            }
+            if (side_a >= 0f64) != (side_b >= 0f64) {
+                points.push(a + (b - a) * (side_a / (side_a - side_b)));
+            }
+        }
+        Polygon::new(points)
+    }
+}
+
+#[test]
+fn test_contains() {
+    let p = Polygon::new(vec![Point::new(0f64, 0f64), Point::new(4f64, 0f64), Point::new(0f64, 4f64)]);
+    assert!(p.contains(&Point::new(1f64, 1f64)));
+    assert!(!p.contains(&Point::new(3f64, 3f64)));
+    assert!(!p.contains(&Point::new(-1f64, 1f64)));
+}
+
+#[test]
+fn test_area() {
+    let r = Rectangle::new_from_raw(1f64, 1f64, 4f64, 3f64);
+    let p = Polygon::new_from_rectangle(&r);
+    assert!((p.area() - 6f64).abs() < ::utils::linelg::EPS);
+}
+
+#[test]
+fn test_clip() {
+    let r = Rectangle::new_from_raw(0f64, 0f64, 4f64, 2f64);
+    let p = Polygon::new_from_rectangle(&r).clip(&Point::new(1f64, 0f64), &Vector::new(1f64, 0f64));
+    assert!((p.area() - 6f64).abs() < ::utils::linelg::EPS);
+    assert!(!p.contains(&Point::new(0.5f64, 1f64)));
+    assert!(p.contains(&Point::new(1.5f64, 1f64)));
+}
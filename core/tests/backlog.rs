@@ -0,0 +1,61 @@
+extern crate crowd_sim;
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crowd_sim::Simulation;
+use crowd_sim::configuration;
+use crowd_sim::simulation::observer::SimulationObserver;
+
+// arrival time of everyone who entered the scene, recovered from waiting time statistics
+struct EntryObserver {
+    arrivals: Rc<RefCell<Vec<f64>>>,
+    count: u32,
+    sum: f64,
+}
+
+impl SimulationObserver for EntryObserver {
+    fn on_tick(&mut self, simulation: &Simulation) {
+        let ref waiting_time = simulation.statistics.spawn_areas[0].waiting_time;
+        if waiting_time.count > self.count {
+            // spawn area fits one person, so nobody else entered during the same tick
+            assert_eq!(waiting_time.count, self.count + 1);
+            self.arrivals.borrow_mut().push(simulation.time.current_time - (waiting_time.sum - self.sum));
+            self.count = waiting_time.count;
+            self.sum = waiting_time.sum;
+        }
+    }
+}
+
+#[test]
+fn test_backlog_drains_in_arrival_order() {
+    let mut config = common::room_config();
+    common::wall(&mut config, [5, 0, 5, 100], 0);
+    common::wall(&mut config, [95, 0, 95, 100], 0);
+    common::spawn_area(&mut config, [48, 88, 52, 92], 0, 0);
+    common::target_area(&mut config, [10, 0, 90, 10], 0, 0, true, 0);
+    common::item(&mut config, 0x02, 0x01); config.extend_from_slice(&[0, 0, 0, 30]);
+    common::item(&mut config, 0x03, 0x01); common::f64_le(&mut config, 4.0);
+    // arrivals stop after 4 seconds
+    common::item(&mut config, 0x08, 0x05); common::f64_le(&mut config, 4.0); config.push(0xFF); common::f64_le(&mut config, 0.0);
+
+    let arrivals = Rc::new(RefCell::new(Vec::new()));
+    let mut simulation = Simulation::new(configuration::new(&mut &config[..]));
+    simulation.add_observer(Box::new(EntryObserver{ arrivals: arrivals.clone(), count: 0, sum: 0.0 }));
+    while !simulation.is_finished() {
+        simulation.step();
+    }
+    simulation.finish();
+
+    let ref statistics = simulation.statistics.spawn_areas[0];
+    assert!(statistics.backlog_size.max > 2.0);
+    assert!(statistics.waiting_time.max > 0.5);
+    assert_eq!(simulation.scene.spawn_backlog_lengths(), vec![0]);
+    let arrivals = arrivals.borrow();
+    assert!(arrivals.len() > 10);
+    for pair in arrivals.windows(2) {
+        assert!(pair[0] < pair[1]);
+    }
+}
@@ -0,0 +1,48 @@
+extern crate crowd_sim;
+
+mod common;
+
+use crowd_sim::Simulation;
+
+fn run_to_end(simulation: &mut Simulation) {
+    while !simulation.is_finished() {
+        simulation.step();
+    }
+    simulation.finish();
+}
+
+fn people_state(simulation: &Simulation) -> Vec<(u32, f64, f64, f64)> {
+    simulation.scene.people.iter().map(|person| (person.id, person.coordinates.x, person.coordinates.y, person.heading)).collect()
+}
+
+#[test]
+fn test_same_seed_gives_same_run() {
+    let mut first = common::new_seeded_simulation(42);
+    run_to_end(&mut first);
+    let mut second = common::new_seeded_simulation(42);
+    run_to_end(&mut second);
+
+    assert_eq!(people_state(&first), people_state(&second));
+    assert_eq!(first.statistics.travel_time.sum, second.statistics.travel_time.sum);
+}
+
+#[test]
+fn test_restored_simulation_continues_identically() {
+    let mut original = common::new_seeded_simulation(7);
+    while original.time.current_time < 4_f64 {
+        original.step();
+    }
+    let mut checkpoint = Vec::new();
+    original.save_checkpoint(&mut checkpoint);
+    run_to_end(&mut original);
+
+    let mut restored = common::new_seeded_simulation(1);
+    restored.restore_checkpoint(&mut &checkpoint[..]);
+    assert!(restored.time.current_time >= 4_f64);
+    run_to_end(&mut restored);
+
+    assert_eq!(people_state(&original), people_state(&restored));
+    assert_eq!(original.statistics.travel_time.count, restored.statistics.travel_time.count);
+    assert_eq!(original.statistics.travel_time.sum, restored.statistics.travel_time.sum);
+    assert_eq!(original.time.current_time, restored.time.current_time);
+}
@@ -0,0 +1,114 @@
+#![allow(dead_code)]
+
+use crowd_sim::Simulation;
+use crowd_sim::configuration;
+
+pub fn item(config: &mut Vec<u8>, section: u8, element: u16) {
+    config.push(section);
+    config.push((element >> 8) as u8);
+    config.push(element as u8);
+}
+
+pub fn u16_be(config: &mut Vec<u8>, value: u16) {
+    config.push((value >> 8) as u8);
+    config.push(value as u8);
+}
+
+pub fn f64_le(config: &mut Vec<u8>, value: f64) {
+    let bits = value.to_bits();
+    for i in 0..8 {
+        config.push((bits >> (8 * i)) as u8);
+    }
+}
+
+pub fn normal(config: &mut Vec<u8>, mean: f64, std_deviation: f64) {
+    config.push(2);
+    f64_le(config, mean);
+    f64_le(config, std_deviation);
+}
+
+pub fn string(config: &mut Vec<u8>, value: &str) {
+    u16_be(config, value.len() as u16);
+    config.extend_from_slice(value.as_bytes());
+}
+
+pub fn wall(config: &mut Vec<u8>, coordinates: [u16; 4], floor: u8) {
+    item(config, 0x01, 0x01);
+    for &coordinate in coordinates.iter() { u16_be(config, coordinate); }
+    config.push(floor);
+}
+
+pub fn spawn_area(config: &mut Vec<u8>, coordinates: [u16; 4], id: u8, floor: u8) {
+    item(config, 0x01, 0x02);
+    for &coordinate in coordinates.iter() { u16_be(config, coordinate); }
+    config.push(id); config.push(floor);
+}
+
+pub fn target_area(config: &mut Vec<u8>, coordinates: [u16; 4], id: u8, sequence_no: u8, last: bool, floor: u8) {
+    item(config, 0x01, 0x03);
+    for &coordinate in coordinates.iter() { u16_be(config, coordinate); }
+    config.push(id); config.push(sequence_no << 1 | last as u8); config.push(floor);
+}
+
+// 5x5 meters room without walls, spawn and target areas, people come once a second during 10 seconds
+pub fn room_config() -> Vec<u8> {
+    let mut config = Vec::new();
+    item(&mut config, 0x00, 0x01); config.push(1);
+
+    item(&mut config, 0x01, 0xFF); string(&mut config, "scene.svg");
+    item(&mut config, 0x01, 0x11); u16_be(&mut config, 100);
+    item(&mut config, 0x01, 0x12); u16_be(&mut config, 100);
+    item(&mut config, 0x01, 0x13); f64_le(&mut config, 0.05);
+
+    item(&mut config, 0x02, 0x01); config.extend_from_slice(&[0, 0, 0, 10]);
+    item(&mut config, 0x02, 0x02); f64_le(&mut config, 0.1);
+
+    item(&mut config, 0x03, 0x01); f64_le(&mut config, 1.0);
+    item(&mut config, 0x03, 0x02); config.push(1); f64_le(&mut config, 0.0); f64_le(&mut config, 10.0);
+    item(&mut config, 0x03, 0x03); config.push(0); config.push(0); f64_le(&mut config, 1.0);
+
+    item(&mut config, 0x04, 0x0201); normal(&mut config, 1.5, 0.0);
+    item(&mut config, 0x04, 0x0101); normal(&mut config, 1.0, 0.0);
+
+    item(&mut config, 0x05, 0x01); normal(&mut config, 5.0, 0.0);
+    item(&mut config, 0x05, 0x02); normal(&mut config, 5.0, 0.0);
+
+    item(&mut config, 0x06, 0x01); config.push(0);
+    item(&mut config, 0x06, 0x02); f64_le(&mut config, 6.0);
+    item(&mut config, 0x06, 0x03); f64_le(&mut config, 15.0);
+
+    item(&mut config, 0x07, 0x01); config.push(0); string(&mut config, "adult");
+    item(&mut config, 0x07, 0x02); config.push(0); normal(&mut config, 0.4, 0.0);
+    config
+}
+
+// people spawn at the bottom of the room and leave at the top
+pub fn flow_config() -> Vec<u8> {
+    let mut config = room_config();
+    wall(&mut config, [5, 0, 5, 100], 0);
+    wall(&mut config, [95, 0, 95, 100], 0);
+    spawn_area(&mut config, [10, 80, 90, 95], 0, 0);
+    target_area(&mut config, [10, 0, 90, 10], 0, 0, true, 0);
+    config
+}
+
+pub fn run(config: Vec<u8>) -> Simulation {
+    let mut simulation = Simulation::new(configuration::new(&mut &config[..]));
+    while !simulation.is_finished() {
+        simulation.step();
+    }
+    simulation.finish();
+    simulation
+}
+
+pub fn new_simulation() -> Simulation {
+    let config = flow_config();
+    Simulation::new(configuration::new(&mut &config[..]))
+}
+
+pub fn new_seeded_simulation(seed: u32) -> Simulation {
+    let mut config = flow_config();
+    item(&mut config, 0x00, 0x04);
+    config.extend_from_slice(&[(seed >> 24) as u8, (seed >> 16) as u8, (seed >> 8) as u8, seed as u8]);
+    Simulation::new(configuration::new(&mut &config[..]))
+}
@@ -0,0 +1,152 @@
+extern crate crowd_sim;
+
+mod common;
+
+use std::env;
+use std::fs::File;
+use std::io::prelude::*;
+
+use crowd_sim::Simulation;
+use crowd_sim::configuration;
+use crowd_sim::output::trips::TripLog;
+use crowd_sim::output::trajectory::TrajectoryLog;
+use crowd_sim::output::measurements::Measurements;
+
+fn temp_filename(name: &str) -> String {
+    env::temp_dir().join(name).to_str().unwrap().to_string()
+}
+
+fn read_lines(filename: &str) -> Vec<String> {
+    let mut content = String::new();
+    File::open(filename).unwrap().read_to_string(&mut content).unwrap();
+    content.lines().map(|line| line.to_string()).collect()
+}
+
+fn run_to_end(simulation: &mut Simulation) {
+    while !simulation.is_finished() {
+        simulation.step();
+    }
+    simulation.finish();
+}
+
+#[test]
+fn test_trip_log_columns() {
+    let config = common::flow_config();
+    let filename = temp_filename("crowd_sim_test_trips.csv");
+    let mut simulation = Simulation::new(configuration::new(&mut &config[..]));
+    simulation.add_observer(Box::new(TripLog::new(&filename)));
+    run_to_end(&mut simulation);
+
+    let lines = read_lines(&filename);
+    assert_eq!(lines[0], "id,path_id,profile_id,status,spawn_time,target_times,exit_time,distance,stuck_time,\
+                          target_speed,repulsion_coeff,forward_fov,backward_fov,noise_level");
+    let rows: Vec<Vec<&str>> = lines[1..].iter().map(|line| line.split(',').collect()).collect();
+    assert!(rows.iter().all(|row| row.len() == 14));
+
+    let exited: Vec<&Vec<&str>> = rows.iter().filter(|row| row[3] == "exited").collect();
+    assert!(exited.len() > 0);
+    assert_eq!(exited.len() as u32, simulation.statistics.travel_time.count);
+    assert_eq!(rows.iter().filter(|row| row[3] == "on_scene").count(), simulation.scene.people.len());
+    for row in exited {
+        // reaching the last target is the exit
+        let exit_time: f64 = row[6].parse().unwrap();
+        let last_target_time: f64 = row[5].split(';').last().unwrap().parse().unwrap();
+        assert_eq!(last_target_time, exit_time);
+        assert!(row[4].parse::<f64>().unwrap() < exit_time);
+        assert!(row[7].parse::<f64>().unwrap() > 0.0);
+    }
+}
+
+#[test]
+fn test_trajectory_columns() {
+    let mut config = common::flow_config();
+    let filename = temp_filename("crowd_sim_test_trajectory.txt");
+    common::item(&mut config, 0x11, 0x01); common::string(&mut config, &filename);
+    common::item(&mut config, 0x11, 0x02); common::f64_le(&mut config, 0.5);
+    common::item(&mut config, 0x11, 0x03); common::f64_le(&mut config, 3.0);
+    common::item(&mut config, 0x11, 0x04); config.push(1);
+    let mut simulation = Simulation::new(configuration::new(&mut &config[..]));
+    let trajectory = TrajectoryLog::new(&configuration::new(&mut &config[..]), 0.1).unwrap();
+    simulation.add_observer(Box::new(trajectory));
+    run_to_end(&mut simulation);
+
+    let lines = read_lines(&filename);
+    assert_eq!(lines[0], "# framerate: 2");
+    assert_eq!(lines[1], "# id frame x/m y/m z/m heading/deg");
+    let rows: Vec<Vec<&str>> = lines[2..].iter().map(|line| line.split(' ').collect()).collect();
+    assert!(rows.len() > 0);
+    let mut last_frame = 0;
+    for row in rows {
+        assert_eq!(row.len(), 6);
+        let frame: u64 = row[1].parse().unwrap();
+        assert!(frame >= last_frame && frame <= 20);
+        last_frame = frame;
+        // 5x5 meters room on the ground floor
+        for coordinate in row[2..4].iter() {
+            let coordinate: f64 = coordinate.parse().unwrap();
+            assert!(coordinate >= 0.0 && coordinate <= 5.0);
+        }
+        assert_eq!(row[4], "0.0000");
+        let heading: f64 = row[5].parse().unwrap();
+        assert!(heading >= 0.0 && heading < 360.0);
+    }
+}
+
+#[test]
+fn test_measurements_columns() {
+    let mut config = common::flow_config();
+    let filename = temp_filename("crowd_sim_test_measurements.csv");
+    let diagram_filename = temp_filename("crowd_sim_test_diagram.csv");
+    // line across the room halfway to the exit, inside the area around it
+    common::item(&mut config, 0x01, 0x05);
+    for &coordinate in [5, 50, 95, 50].iter() { common::u16_be(&mut config, coordinate); }
+    config.push(0); config.push(0);
+    common::item(&mut config, 0x01, 0x06);
+    for &coordinate in [5, 30, 95, 70].iter() { common::u16_be(&mut config, coordinate); }
+    config.push(0); config.push(0);
+    common::item(&mut config, 0x12, 0x01); common::f64_le(&mut config, 1.0);
+    common::item(&mut config, 0x12, 0x02); common::string(&mut config, &filename);
+    common::item(&mut config, 0x12, 0x03); common::string(&mut config, &diagram_filename);
+    let mut simulation = Simulation::new(configuration::new(&mut &config[..]));
+    let measurements = Measurements::new(&configuration::new(&mut &config[..])).unwrap();
+    simulation.add_observer(Box::new(measurements));
+    run_to_end(&mut simulation);
+
+    let lines = read_lines(&filename);
+    assert!(lines[0].starts_with("time_from,time_to,kind,id,forward,backward,flow,density,speed"));
+    let columns = lines[0].split(',').count();
+    let rows: Vec<Vec<&str>> = lines[1..].iter().map(|line| line.split(',').collect()).collect();
+    // one line and one area row per bin, bins follow each other up to the end of simulation
+    assert_eq!(rows[0][0], "0");
+    for pair in rows.chunks(2) {
+        assert_eq!((pair[0][0], pair[0][1]), (pair[1][0], pair[1][1]));
+    }
+    for pairs in rows.windows(3).step_by(2) {
+        assert_eq!(pairs[0][1], pairs[2][0]);
+    }
+    assert!(rows[rows.len() - 1][1].parse::<f64>().unwrap() >= 10.0);
+    let mut crossings = 0;
+    for row in rows.iter() {
+        assert_eq!(row.len(), columns);
+        assert_eq!(row[3], "0");
+        match row[2] {
+            "line" => {
+                crossings += row[4].parse::<u32>().unwrap() + row[5].parse::<u32>().unwrap();
+                assert!(row[7..].iter().all(|value| value.is_empty()));
+            },
+            "area" => {
+                assert!(row[4..7].iter().all(|value| value.is_empty()));
+                assert!(row[7].parse::<f64>().unwrap() >= 0.0);
+            },
+            kind => panic!("Unexpected measurement kind: {}", kind)
+        }
+    }
+    assert!(crossings >= simulation.statistics.travel_time.count);
+
+    let lines = read_lines(&diagram_filename);
+    assert!(lines[0].starts_with("time_from,time_to,area_id,density,speed,flow,line_flow"));
+    let columns = lines[0].split(',').count();
+    // bins with nobody in the area are left out of the diagram
+    assert_eq!(lines.len() - 1, rows.iter().filter(|row| row[2] == "area" && !row[8].is_empty()).count());
+    assert!(lines[1..].iter().all(|line| line.split(',').count() == columns && line.split(',').nth(2) == Some("0")));
+}
@@ -0,0 +1,87 @@
+extern crate crowd_sim;
+
+mod common;
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::Write;
+use std::rc::Rc;
+
+use crowd_sim::Person;
+use crowd_sim::Point;
+use crowd_sim::Simulation;
+use crowd_sim::configuration;
+use crowd_sim::simulation::observer::SimulationObserver;
+
+struct SpawnObserver(Rc<RefCell<Vec<(u32, Point)>>>);
+
+impl SimulationObserver for SpawnObserver {
+    fn on_spawn(&mut self, _simulation: &Simulation, person: &Person) {
+        self.0.borrow_mut().push((person.id, person.coordinates));
+    }
+}
+
+#[test]
+fn test_population_positions_are_read_in_meters() {
+    let filename = ::std::env::temp_dir().join("crowd_sim_population_test.csv");
+    let mut file = File::create(&filename).unwrap();
+    file.write_all(b"x,y,path\n1.0,2.5,0\n4.0,1.25,0\n").unwrap();
+
+    let mut config = common::flow_config();
+    common::item(&mut config, 0x03, 0x04); common::string(&mut config, filename.to_str().unwrap());
+    let spawned = Rc::new(RefCell::new(Vec::new()));
+    let mut simulation = Simulation::new(configuration::new(&mut &config[..]));
+    simulation.add_observer(Box::new(SpawnObserver(spawned.clone())));
+    simulation.step();
+
+    // scene is 5 cm per pixel
+    let spawned = spawned.borrow();
+    let expected = [(0, Point::new(20.0, 50.0)), (1, Point::new(80.0, 25.0))];
+    for (&(id, coordinates), &(expected_id, expected_coordinates)) in spawned.iter().zip(expected.iter()) {
+        assert_eq!(id, expected_id);
+        assert!((coordinates.x - expected_coordinates.x).abs() < 1e-9 && (coordinates.y - expected_coordinates.y).abs() < 1e-9);
+    }
+}
+
+fn spawned_in_escape(population_kind: u8, value: f64) -> Vec<(u32, Point)> {
+    let mut config = common::room_config();
+    common::item(&mut config, 0x00, 0x01); config.push(2);
+    common::item(&mut config, 0x00, 0x04); config.extend_from_slice(&[0, 0, 0, 5]);
+    common::wall(&mut config, [5, 0, 5, 100], 0);
+    common::wall(&mut config, [95, 0, 95, 100], 0);
+    common::spawn_area(&mut config, [10, 10, 90, 60], 0, 0);
+    common::target_area(&mut config, [10, 90, 90, 100], 0, 0, true, 0);
+    common::item(&mut config, 0x03, 0x05); config.push(0); config.push(population_kind); common::f64_le(&mut config, value);
+    let spawned = Rc::new(RefCell::new(Vec::new()));
+    let mut simulation = Simulation::new(configuration::new(&mut &config[..]));
+    simulation.add_observer(Box::new(SpawnObserver(spawned.clone())));
+    simulation.step();
+    let spawned = spawned.borrow().clone();
+    spawned
+}
+
+fn assert_spaced(spawned: &Vec<(u32, Point)>) {
+    for (i, &(_id, first)) in spawned.iter().enumerate() {
+        assert!(first.x > 10.0 && first.x < 90.0 && first.y > 10.0 && first.y < 60.0);
+        for &(_id, second) in spawned[i + 1 ..].iter() {
+            // 0.4 meters wide bodies at 5 cm per pixel
+            let distance = ((first.x - second.x).powi(2) + (first.y - second.y).powi(2)).sqrt();
+            assert!(distance >= 8.0 - 1e-9);
+        }
+    }
+}
+
+#[test]
+fn test_escape_area_filled_by_head_count() {
+    let spawned = spawned_in_escape(1, 20.0);
+    assert_eq!(spawned.len(), 20);
+    assert_spaced(&spawned);
+}
+
+#[test]
+fn test_escape_area_filled_by_density() {
+    // spawn area is 4 by 2.5 meters
+    let spawned = spawned_in_escape(2, 2.0);
+    assert_eq!(spawned.len(), 20);
+    assert_spaced(&spawned);
+}
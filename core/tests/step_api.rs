@@ -0,0 +1,112 @@
+extern crate crowd_sim;
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crowd_sim::Forceable;
+use crowd_sim::Person;
+use crowd_sim::Scene;
+use crowd_sim::Simulation;
+use crowd_sim::Vector;
+use crowd_sim::simulation::observer::SimulationObserver;
+use crowd_sim::simulation::events::SimulationEvent;
+use crowd_sim::simulation::events::SimulationEventKind;
+
+#[derive(Default)]
+struct Counters {
+    started: u32,
+    ticks: u32,
+    spawned: u32,
+    reached: u32,
+    exited: u32,
+    exit_events: u32,
+    finished: u32,
+}
+
+struct CountingObserver(Rc<RefCell<Counters>>);
+
+impl SimulationObserver for CountingObserver {
+    fn on_start(&mut self, _simulation: &Simulation) { self.0.borrow_mut().started += 1; }
+    fn on_tick(&mut self, _simulation: &Simulation) { self.0.borrow_mut().ticks += 1; }
+    fn on_spawn(&mut self, _simulation: &Simulation, _person: &Person) { self.0.borrow_mut().spawned += 1; }
+    fn on_target_reached(&mut self, _simulation: &Simulation, _person: &Person, _target_index: u16) { self.0.borrow_mut().reached += 1; }
+    fn on_exit(&mut self, _simulation: &Simulation, _person: &Person) { self.0.borrow_mut().exited += 1; }
+    fn on_event(&mut self, _simulation: &Simulation, event: &SimulationEvent) {
+        if event.kind == SimulationEventKind::Exit { self.0.borrow_mut().exit_events += 1; }
+    }
+    fn on_finish(&mut self, _simulation: &Simulation) { self.0.borrow_mut().finished += 1; }
+}
+
+#[test]
+fn test_step_notifies_observers() {
+    let counters = Rc::new(RefCell::new(Counters::default()));
+    let mut simulation = common::new_simulation();
+    simulation.add_observer(Box::new(CountingObserver(counters.clone())));
+
+    let mut steps = 0;
+    while !simulation.is_finished() {
+        simulation.step();
+        steps += 1;
+        let counters = counters.borrow();
+        assert_eq!(counters.spawned - counters.exited, simulation.scene.people.len() as u32);
+    }
+    simulation.finish();
+    simulation.finish();
+
+    let counters = counters.borrow();
+    assert_eq!(counters.started, 1);
+    assert_eq!(counters.ticks, steps);
+    assert!(counters.exited > 0);
+    assert_eq!(counters.reached, counters.exited);
+    assert_eq!(counters.exit_events, counters.exited);
+    assert_eq!(counters.finished, 1);
+    assert_eq!(simulation.statistics.travel_time.count, counters.exited);
+}
+
+#[derive(Debug)]
+struct BackwardForce;
+
+impl Forceable for BackwardForce {
+    fn force_for_person(&self, _person: &Person, _scene: &Scene) -> Vector {
+        // outweighs target force, which pulls people up
+        Vector::new(0.0, 20.0)
+    }
+}
+
+#[test]
+fn test_custom_force() {
+    let counters = Rc::new(RefCell::new(Counters::default()));
+    let mut simulation = common::new_simulation();
+    simulation.add_force("backward", Box::new(BackwardForce), 2.0);
+    simulation.add_observer(Box::new(CountingObserver(counters.clone())));
+    assert!(simulation.active_forces().contains(&("backward", 2.0)));
+
+    while !simulation.is_finished() {
+        simulation.step();
+    }
+    simulation.finish();
+
+    let counters = counters.borrow();
+    assert!(counters.spawned > 0);
+    assert_eq!(counters.exited, 0);
+}
+
+#[test]
+fn test_travel_statistics_count_from_spawn() {
+    let mut simulation = common::new_simulation();
+    while !simulation.is_finished() {
+        simulation.step();
+    }
+    simulation.finish();
+
+    let ref statistics = simulation.statistics;
+    assert!(statistics.travel_time.count > 1);
+    // people spawned later have spent less time on scene than simulation lasted
+    assert!(statistics.travel_time.max < simulation.time.current_time - 1.0);
+    // spawn area is 3.5 meters away from exit
+    assert!(statistics.travel_distance.min >= 3.4);
+    assert_eq!(statistics.travel_speed.count, statistics.travel_time.count);
+    assert!(statistics.travel_speed.current_avg() > 0.5 && statistics.travel_speed.current_avg() < 2.0);
+}
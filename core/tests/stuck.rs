@@ -0,0 +1,127 @@
+extern crate crowd_sim;
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crowd_sim::Simulation;
+use crowd_sim::configuration;
+use crowd_sim::simulation::events::SimulationEvent;
+use crowd_sim::simulation::events::SimulationEventKind;
+use crowd_sim::simulation::observer::SimulationObserver;
+
+const REPORT: u8 = 1;
+const PERTURB: u8 = 2;
+const REROUTE: u8 = 3;
+const REMOVE: u8 = 4;
+
+// stuck people with how far they were moved by resolution
+struct StuckObserver(Rc<RefCell<Vec<Option<f64>>>>);
+
+impl SimulationObserver for StuckObserver {
+    fn on_event(&mut self, simulation: &Simulation, event: &SimulationEvent) {
+        if let SimulationEventKind::Stuck{ .. } = event.kind {
+            let moved = simulation.scene.people.iter().find(|person| Some(person.id) == event.person_id)
+                .map(|person| (person.coordinates - event.location).length());
+            self.0.borrow_mut().push(moved);
+        }
+    }
+}
+
+fn stuck_config(strategy: u8) -> Vec<u8> {
+    let mut config = common::room_config();
+    common::item(&mut config, 0x00, 0x04); config.extend_from_slice(&[0, 0, 0, 3]);
+    common::item(&mut config, 0x0F, 0x01); config.push(1);
+    common::item(&mut config, 0x0F, 0x02); common::f64_le(&mut config, 2.0);
+    common::item(&mut config, 0x0F, 0x03); common::f64_le(&mut config, 0.3);
+    common::item(&mut config, 0x0F, 0x04); config.push(strategy);
+    config
+}
+
+// people come at the left and head to the exit above them, which is cut off by a wall
+fn blocked_exit_config(strategy: u8) -> Vec<u8> {
+    let mut config = stuck_config(strategy);
+    common::wall(&mut config, [5, 0, 5, 100], 0);
+    common::wall(&mut config, [95, 0, 95, 100], 0);
+    common::wall(&mut config, [5, 40, 50, 40], 0);
+    common::spawn_area(&mut config, [10, 80, 40, 95], 0, 0);
+    common::target_area(&mut config, [10, 0, 40, 10], 0, 0, true, 0);
+    config
+}
+
+fn run_observed(config: Vec<u8>) -> (Simulation, Vec<Option<f64>>) {
+    let stuck = Rc::new(RefCell::new(Vec::new()));
+    let mut simulation = Simulation::new(configuration::new(&mut &config[..]));
+    simulation.add_observer(Box::new(StuckObserver(stuck.clone())));
+    while !simulation.is_finished() {
+        simulation.step();
+    }
+    simulation.finish();
+    let stuck = stuck.borrow().clone();
+    (simulation, stuck)
+}
+
+#[test]
+fn test_stuck_people_reported() {
+    let (simulation, stuck) = run_observed(blocked_exit_config(REPORT));
+
+    let stuck_statistics = simulation.statistics.stuck.as_ref().unwrap();
+    assert!(stuck_statistics.flagged > 0);
+    assert_eq!(stuck_statistics.flagged as usize, stuck.len());
+    assert_eq!(stuck_statistics.removed, 0);
+    assert!(stuck.iter().all(|&moved| moved == Some(0.0)));
+    assert_eq!(simulation.statistics.travel_time.count, 0);
+}
+
+#[test]
+fn test_stuck_people_perturbed() {
+    let (simulation, stuck) = run_observed(blocked_exit_config(PERTURB));
+
+    assert!(simulation.statistics.stuck.as_ref().unwrap().flagged > 0);
+    // perturbation is 0.5 meters by default, people are moved at least half of it when there is room
+    assert!(stuck.iter().any(|&moved| moved.unwrap() >= 5.0 - 1e-9));
+    assert!(stuck.iter().all(|&moved| moved.unwrap() <= 10.0 + 1e-9));
+}
+
+#[test]
+fn test_stuck_people_rerouted_to_alternative() {
+    let mut config = blocked_exit_config(REROUTE);
+    // farther alternative of the same target beyond the end of the wall
+    common::target_area(&mut config, [60, 0, 90, 10], 0, 0, true, 0);
+    let (simulation, stuck) = run_observed(config);
+
+    assert!(stuck.len() > 0);
+    assert!(simulation.statistics.travel_time.count > 0);
+}
+
+#[test]
+fn test_stuck_people_removed() {
+    let (simulation, stuck) = run_observed(blocked_exit_config(REMOVE));
+
+    let stuck_statistics = simulation.statistics.stuck.as_ref().unwrap();
+    assert!(stuck_statistics.removed > 0);
+    assert_eq!(stuck_statistics.removed, stuck_statistics.flagged);
+    assert!(stuck.iter().all(|moved| moved.is_none()));
+}
+
+#[test]
+fn test_people_queued_at_full_connector_not_stuck() {
+    let mut config = stuck_config(REPORT);
+    common::item(&mut config, 0x01, 0xFF); common::string(&mut config, "upstairs.svg");
+    common::wall(&mut config, [5, 0, 5, 100], 0);
+    common::wall(&mut config, [95, 0, 95, 100], 0);
+    common::spawn_area(&mut config, [10, 80, 90, 95], 0, 0);
+    common::target_area(&mut config, [10, 80, 90, 95], 0, 0, true, 1);
+    // stairs take one person at a time for a minute
+    common::item(&mut config, 0x09, 0x01);
+    config.push(0); for &coordinate in [10, 0, 90, 30].iter() { common::u16_be(&mut config, coordinate); }
+    config.push(1); for &coordinate in [10, 0, 90, 30].iter() { common::u16_be(&mut config, coordinate); }
+    common::f64_le(&mut config, 60.0); common::u16_be(&mut config, 1);
+    let (simulation, stuck) = run_observed(config);
+
+    let waiting = simulation.scene.people.iter().filter(|person| person.floor == 0 && person.coordinates.y < 30.0).count();
+    assert!(waiting > 1);
+    assert_eq!(simulation.statistics.stuck.as_ref().unwrap().flagged, 0);
+    assert!(stuck.is_empty());
+}
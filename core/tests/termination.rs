@@ -0,0 +1,36 @@
+extern crate crowd_sim;
+
+mod common;
+
+use crowd_sim::Simulation;
+use crowd_sim::configuration;
+use crowd_sim::simulation::termination::TerminationReason;
+
+fn run(config: Vec<u8>) -> Simulation {
+    let mut simulation = Simulation::new(configuration::new(&mut &config[..]));
+    while !simulation.is_finished() {
+        simulation.step();
+    }
+    simulation.finish();
+    simulation
+}
+
+#[test]
+fn test_max_time_stops_before_end_time() {
+    let mut config = common::flow_config();
+    common::item(&mut config, 0x10, 0x01); common::f64_le(&mut config, 4.0);
+    let simulation = run(config);
+
+    assert!(simulation.time.current_time < 4.1);
+    assert_eq!(simulation.statistics.termination_reason, Some(TerminationReason::MaxTime));
+}
+
+#[test]
+fn test_arrivals_stop_simulation() {
+    let mut config = common::flow_config();
+    common::item(&mut config, 0x10, 0x02); config.extend_from_slice(&[0, 0, 0, 2]);
+    let simulation = run(config);
+
+    assert_eq!(simulation.statistics.travel_time.count, 2);
+    assert_eq!(simulation.statistics.termination_reason, Some(TerminationReason::Arrivals));
+}
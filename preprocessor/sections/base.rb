@@ -39,13 +39,13 @@ module Sections
     end
 
     def get_data(key)
-      return data[key] if data[key]
+      return data[key] if data.key?(key)
       field = fields[key]
       data[key] = get_value(field, nil) if field
     end
 
     def get_value(field, value, &blc)
-      value ||= field[:default]
+      value = field[:default] if value.nil?
       value = get_ref_value(value) if String === value && value.start_with?('ref:')
       value = case field[:type]
               when :int then value.to_i
@@ -87,7 +87,8 @@ module Sections
       def descendants_to_config
         fields.select{ |_field_name, field| %i[descendant distribution].include? field[:type] }.inject('') do |res, (_field_name, field)|
           descendant = data[field[:name]]
-          res + descendant.to_config
+          # optional sections and distributions may be omitted from sim params
+          descendant ? res + descendant.to_config : res
         end
       end
   end
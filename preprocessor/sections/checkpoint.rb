@@ -0,0 +1,25 @@
+require_relative 'base'
+
+module Sections
+  class Checkpoint < Base
+    CHECKPOINT_SECTION = 0x0D
+    CHECKPOINT_ELEMENTS = {'save_file' => 0x01, 'save_time' => 0x02, 'restore_file' => 0x03}
+    CHECKPOINT_ELEMENTS_TEMPLATES = {'save_file' => 'S>A:len:', 'save_time' => 'E', 'restore_file' => 'S>A:len:'}
+
+    # whole simulation state is written to save_file once save_time is reached
+    field name: 'save_file', type: :string
+    field name: 'save_time', type: :float
+    # simulation starts from state saved before, sim params besides checkpoint section must be the same
+    field name: 'restore_file', type: :string
+
+    def to_config
+      CHECKPOINT_ELEMENTS.keys.select{ |element| data[element] }.inject('') do |config, element|
+        value = data[element]
+        template = CHECKPOINT_ELEMENTS_TEMPLATES[element]
+        values = String === value ? [value.size, value] : [value]
+        template = template.sub(':len:', value.size.to_s) if String === value
+        config + [CHECKPOINT_SECTION, CHECKPOINT_ELEMENTS[element], *values].pack(CONFIG_ITEM_TEMPLATE_PREFIX + template)
+      end
+    end
+  end
+end
@@ -0,0 +1,41 @@
+require_relative 'base'
+
+module Sections
+  class Connectors < Base
+    CONNECTORS_SECTION = 0x09
+    CONNECTORS_ELEMENTS = {'stairs' => 0x01, 'lift' => 0x02}
+    # from_floor from_x0 from_y0 from_x1 from_y1 to_floor to_x0 to_y0 to_x1 to_y1 traversal_time capacity
+    CONNECTOR_TEMPLATE = 'CS>S>S>S>CS>S>S>S>ES>'
+
+    # entry and exit areas are given either by x-csim-name from svg file or as [floor, x0, y0, x1, y1];
+    # stairs let people in one by one while capacity allows, lift carries a whole cabin and returns empty
+    def stairs(from:, to:, time:, capacity:)
+      add_connector('stairs', from, to, time, capacity)
+    end
+
+    def lift(from:, to:, time:, capacity:)
+      add_connector('lift', from, to, time, capacity)
+    end
+
+    def to_config
+      (data['connectors'] || []).inject('') do |config, connector|
+        values = scene_area(connector[:from]) + scene_area(connector[:to]) + [connector[:time], connector[:capacity]]
+        config + [CONNECTORS_SECTION, CONNECTORS_ELEMENTS[connector[:type]], *values].pack(CONFIG_ITEM_TEMPLATE_PREFIX + CONNECTOR_TEMPLATE)
+      end
+    end
+
+    private
+
+      def add_connector(type, from, to, time, capacity)
+        # nobody could ever board, people routed through it would wait forever
+        raise ArgumentError, "Capacity of #{type} from #{from} to #{to} must be positive" unless capacity.to_i > 0
+        (data['connectors'] ||= []) << {type: type, from: from, to: to, time: time.to_f, capacity: capacity.to_i}
+      end
+
+      def scene_area(area)
+        return area.map(&:to_i) if Array === area
+        scene = parent.data['scene']
+        [scene.named_element_floor(area)] + scene.named_element(area)
+      end
+  end
+end
@@ -4,22 +4,30 @@ module Sections
   class DensityMap < Base
     DM_SECTION = 0x06
     DM_ELEMENTS = {
-      'enabled' => 0x01, 'min_threshold' => 0x02, 'max_threshold' => 0x03
+      'enabled' => 0x01, 'min_threshold' => 0x02, 'max_threshold' => 0x03,
+      'method' => 0x04, 'voronoi_cutoff' => 0x05
     }
 
     DM_ELEMENTS_TEMPLATES = {
-      'enabled' => 'C', 'min_threshold' => 'E', 'max_threshold' => 'E'
+      'enabled' => 'C', 'min_threshold' => 'E', 'max_threshold' => 'E',
+      'method' => 'C', 'voronoi_cutoff' => 'E'
     }
 
     field name: 'enabled', type: :bool
     field name: 'min_threshold', type: :float
     field name: 'max_threshold', type: :float
+    # kernel sum in pixels or people per square meter from voronoi cells clipped by walls, thresholds are in the same units
+    field name: 'method', type: :enum, values: {'kernel' => 0x01, 'voronoi' => 0x02}
+    # meters, voronoi cells of lonely people don't grow further, also used by measurement and trajectory
+    field name: 'voronoi_cutoff', type: :float
 
     def to_config
       config = ""
       config += [DM_SECTION, DM_ELEMENTS['enabled'], get_data('enabled') ? 1 : 0].pack(CONFIG_ITEM_TEMPLATE_PREFIX + DM_ELEMENTS_TEMPLATES['enabled'])
       config += [DM_SECTION, DM_ELEMENTS['min_threshold'], get_data('min_threshold')].pack(CONFIG_ITEM_TEMPLATE_PREFIX + DM_ELEMENTS_TEMPLATES['min_threshold'])
       config += [DM_SECTION, DM_ELEMENTS['max_threshold'], get_data('max_threshold')].pack(CONFIG_ITEM_TEMPLATE_PREFIX + DM_ELEMENTS_TEMPLATES['max_threshold'])
+      config += [DM_SECTION, DM_ELEMENTS['method'], data['method']].pack(CONFIG_ITEM_TEMPLATE_PREFIX + DM_ELEMENTS_TEMPLATES['method']) if data['method']
+      config += [DM_SECTION, DM_ELEMENTS['voronoi_cutoff'], data['voronoi_cutoff']].pack(CONFIG_ITEM_TEMPLATE_PREFIX + DM_ELEMENTS_TEMPLATES['voronoi_cutoff']) if data['voronoi_cutoff']
       config += super.to_s
       config
     end
@@ -0,0 +1,25 @@
+module Sections::Force
+  # enabled flag and weight every force has
+  module Composition
+    ENABLED_ELEMENT = 0x10
+    WEIGHT_ELEMENT = 0x11
+
+    def self.included(base)
+      base.field name: 'enabled', type: :bool
+      base.field name: 'weight', type: :float
+    end
+
+    def to_config
+      config = descendants_to_config
+      config += [Sections::Forces::FORCES_SECTION, subsection | ENABLED_ELEMENT, data['enabled'] ? 1 : 0].pack(CONFIG_ITEM_TEMPLATE_PREFIX + 'C') if data.key?('enabled')
+      config += [Sections::Forces::FORCES_SECTION, subsection | WEIGHT_ELEMENT, data['weight']].pack(CONFIG_ITEM_TEMPLATE_PREFIX + 'E') if data.key?('weight')
+      config + params_to_config
+    end
+
+    private
+
+      def params_to_config
+        ''
+      end
+  end
+end
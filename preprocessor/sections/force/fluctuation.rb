@@ -0,0 +1,41 @@
+require_relative '../base'
+
+module Sections::Force
+  class Fluctuation < Sections::Base
+    FLUCTUATION_SUBSECTION = 0x0300
+    FLUCTUATION_MAGNITUDE_ELEMENT = FLUCTUATION_SUBSECTION | 0x01
+    FLUCTUATION_POWER_ELEMENT = FLUCTUATION_SUBSECTION | 0x02
+    FLUCTUATION_MODE_ELEMENT = FLUCTUATION_SUBSECTION | 0x03
+    FLUCTUATION_CORRELATION_TIME_ELEMENT = FLUCTUATION_SUBSECTION | 0x04
+    FLUCTUATION_LEVEL_ELEMENT = FLUCTUATION_SUBSECTION | 0x05
+
+    include Composition
+
+    # maximal length of random force, shortcut for uniform power from 0 to magnitude
+    field name: 'magnitude', type: :float
+    # length of random force drawn every tick, direction is always uniform
+    field name: 'power', type: :distribution, current_section: Sections::Forces::FORCES_SECTION,
+          element: FLUCTUATION_POWER_ELEMENT
+    # white noise is independent every tick, ornstein_uhlenbeck one changes smoothly
+    field name: 'mode', type: :enum, values: {'white' => 0x01, 'ornstein_uhlenbeck' => 0x02}
+    # seconds, how long correlated noise keeps its direction
+    field name: 'correlation_time', type: :float
+    # per person multiplier of random force
+    field name: 'level', type: :distribution, current_section: Sections::Forces::FORCES_SECTION,
+          element: FLUCTUATION_LEVEL_ELEMENT
+
+    private
+
+      def subsection
+        FLUCTUATION_SUBSECTION
+      end
+
+      def params_to_config
+        config = ''
+        config += [Sections::Forces::FORCES_SECTION, FLUCTUATION_MAGNITUDE_ELEMENT, data['magnitude']].pack(CONFIG_ITEM_TEMPLATE_PREFIX + 'E') if data.key?('magnitude')
+        config += [Sections::Forces::FORCES_SECTION, FLUCTUATION_MODE_ELEMENT, data['mode']].pack(CONFIG_ITEM_TEMPLATE_PREFIX + 'C') if data.key?('mode')
+        config += [Sections::Forces::FORCES_SECTION, FLUCTUATION_CORRELATION_TIME_ELEMENT, data['correlation_time']].pack(CONFIG_ITEM_TEMPLATE_PREFIX + 'E') if data.key?('correlation_time')
+        config
+      end
+  end
+end
@@ -0,0 +1,16 @@
+require_relative '../base'
+
+module Sections::Force
+  # push away from hazard, its strength is set by repulsion in hazard section
+  class Hazard < Sections::Base
+    HAZARD_SUBSECTION = 0x0400
+
+    include Composition
+
+    private
+
+      def subsection
+        HAZARD_SUBSECTION
+      end
+  end
+end
@@ -4,8 +4,33 @@ module Sections::Force
   class Repulsion < Sections::Base
     REPULSION_SUBSECTION = 0x0100
     REPULSION_COEFF_ELEMENT = REPULSION_SUBSECTION | 0x01
+    REPULSION_PARAMS_ELEMENTS = {'distance_sqr_threshold' => REPULSION_SUBSECTION | 0x02,
+                                 'ellipse_rx' => REPULSION_SUBSECTION | 0x03,
+                                 'ellipse_ry' => REPULSION_SUBSECTION | 0x04,
+                                 'cap' => REPULSION_SUBSECTION | 0x05}
+
+    include Composition
 
     field name: 'coeff', type: :distribution, current_section: Sections::Forces::FORCES_SECTION,
           element: REPULSION_COEFF_ELEMENT
+    # obstacles farther than square root of this are ignored, meters
+    field name: 'distance_sqr_threshold', type: :float
+    # repulsion ellipse radii, person is more sensitive to what is in front of him
+    field name: 'ellipse_rx', type: :float
+    field name: 'ellipse_ry', type: :float
+    # maximal length of repulsion force
+    field name: 'cap', type: :float
+
+    private
+
+      def subsection
+        REPULSION_SUBSECTION
+      end
+
+      def params_to_config
+        REPULSION_PARAMS_ELEMENTS.select{ |param, _element| data.key?(param) }.inject('') do |config, (param, element)|
+          config + [Sections::Forces::FORCES_SECTION, element, data[param]].pack(CONFIG_ITEM_TEMPLATE_PREFIX + 'E')
+        end
+      end
   end
 end
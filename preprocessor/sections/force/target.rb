@@ -5,7 +5,15 @@ module Sections::Force
     TARGET_SUBSECTION = 0x0200
     TARGET_SPEED_ELEMENT = TARGET_SUBSECTION | 0x01
 
+    include Composition
+
     field name: 'speed', type: :distribution, current_section: Sections::Forces::FORCES_SECTION,
           element: TARGET_SPEED_ELEMENT
+
+    private
+
+      def subsection
+        TARGET_SUBSECTION
+      end
   end
 end
@@ -4,11 +4,16 @@ module Sections
   class Forces < Base
     FORCES_SECTION = 0x04
 
+    # every force may be switched off with `enabled false` and scaled with `weight`
     field name: 'target', type: :descendant, klass: 'Force::Target'
     field name: 'repulsion', type: :descendant, klass: 'Force::Repulsion'
+    field name: 'fluctuation', type: :descendant, klass: 'Force::Fluctuation'
+    field name: 'hazard', type: :descendant, klass: 'Force::Hazard'
   end
 end
 
+require_relative 'force/composition'
 require_relative 'force/repulsion'
 require_relative 'force/target'
-
+require_relative 'force/fluctuation'
+require_relative 'force/hazard'
@@ -0,0 +1,45 @@
+require_relative 'base'
+
+module Sections
+  class Hazard < Base
+    HAZARD_SECTION = 0x0B
+    HAZARD_ELEMENTS = {
+      'cell_size' => 0x01, 'diffusion' => 0x02, 'decay' => 0x03, 'source' => 0x04,
+      'speed_reduction' => 0x05, 'fov_reduction' => 0x06, 'repulsion' => 0x07
+    }
+    HAZARD_ELEMENTS_TEMPLATES = {
+      'cell_size' => 'E', 'diffusion' => 'E', 'decay' => 'E',
+      # floor x y start_time emission_rate
+      'source' => 'CS>S>EE',
+      'speed_reduction' => 'E', 'fov_reduction' => 'E', 'repulsion' => 'E'
+    }
+
+    # grid cell size, meters
+    field name: 'cell_size', type: :float, default: 0.5
+    # share of concentration difference spreading to neighbour cell per second
+    field name: 'diffusion', type: :float, default: 0.5
+    # share of concentration disappearing per second
+    field name: 'decay', type: :float, default: 0.0
+    # speed and field of view are reduced by this share per unit of concentration
+    field name: 'speed_reduction', type: :float, default: 0.5
+    field name: 'fov_reduction', type: :float, default: 0.5
+    # force pushing people down the concentration gradient
+    field name: 'repulsion', type: :float, default: 1.0
+
+    # source is given by point [x, y] on scene in pixels, it starts emitting at given time
+    def source(at:, floor: 0, start: 0.0, rate:)
+      (data['sources'] ||= []) << [floor.to_i, *at.map(&:to_i), start.to_f, rate.to_f]
+    end
+
+    def to_config
+      raise 'Hazard section needs at least one source' if (data['sources'] || []).empty?
+      config = %w[cell_size diffusion decay speed_reduction fov_reduction repulsion].inject('') do |res, element|
+        res + [HAZARD_SECTION, HAZARD_ELEMENTS[element], get_data(element)].pack(CONFIG_ITEM_TEMPLATE_PREFIX + HAZARD_ELEMENTS_TEMPLATES[element])
+      end
+      config += data['sources'].inject('') do |res, source|
+        res + [HAZARD_SECTION, HAZARD_ELEMENTS['source'], *source].pack(CONFIG_ITEM_TEMPLATE_PREFIX + HAZARD_ELEMENTS_TEMPLATES['source'])
+      end
+      config
+    end
+  end
+end
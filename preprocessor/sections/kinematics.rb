@@ -0,0 +1,34 @@
+require_relative 'base'
+
+module Sections
+  class Kinematics < Base
+    KINEMATICS_SECTION = 0x0E
+    KINEMATICS_ELEMENTS = {'turn_rate' => 0x01, 'max_speed' => 0x02, 'max_acceleration' => 0x03,
+                           'heading_model' => 0x04, 'instant_turn_threshold' => 0x05,
+                           'max_heading_diff_to_target' => 0x06}
+    KINEMATICS_ELEMENTS_TEMPLATES = {'heading_model' => 'C', 'instant_turn_threshold' => 'E',
+                                     'max_heading_diff_to_target' => 'E'}
+
+    # how fast person turns, degrees per second
+    field name: 'turn_rate', type: :distribution, current_section: KINEMATICS_SECTION,
+          element: KINEMATICS_ELEMENTS['turn_rate']
+    # meters per second
+    field name: 'max_speed', type: :distribution, current_section: KINEMATICS_SECTION,
+          element: KINEMATICS_ELEMENTS['max_speed']
+    # meters per second squared
+    field name: 'max_acceleration', type: :distribution, current_section: KINEMATICS_SECTION,
+          element: KINEMATICS_ELEMENTS['max_acceleration']
+    # walking: person faces where he walks, body: body turns separately and person may side-step
+    field name: 'heading_model', type: :enum, values: {'walking' => 0x01, 'body' => 0x02}
+    # smaller changes of walking direction happen instantly, degrees
+    field name: 'instant_turn_threshold', type: :float
+    # walking direction never deviates from target more than this, degrees
+    field name: 'max_heading_diff_to_target', type: :float
+
+    def to_config
+      KINEMATICS_ELEMENTS_TEMPLATES.keys.select{ |element| data.key?(element) }.inject(descendants_to_config) do |config, element|
+        config + [KINEMATICS_SECTION, KINEMATICS_ELEMENTS[element], data[element]].pack(CONFIG_ITEM_TEMPLATE_PREFIX + KINEMATICS_ELEMENTS_TEMPLATES[element])
+      end
+    end
+  end
+end
@@ -0,0 +1,26 @@
+require_relative 'base'
+
+module Sections
+  class Measurement < Base
+    MEASUREMENT_SECTION = 0x12
+    MEASUREMENT_ELEMENTS = {'bin' => 0x01, 'file' => 0x02, 'diagram_file' => 0x03}
+    MEASUREMENT_ELEMENTS_TEMPLATES = {'bin' => 'E', 'file' => 'S>A:len:', 'diagram_file' => 'S>A:len:'}
+
+    # seconds every row of measurements covers
+    field name: 'bin', type: :float, default: 1.0
+    # csv with crossings of measure lines and density and speed in measure areas, by head count and by voronoi cells
+    field name: 'file', type: :string
+    # csv with density, speed and flow of measure areas, flow is also measured on lines inside area
+    field name: 'diagram_file', type: :string
+
+    def to_config
+      MEASUREMENT_ELEMENTS.keys.select{ |element| get_data(element) }.inject('') do |config, element|
+        value = get_data(element)
+        template = MEASUREMENT_ELEMENTS_TEMPLATES[element]
+        values = String === value ? [value.size, value] : [value]
+        template = template.sub(':len:', value.size.to_s) if String === value
+        config + [MEASUREMENT_SECTION, MEASUREMENT_ELEMENTS[element], *values].pack(CONFIG_ITEM_TEMPLATE_PREFIX + template)
+      end
+    end
+  end
+end
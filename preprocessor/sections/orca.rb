@@ -0,0 +1,22 @@
+require_relative 'base'
+
+module Sections
+  class Orca < Base
+    ORCA_SECTION = 0x0C
+    ORCA_ELEMENTS = {'time_horizon' => 0x01, 'obstacle_time_horizon' => 0x02, 'neighbor_distance' => 0x03}
+    ORCA_ELEMENTS_TEMPLATES = {'time_horizon' => 'E', 'obstacle_time_horizon' => 'E', 'neighbor_distance' => 'E'}
+
+    # how far in future collisions with other people are avoided, seconds
+    field name: 'time_horizon', type: :float, default: 2.0
+    # same for walls
+    field name: 'obstacle_time_horizon', type: :float, default: 1.0
+    # other people farther than this are ignored, meters
+    field name: 'neighbor_distance', type: :float, default: 5.0
+
+    def to_config
+      ORCA_ELEMENTS.keys.inject('') do |config, element|
+        config + [ORCA_SECTION, ORCA_ELEMENTS[element], get_data(element)].pack(CONFIG_ITEM_TEMPLATE_PREFIX + ORCA_ELEMENTS_TEMPLATES[element])
+      end
+    end
+  end
+end
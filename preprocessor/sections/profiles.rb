@@ -0,0 +1,66 @@
+require_relative 'base'
+
+module Sections
+  class Profiles < Base
+    PROFILES_SECTION = 0x07
+    PROFILES_ELEMENTS = {
+      'name' => 0x01, 'radius' => 0x02, 'speed' => 0x03,
+      'repulsion' => 0x04, 'fov_forward' => 0x05, 'fov_backward' => 0x06
+    }
+    PROFILES_ELEMENTS_TEMPLATES = {
+      # profile_id name
+      'name' => 'CS>A:len:'
+    }
+
+    field name: 'profile', type: :custom, parser: :add_profile
+
+    def add_profile(name, &blc)
+      profiles = data['profile'] || []
+      raise "Profile #{name} is described twice" if profiles.any?{ |profile| profile.name == name }
+      profiles << Profile.new(self, name, profiles.size, &blc)
+    end
+
+    def profile_id(name)
+      profile = (data['profile'] || []).find{ |pr| pr.name == name.to_s }
+      raise "Unknown profile: #{name}" unless profile
+      profile.id
+    end
+
+    def to_config
+      (data['profile'] || []).inject('') do |res, profile|
+        res + profile.to_config
+      end
+    end
+  end
+
+  class Profile < Base
+    attr_reader :name, :id
+
+    field name: 'radius', type: :distribution, current_section: Profiles::PROFILES_SECTION,
+          element: Profiles::PROFILES_ELEMENTS['radius']
+    field name: 'speed', type: :distribution, current_section: Profiles::PROFILES_SECTION,
+          element: Profiles::PROFILES_ELEMENTS['speed']
+    field name: 'repulsion', type: :distribution, current_section: Profiles::PROFILES_SECTION,
+          element: Profiles::PROFILES_ELEMENTS['repulsion']
+    field name: 'fov_forward', type: :distribution, current_section: Profiles::PROFILES_SECTION,
+          element: Profiles::PROFILES_ELEMENTS['fov_forward']
+    field name: 'fov_backward', type: :distribution, current_section: Profiles::PROFILES_SECTION,
+          element: Profiles::PROFILES_ELEMENTS['fov_backward']
+
+    def initialize(parent, name, id, &blc)
+      @name, @id = name.to_s, id
+      super(parent, &blc)
+    end
+
+    def distribution_ids
+      [id]
+    end
+
+    def to_config
+      name_template = Profiles::PROFILES_ELEMENTS_TEMPLATES['name'].sub(':len:', name.size.to_s)
+      config = [Profiles::PROFILES_SECTION, Profiles::PROFILES_ELEMENTS['name'], id, name.size, name].pack(CONFIG_ITEM_TEMPLATE_PREFIX + name_template)
+      config += super.to_s
+      config
+    end
+  end
+end
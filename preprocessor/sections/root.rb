@@ -5,6 +5,18 @@ require_relative 'spawn'
 require_relative 'time'
 require_relative 'fov'
 require_relative 'density_map'
+require_relative 'profiles'
+require_relative 'schedule'
+require_relative 'connectors'
+require_relative 'services'
+require_relative 'hazard'
+require_relative 'orca'
+require_relative 'checkpoint'
+require_relative 'kinematics'
+require_relative 'stuck'
+require_relative 'termination'
+require_relative 'trajectory'
+require_relative 'measurement'
 
 module Sections
   class Root < Base
@@ -13,16 +25,41 @@ module Sections
     end
 
     GENERAL_SECTION = 0x00
-    GENERAL_ELEMENTS = {'type' => 0x01}
-    GENERAL_ELEMENTS_TEMPLATES = {'type' => 'C'}
+    GENERAL_ELEMENTS = {'type' => 0x01, 'model' => 0x02, 'events_file' => 0x03, 'seed' => 0x04,
+                        'run_mode' => 0x05, 'speed' => 0x06, 'trips_file' => 0x07}
+    GENERAL_ELEMENTS_TEMPLATES = {'type' => 'C', 'model' => 'C', 'events_file' => 'S>A:len:', 'seed' => 'L>',
+                                  'run_mode' => 'C', 'speed' => 'E', 'trips_file' => 'S>A:len:'}
 
     field name: 'type', type: :enum, values: {'flow' => 0x01, 'escape' => 0x02}, default: 'flow'
+    # model resolving interactions between people: repulsion forces or velocity obstacles
+    field name: 'model', type: :enum, values: {'social_force' => 0x01, 'orca' => 0x02}, default: 'social_force'
+    # spawns, exits and other events of every person are written there as json lines
+    field name: 'events_file', type: :string
+    # runs with the same seed are identical, random one is used when omitted
+    field name: 'seed', type: :int
+    # batch runs as fast as possible, real_time keeps simulated time at speed times wall clock
+    field name: 'run_mode', type: :enum, values: {'batch' => 0x01, 'real_time' => 0x02}
+    field name: 'speed', type: :float
+    # csv with a row per person: spawn, target and exit times, walked distance and personal parameters
+    field name: 'trips_file', type: :string
     field name: 'scene', type: :descendant, klass: 'Scene'
     field name: 'time', type: :descendant, klass: 'Time'
     field name: 'spawn', type: :descendant, klass: 'Spawn'
     field name: 'forces', type: :descendant, klass: 'Forces'
     field name: 'fov', type: :descendant, klass: 'Fov'
     field name: 'density_map', type: :descendant, klass: 'DensityMap'
+    field name: 'profiles', type: :descendant, klass: 'Profiles'
+    field name: 'schedule', type: :descendant, klass: 'Schedule'
+    field name: 'connectors', type: :descendant, klass: 'Connectors'
+    field name: 'services', type: :descendant, klass: 'Services'
+    field name: 'hazard', type: :descendant, klass: 'Hazard'
+    field name: 'orca', type: :descendant, klass: 'Orca'
+    field name: 'checkpoint', type: :descendant, klass: 'Checkpoint'
+    field name: 'kinematics', type: :descendant, klass: 'Kinematics'
+    field name: 'stuck', type: :descendant, klass: 'Stuck'
+    field name: 'termination', type: :descendant, klass: 'Termination'
+    field name: 'trajectory', type: :descendant, klass: 'Trajectory'
+    field name: 'measurement', type: :descendant, klass: 'Measurement'
 
     def get_ref_value(value)
       value = value.sub('ref:', '')
@@ -43,6 +80,20 @@ module Sections
     def to_config
       config = ""
       config += [GENERAL_SECTION, GENERAL_ELEMENTS['type'], get_data('type')].pack(CONFIG_ITEM_TEMPLATE_PREFIX + GENERAL_ELEMENTS_TEMPLATES['type'])
+      config += [GENERAL_SECTION, GENERAL_ELEMENTS['model'], get_data('model')].pack(CONFIG_ITEM_TEMPLATE_PREFIX + GENERAL_ELEMENTS_TEMPLATES['model'])
+      if data['events_file']
+        events_file = data['events_file']
+        events_file_template = GENERAL_ELEMENTS_TEMPLATES['events_file'].sub(':len:', events_file.size.to_s)
+        config += [GENERAL_SECTION, GENERAL_ELEMENTS['events_file'], events_file.size, events_file].pack(CONFIG_ITEM_TEMPLATE_PREFIX + events_file_template)
+      end
+      config += [GENERAL_SECTION, GENERAL_ELEMENTS['seed'], data['seed']].pack(CONFIG_ITEM_TEMPLATE_PREFIX + GENERAL_ELEMENTS_TEMPLATES['seed']) if data['seed']
+      config += [GENERAL_SECTION, GENERAL_ELEMENTS['run_mode'], data['run_mode']].pack(CONFIG_ITEM_TEMPLATE_PREFIX + GENERAL_ELEMENTS_TEMPLATES['run_mode']) if data['run_mode']
+      config += [GENERAL_SECTION, GENERAL_ELEMENTS['speed'], data['speed']].pack(CONFIG_ITEM_TEMPLATE_PREFIX + GENERAL_ELEMENTS_TEMPLATES['speed']) if data['speed']
+      if data['trips_file']
+        trips_file = data['trips_file']
+        trips_file_template = GENERAL_ELEMENTS_TEMPLATES['trips_file'].sub(':len:', trips_file.size.to_s)
+        config += [GENERAL_SECTION, GENERAL_ELEMENTS['trips_file'], trips_file.size, trips_file].pack(CONFIG_ITEM_TEMPLATE_PREFIX + trips_file_template)
+      end
       config += super.to_s
       config
     end
@@ -6,55 +6,88 @@ module Sections
   class Scene < Base
     SCENE_SECTION = 0x01
     SCENE_ELEMENTS = {
-      'wall' => 0x01, 'spawn-area' => 0x02, 'target-area' => 0x03,
+      'wall' => 0x01, 'spawn-area' => 0x02, 'target-area' => 0x03, 'terrain' => 0x04,
+      'measure-line' => 0x05, 'measure-area' => 0x06,
       'width' => 0x11, 'height' => 0x12, 'scale' => 0x13,
       'file_name' => 0xFF
     }
     SCENE_ELEMENTS_TEMPLATES = {
-      # x0 y0 x1 y1
-      'wall' => 'S>S>S>S>',
-      # x0 y0 x1 y1 id
-      'spawn-area' => 'S>S>S>S>C',
-      # x0 y0 x1 y1 id seq_no(7bit)|last(1bit)
-      'target-area' => 'S>S>S>S>CC',
+      # x0 y0 x1 y1 floor
+      'wall' => 'S>S>S>S>C',
+      # x0 y0 x1 y1 id floor
+      'spawn-area' => 'S>S>S>S>CC',
+      # x0 y0 x1 y1 id seq_no(7bit)|last(1bit) floor
+      'target-area' => 'S>S>S>S>CCC',
+      # speed_factor reverse_speed_factor direction(radians) points_count (x y)*points_count floor
+      'terrain' => 'EEES>:points:C',
+      # x0 y0 x1 y1 id floor
+      'measure-line' => 'S>S>S>S>CC',
+      # x0 y0 x1 y1 id floor
+      'measure-area' => 'S>S>S>S>CC',
 
       'width' => 'S>', 'height' => 'S>',
       'scale' => 'E',
       'file_name' => 'S>A:len:'
     }
 
-    field name: 'file', type: :custom, parser: :read_scene_file
+    # single svg file or list of files, one for every floor starting from the ground one
+    field name: 'file', type: :custom, parser: :read_scene_files
     field name: 'scale', type: :float
 
     def to_config
-      file_name = get_data('scene_file')
-      file_name_template = SCENE_ELEMENTS_TEMPLATES['file_name'].sub(':len:', file_name.size.to_s)
-      config = [SCENE_SECTION, SCENE_ELEMENTS['file_name'], file_name.size, file_name].pack(CONFIG_ITEM_TEMPLATE_PREFIX + file_name_template)
+      config = get_data('scene_files').inject('') do |files_conf, file_name|
+        file_name_template = SCENE_ELEMENTS_TEMPLATES['file_name'].sub(':len:', file_name.size.to_s)
+        files_conf + [SCENE_SECTION, SCENE_ELEMENTS['file_name'], file_name.size, file_name].pack(CONFIG_ITEM_TEMPLATE_PREFIX + file_name_template)
+      end
 
       config += get_data('geometry').inject("") do |geom_conf, (geom_el_type, geom_el_data)|
         data = [SCENE_SECTION, SCENE_ELEMENTS[geom_el_type], geom_el_data].flatten
-        geom_conf + data.pack(CONFIG_ITEM_TEMPLATE_PREFIX + SCENE_ELEMENTS_TEMPLATES[geom_el_type])
+        template = SCENE_ELEMENTS_TEMPLATES[geom_el_type]
+        template = template.sub(':points:', 'S>S>' * geom_el_data[3]) if geom_el_type == 'terrain'
+        geom_conf + data.pack(CONFIG_ITEM_TEMPLATE_PREFIX + template)
       end
       config += [SCENE_SECTION, SCENE_ELEMENTS['scale'], get_data('scale')].pack(CONFIG_ITEM_TEMPLATE_PREFIX + SCENE_ELEMENTS_TEMPLATES['scale'])
       config += super.to_s
       config
     end
 
-    def read_scene_file(file, &blc)
-      data['scene_file'] = file
+    # coordinates of scene element marked with x-csim-name attribute
+    def named_element(name)
+      find_named_element(name)[:coords]
+    end
+
+    def named_element_floor(name)
+      find_named_element(name)[:floor]
+    end
+
+    def read_scene_files(files, &blc)
+      data['scene_files'] = [files].flatten
+      data['geometry'] = []
+      data['named_elements'] = {}
+      data['target_areas'] = []
+      data['scene_files'].each_with_index{ |file, floor| read_scene_file(file, floor) }
+      check_alternatives(data['target_areas'])
+
+      # all floors share one coordinate system
+      width = data['geometry'].select{ |(geom_el_type, _)| geom_el_type == 'width' }.map(&:last).max
+      height = data['geometry'].select{ |(geom_el_type, _)| geom_el_type == 'height' }.map(&:last).max
+      data['geometry'].reject!{ |(geom_el_type, _)| %w[width height].include?(geom_el_type) }
+      data['geometry'].unshift(['width', width], ['height', height])
+    end
 
+    def read_scene_file(file, floor)
       scene_data = Crack::XML.parse(File.read(file))['svg']
       geometry = []
       geometry << ['width', scene_data['width'].to_i]
       geometry << ['height', scene_data['height'].to_i]
 
-      scene_data['line'].select{ |line| line['x_csim_class'] == 'wall' }.each do |wall|
+      svg_elements(scene_data, 'line').select{ |line| line['x_csim_class'] == 'wall' }.each do |wall|
         geometry << [
           'wall',
           [wall['x1'], wall['y1'], wall['x2'], wall['y2']].map(&:to_i)
         ]
       end
-      scene_data['rect'].select{ |rect| rect['x_csim_class'] == 'spawn-area' }.each do |spawn|
+      svg_elements(scene_data, 'rect').select{ |rect| rect['x_csim_class'] == 'spawn-area' }.each do |spawn|
         geometry << [
           'spawn-area',
           [spawn['x'].to_i, spawn['y'].to_i,
@@ -62,7 +95,7 @@ module Sections
            spawn['x_csim_id'].to_i]
         ]
       end
-      scene_data['rect'].select{ |rect| rect['x_csim_class'] == 'target-area' }.each do |target|
+      svg_elements(scene_data, 'rect').select{ |rect| rect['x_csim_class'] == 'target-area' }.each do |target|
         geometry << [
           'target-area',
           [target['x'].to_i, target['y'].to_i,
@@ -70,8 +103,79 @@ module Sections
            target['x_csim_id'].to_i,
            (target['x_csim_seq_no'].to_i << 1) | (target['x_csim_last'] == 'true' ? 1 : 0)]
         ]
+        data['target_areas'] << [target['x_csim_id'].to_i, target['x_csim_seq_no'].to_i, target['x_csim_alternative'] == 'true']
+      end
+      svg_elements(scene_data, 'rect').select{ |rect| rect['x_csim_class'] == 'terrain' }.each do |terrain|
+        x0, y0 = terrain['x'].to_i, terrain['y'].to_i
+        x1, y1 = x0 + terrain['width'].to_i, y0 + terrain['height'].to_i
+        geometry << ['terrain', terrain_params(terrain) + [4, x0, y0, x0, y1, x1, y1, x1, y0]]
+      end
+      svg_elements(scene_data, 'polygon').select{ |polygon| polygon['x_csim_class'] == 'terrain' }.each do |terrain|
+        points = terrain['points'].split(/[\s,]+/).map{ |coord| coord.to_f.to_i }
+        geometry << ['terrain', terrain_params(terrain) + [points.size / 2] + points]
+      end
+      # crossings are counted in both directions, forward one goes from left to right looking from x1 y1 to x2 y2
+      svg_elements(scene_data, 'line').select{ |line| line['x_csim_class'] == 'measure-line' }.each do |line|
+        geometry << [
+          'measure-line',
+          [line['x1'], line['y1'], line['x2'], line['y2']].map(&:to_i) + [line['x_csim_id'].to_i]
+        ]
+      end
+      svg_elements(scene_data, 'rect').select{ |rect| rect['x_csim_class'] == 'measure-area' }.each do |area|
+        geometry << [
+          'measure-area',
+          [area['x'].to_i, area['y'].to_i,
+           area['x'].to_i + area['width'].to_i, area['y'].to_i + area['height'].to_i,
+           area['x_csim_id'].to_i]
+        ]
+      end
+      geometry.each do |(geom_el_type, geom_el_data)|
+        geom_el_data << floor unless %w[width height].include?(geom_el_type)
+      end
+      data['geometry'] += geometry
+
+      svg_elements(scene_data, 'line').select{ |line| line['x_csim_name'] }.each do |line|
+        data['named_elements'][line['x_csim_name']] =
+          {floor: floor, coords: [line['x1'], line['y1'], line['x2'], line['y2']].map(&:to_i)}
+      end
+      svg_elements(scene_data, 'rect').select{ |rect| rect['x_csim_name'] }.each do |rect|
+        data['named_elements'][rect['x_csim_name']] =
+          {floor: floor, coords: [rect['x'].to_i, rect['y'].to_i, rect['x'].to_i + rect['width'].to_i, rect['y'].to_i + rect['height'].to_i]}
       end
-      data['geometry'] = geometry
     end
+
+    private
+
+      def find_named_element(name)
+        data['named_elements'][name.to_s] || raise("Unknown scene element name: #{name}")
+      end
+
+      # target areas of a path sharing sequence number are alternatives of one target,
+      # unmarked ones are most likely a numbering mistake
+      def check_alternatives(target_areas)
+        target_areas.group_by{ |(id, seq_no, _)| [id, seq_no] }.each do |(id, seq_no), areas|
+          next if areas.size == 1 || areas.all?{ |(_, _, alternative)| alternative }
+          raise ArgumentError, "Target areas of path #{id} share sequence number #{seq_no}, " \
+                               "mark them with x-csim-alternative=\"true\" if they are alternatives"
+        end
+      end
+
+      # crack gives a hash for single element and nothing for missing ones
+      def svg_elements(scene_data, tag)
+        [scene_data[tag]].flatten.compact
+      end
+
+      # x-csim-speed-factor scales desired speed inside zone, x-csim-reverse-speed-factor is used
+      # when moving against x-csim-direction (degrees, 270 - up the scene)
+      def terrain_params(terrain)
+        unless terrain['x_csim_speed_factor']
+          element = terrain['x_csim_name'] || terrain['id'] || terrain['points'] || [terrain['x'], terrain['y']].join(',')
+          raise ArgumentError, "Terrain zone #{element} has no x-csim-speed-factor"
+        end
+        speed_factor = terrain['x_csim_speed_factor'].to_f
+        reverse_speed_factor = (terrain['x_csim_reverse_speed_factor'] || speed_factor).to_f
+        direction = terrain['x_csim_direction'].to_f * Math::PI / 180
+        [speed_factor, reverse_speed_factor, direction]
+      end
   end
 end
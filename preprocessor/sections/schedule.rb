@@ -0,0 +1,68 @@
+require_relative 'base'
+
+module Sections
+  class Schedule < Base
+    SCHEDULE_SECTION = 0x08
+    SCHEDULE_ELEMENTS = {
+      'add_wall' => 0x01, 'remove_wall' => 0x02,
+      'enable_target' => 0x03, 'disable_target' => 0x04,
+      'spawn_rate' => 0x05, 'termination' => 0x06
+    }
+    SCHEDULE_ELEMENTS_TEMPLATES = {
+      # time x0 y0 x1 y1 floor
+      'add_wall' => 'ES>S>S>S>C', 'remove_wall' => 'ES>S>S>S>C',
+      'enable_target' => 'ES>S>S>S>C', 'disable_target' => 'ES>S>S>S>C',
+      # time spawn_id(FF - all spawn areas) rate
+      'spawn_rate' => 'ECE',
+      # time sim_type
+      'termination' => 'EC'
+    }
+    SIM_TYPES = {'flow' => 0x01, 'escape' => 0x02}
+
+    # scene elements are given either by x-csim-name from svg file or by coords [x0, y0, x1, y1] and floor
+    def add_wall(at:, name: nil, coords: nil, floor: 0)
+      add_event('add_wall', at, name: name, coords: coords, floor: floor)
+    end
+
+    def remove_wall(at:, name: nil, coords: nil, floor: 0)
+      add_event('remove_wall', at, name: name, coords: coords, floor: floor)
+    end
+
+    def enable_target(at:, name: nil, coords: nil, floor: 0)
+      add_event('enable_target', at, name: name, coords: coords, floor: floor)
+    end
+
+    def disable_target(at:, name: nil, coords: nil, floor: 0)
+      add_event('disable_target', at, name: name, coords: coords, floor: floor)
+    end
+
+    def spawn_rate(at:, rate:, spawn_id: 0xFF)
+      add_event('spawn_rate', at, values: [spawn_id.to_i, rate.to_f])
+    end
+
+    def termination(at:, type:)
+      sim_type = SIM_TYPES[type.to_s] || raise("Unknown termination type #{type}, expected one of #{SIM_TYPES.keys.inspect}")
+      add_event('termination', at, values: [sim_type])
+    end
+
+    def to_config
+      (data['events'] || []).inject('') do |config, event|
+        values = event[:values] || scene_coords(event)
+        config + [SCHEDULE_SECTION, SCHEDULE_ELEMENTS[event[:type]], event[:time], *values].pack(CONFIG_ITEM_TEMPLATE_PREFIX + SCHEDULE_ELEMENTS_TEMPLATES[event[:type]])
+      end
+    end
+
+    private
+
+      def add_event(type, time, name: nil, coords: nil, floor: 0, values: nil)
+        (data['events'] ||= []) << {type: type, time: time.to_f, name: name, coords: coords, floor: floor, values: values}
+      end
+
+      def scene_coords(event)
+        return event[:coords].map(&:to_i) + [event[:floor].to_i] if event[:coords]
+        raise "No scene element given for #{event[:type]} event at #{event[:time]}" unless event[:name]
+        scene = parent.data['scene']
+        scene.named_element(event[:name]) + [scene.named_element_floor(event[:name])]
+      end
+  end
+end
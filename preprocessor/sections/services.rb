@@ -0,0 +1,72 @@
+require_relative 'base'
+
+module Sections
+  class Services < Base
+    SERVICES_SECTION = 0x0A
+    SERVICES_ELEMENTS = {
+      'name' => 0x01, 'area' => 0x02, 'servers' => 0x03,
+      'queue_direction' => 0x04, 'dwell' => 0x05
+    }
+    SERVICES_ELEMENTS_TEMPLATES = {
+      # service_id name
+      'name' => 'CS>A:len:',
+      # service_id floor x0 y0 x1 y1
+      'area' => 'CCS>S>S>S>',
+      # service_id servers
+      'servers' => 'CS>',
+      # service_id direction(radians)
+      'queue_direction' => 'CE'
+    }
+
+    field name: 'service', type: :custom, parser: :add_service
+
+    def add_service(name, &blc)
+      services = data['service'] || []
+      raise "Service #{name} is described twice" if services.any?{ |service| service.name == name }
+      services << Service.new(self, name, services.size, &blc)
+    end
+
+    def to_config
+      (data['service'] || []).inject('') do |res, service|
+        res + service.to_config
+      end
+    end
+  end
+
+  # service point at target area with given x-csim-name, people queue in front of it for a free server
+  class Service < Base
+    attr_reader :name, :id
+
+    field name: 'servers', type: :int, default: 1
+    # direction queue grows from service point (degrees, 90 - down the scene)
+    field name: 'queue_direction', type: :float, default: 90.0
+    field name: 'dwell', type: :distribution, current_section: Services::SERVICES_SECTION,
+          element: Services::SERVICES_ELEMENTS['dwell']
+
+    def initialize(parent, name, id, &blc)
+      @name, @id = name.to_s, id
+      super(parent, &blc)
+    end
+
+    def distribution_ids
+      [id]
+    end
+
+    def to_config
+      scene = parent.parent.data['scene']
+      values = {
+        'area' => [scene.named_element_floor(name)] + scene.named_element(name),
+        'servers' => [get_data('servers')],
+        'queue_direction' => [get_data('queue_direction') * Math::PI / 180]
+      }
+
+      name_template = Services::SERVICES_ELEMENTS_TEMPLATES['name'].sub(':len:', name.size.to_s)
+      config = [Services::SERVICES_SECTION, Services::SERVICES_ELEMENTS['name'], id, name.size, name].pack(CONFIG_ITEM_TEMPLATE_PREFIX + name_template)
+      config += values.inject('') do |res, (element, element_values)|
+        res + [Services::SERVICES_SECTION, Services::SERVICES_ELEMENTS[element], id, *element_values].pack(CONFIG_ITEM_TEMPLATE_PREFIX + Services::SERVICES_ELEMENTS_TEMPLATES[element])
+      end
+      config += super.to_s
+      config
+    end
+  end
+end
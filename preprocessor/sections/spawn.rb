@@ -5,10 +5,18 @@ module Sections
     SPAWN_SECTION = 0x03
     SPAWN_ELEMENTS = {
       'rate' => 0x01,
-      'time' => 0x02
+      'time' => 0x02,
+      'mix' => 0x03,
+      'population' => 0x04,
+      'initial_population' => 0x05
     }
     SPAWN_ELEMENTS_TEMPLATES = {
-      'rate' => 'E'
+      'rate' => 'E',
+      # spawn_id profile_id ratio
+      'mix' => 'CCE',
+      'population' => 'S>A:len:',
+      # spawn_id kind value
+      'initial_population' => 'CCE'
     }
 
     field name: 'rate', type: :float
@@ -16,9 +24,40 @@ module Sections
           element: SPAWN_ELEMENTS['time'],
           context_defaults: {'from' => 0.0, 'to' => 'ref:time.end_time'}
 
+    # csv file with people present at start, header names columns:
+    # x, y, path (required), floor, heading, target_index, profile, radius, target_speed, repulsion_coeff, forward_fov, backward_fov,
+    # noise_level; x and y are in meters like in trajectory file
+    field name: 'population', type: :string
+
+    # profile mix for spawn area, e.g. mix 0, 'adult' => 0.7, 'child' => 0.3
+    def mix(spawn_id, ratios)
+      (data['mix'] ||= []) << [spawn_id.to_i, ratios]
+    end
+
+    # people filling spawn area at escape start, e.g. initial_population 0, density: 2.0 (persons per m2) or count: 50
+    def initial_population(spawn_id, count: nil, density: nil)
+      raise 'Initial population needs either count or density' unless count.nil? ^ density.nil?
+      kind, value = count ? [0x01, count] : [0x02, density]
+      (data['initial_population'] ||= []) << [spawn_id.to_i, kind, value.to_f]
+    end
+
     def to_config
       config = ""
       config += [SPAWN_SECTION, SPAWN_ELEMENTS['rate'], get_data('rate')].pack(CONFIG_ITEM_TEMPLATE_PREFIX + SPAWN_ELEMENTS_TEMPLATES['rate'])
+      (data['mix'] || []).each do |spawn_id, ratios|
+        profiles = parent.data['profiles'] || raise('Profile mix given, but no profiles described')
+        ratios.each do |profile_name, ratio|
+          config += [SPAWN_SECTION, SPAWN_ELEMENTS['mix'], spawn_id, profiles.profile_id(profile_name), ratio.to_f].pack(CONFIG_ITEM_TEMPLATE_PREFIX + SPAWN_ELEMENTS_TEMPLATES['mix'])
+        end
+      end
+      (data['initial_population'] || []).each do |values|
+        config += [SPAWN_SECTION, SPAWN_ELEMENTS['initial_population'], *values].pack(CONFIG_ITEM_TEMPLATE_PREFIX + SPAWN_ELEMENTS_TEMPLATES['initial_population'])
+      end
+      if data['population']
+        population = data['population']
+        population_template = SPAWN_ELEMENTS_TEMPLATES['population'].sub(':len:', population.size.to_s)
+        config += [SPAWN_SECTION, SPAWN_ELEMENTS['population'], population.size, population].pack(CONFIG_ITEM_TEMPLATE_PREFIX + population_template)
+      end
       config += super.to_s
       config
     end
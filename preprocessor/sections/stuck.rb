@@ -0,0 +1,29 @@
+require_relative 'base'
+
+module Sections
+  class Stuck < Base
+    STUCK_SECTION = 0x0F
+    STUCK_ELEMENTS = {'enabled' => 0x01, 'window' => 0x02, 'min_progress' => 0x03,
+                      'strategy' => 0x04, 'perturbation' => 0x05}
+    STUCK_ELEMENTS_TEMPLATES = {'enabled' => 'C', 'window' => 'E', 'min_progress' => 'E',
+                                'strategy' => 'C', 'perturbation' => 'E'}
+
+    field name: 'enabled', type: :bool, default: true
+    # person who got less than min_progress meters closer to his target in window seconds is stuck
+    field name: 'window', type: :float, default: 5.0
+    field name: 'min_progress', type: :float, default: 0.5
+    # what happens to stuck person: only reported, moved a bit, sent to another alternative target or removed
+    field name: 'strategy', type: :enum, values: {'report' => 0x01, 'perturb' => 0x02, 'reroute' => 0x03, 'remove' => 0x04},
+          default: 'report'
+    # how far stuck person may be moved, meters
+    field name: 'perturbation', type: :float, default: 0.5
+
+    def to_config
+      STUCK_ELEMENTS.keys.inject('') do |config, element|
+        value = get_data(element)
+        value = value ? 1 : 0 if element == 'enabled'
+        config + [STUCK_SECTION, STUCK_ELEMENTS[element], value].pack(CONFIG_ITEM_TEMPLATE_PREFIX + STUCK_ELEMENTS_TEMPLATES[element])
+      end
+    end
+  end
+end
@@ -0,0 +1,29 @@
+require_relative 'base'
+
+module Sections
+  class Termination < Base
+    TERMINATION_SECTION = 0x10
+    TERMINATION_ELEMENTS = {'max_time' => 0x01, 'max_arrivals' => 0x02, 'exit_fraction' => 0x03,
+                            'wall_clock' => 0x04, 'steady_state_window' => 0x05, 'steady_state_tolerance' => 0x06}
+    TERMINATION_ELEMENTS_TEMPLATES = {'max_time' => 'E', 'max_arrivals' => 'L>', 'exit_fraction' => 'E',
+                                      'wall_clock' => 'E', 'steady_state_window' => 'E', 'steady_state_tolerance' => 'E'}
+
+    # simulated seconds, in any mode
+    field name: 'max_time', type: :float
+    # people who reached their last target
+    field name: 'max_arrivals', type: :int
+    # part of everyone spawned who reached their last target, from 0 to 1
+    field name: 'exit_fraction', type: :float
+    # real seconds the run may take
+    field name: 'wall_clock', type: :float
+    # flow rate is measured every window seconds, run stops once it stays within tolerance for a few windows
+    field name: 'steady_state_window', type: :float
+    field name: 'steady_state_tolerance', type: :float
+
+    def to_config
+      TERMINATION_ELEMENTS.keys.select{ |element| data.key?(element) }.inject('') do |config, element|
+        config + [TERMINATION_SECTION, TERMINATION_ELEMENTS[element], data[element]].pack(CONFIG_ITEM_TEMPLATE_PREFIX + TERMINATION_ELEMENTS_TEMPLATES[element])
+      end
+    end
+  end
+end
@@ -0,0 +1,32 @@
+require_relative 'base'
+
+module Sections
+  class Trajectory < Base
+    TRAJECTORY_SECTION = 0x11
+    TRAJECTORY_ELEMENTS = {'file' => 0x01, 'interval' => 0x02, 'floor_height' => 0x03, 'orientation' => 0x04, 'density' => 0x05}
+    TRAJECTORY_ELEMENTS_TEMPLATES = {'file' => 'S>A:len:', 'interval' => 'E', 'floor_height' => 'E', 'orientation' => 'C',
+                                     'density' => 'C'}
+
+    # every person's position is written there as `id frame x y` line in meters
+    field name: 'file', type: :string
+    # seconds between frames, every tick when omitted
+    field name: 'interval', type: :float
+    # meters between floors, z column is added when set
+    field name: 'floor_height', type: :float
+    # adds heading column in degrees
+    field name: 'orientation', type: :bool
+    # adds personal voronoi density column, people per square meter
+    field name: 'density', type: :bool
+
+    def to_config
+      TRAJECTORY_ELEMENTS.keys.select{ |element| data.key?(element) }.inject('') do |config, element|
+        value = data[element]
+        template = TRAJECTORY_ELEMENTS_TEMPLATES[element]
+        values = String === value ? [value.size, value] : [value]
+        template = template.sub(':len:', value.size.to_s) if String === value
+        values = [value ? 1 : 0] if %w[orientation density].include?(element)
+        config + [TRAJECTORY_SECTION, TRAJECTORY_ELEMENTS[element], *values].pack(CONFIG_ITEM_TEMPLATE_PREFIX + template)
+      end
+    end
+  end
+end
@@ -45,14 +45,16 @@ module Utils
       config = ""
       distribution = get_data('distribution')
       raise ArgumentError, "Unknow distribution: #{distribution}" unless %w[uniform normal time_infinite].include?(distribution)
-      distribution_template = DISTRIBUTION_TEMPLATES[distribution]
+      # owner ids (e.g. profile id) are packed between element and distribution
+      ids = parent.respond_to?(:distribution_ids) ? parent.distribution_ids : []
+      distribution_template = 'C' * ids.size + DISTRIBUTION_TEMPLATES[distribution]
       case distribution
       when 'uniform'
-        config += [current_section, element, DISTRIBUTION_TYPES[distribution], get_data('from'), get_data('to')].pack(CONFIG_ITEM_TEMPLATE_PREFIX + distribution_template)
+        config += [current_section, element, *ids, DISTRIBUTION_TYPES[distribution], get_data('from'), get_data('to')].pack(CONFIG_ITEM_TEMPLATE_PREFIX + distribution_template)
       when 'normal'
-        config += [current_section, element, DISTRIBUTION_TYPES[distribution], get_data('mean'), get_data('std_deviation')].pack(CONFIG_ITEM_TEMPLATE_PREFIX + distribution_template)
+        config += [current_section, element, *ids, DISTRIBUTION_TYPES[distribution], get_data('mean'), get_data('std_deviation')].pack(CONFIG_ITEM_TEMPLATE_PREFIX + distribution_template)
       when 'time_infinite'
-        config += [current_section, element, DISTRIBUTION_TYPES[distribution], get_data('avg_rate'), get_data('rate_deviation')].pack(CONFIG_ITEM_TEMPLATE_PREFIX + distribution_template)
+        config += [current_section, element, *ids, DISTRIBUTION_TYPES[distribution], get_data('avg_rate'), get_data('rate_deviation')].pack(CONFIG_ITEM_TEMPLATE_PREFIX + distribution_template)
       end
       config += super.to_s
       config
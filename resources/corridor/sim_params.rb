@@ -48,3 +48,17 @@ density_map do
   min_threshold 6.0
   max_threshold 15.0
 end
+
+# scripted scene events (optional)
+# walls and target areas are given by x-csim-name in scene file or by coords [x0, y0, x1, y1]
+# target areas of a path with the same x-csim-seq-no marked with x-csim-alternative="true" are alternatives,
+# person heads to the nearest enabled one
+# schedule do
+#   add_wall at: 60.0, name: 'fire-door'
+#   remove_wall at: 90.0, coords: [10, 300, 100, 300]
+#   disable_target at: 0.0, name: 'second-exit'
+#   enable_target at: 45.0, name: 'second-exit'
+#   spawn_rate at: 20.0, rate: 0.5
+#   spawn_rate at: 30.0, rate: 0.0, spawn_id: 0
+#   termination at: 120.0, type: 'escape'
+# end
@@ -1,3 +1,47 @@
+# interaction model: 'social_force' (default) or 'orca'
+# model 'orca'
+
+# file for spawns, target arrivals, exits and other events of every person, json per line (optional)
+# events_file 'events.jsonl'
+
+# file for per person trip summaries: spawn, target and exit times, distance and parameters, csv (optional)
+# trips_file 'trips.csv'
+
+# live demo pacing: 'batch' (default) runs as fast as possible, 'real_time' follows wall clock sped up by speed (optional)
+# run_mode 'real_time'
+# speed 10.0
+
+# velocity obstacles settings, used by 'orca' model only (optional)
+# orca do
+#   # how far in future collisions with other people are avoided, seconds
+#   time_horizon 2.0
+#   # same for walls
+#   obstacle_time_horizon 1.0
+#   # other people farther than this are ignored, meters
+#   neighbor_distance 5.0
+# end
+
+# how people turn and speed up, with either interaction model (optional)
+# kinematics do
+#   # degrees per second
+#   turn_rate{ distribution 'normal'; mean 100.0; std_deviation 10.0 }
+#   # meters per second
+#   max_speed{ distribution 'normal'; mean 2.0; std_deviation 0.2 }
+#   # meters per second squared
+#   max_acceleration{ distribution 'uniform'; from 1.5; to 2.5 }
+#   # 'walking' (default) or 'body', body turns separately so people may side-step in dense crowd
+#   heading_model 'body'
+# end
+
+# people who make no progress towards their targets, escape run doesn't end while they stay (optional)
+# stuck do
+#   window 5.0
+#   min_progress 0.5
+#   # 'report' (default), 'perturb', 'reroute' or 'remove'
+#   strategy 'perturb'
+#   perturbation 0.5
+# end
+
 # scene description
 scene do
   # svg file with scene geometry
@@ -33,7 +77,18 @@ forces do
   repulsion do
     # force coeff distribution
     coeff{ distribution 'normal'; mean 2.0; std_deviation 0.1 }
+    # every force can be weighted or switched off, repulsion shape can be tuned
+    # weight 1.0
+    # cap 4.0
   end
+  # random force that breaks symmetric deadlocks
+  # fluctuation do
+  #   enabled false
+  #   magnitude 0.1
+  #   mode 'ornstein_uhlenbeck'
+  #   correlation_time 2.0
+  #   level{ distribution 'uniform'; from 0.5; to 1.5 }
+  # end
 end
 
 # field of view description
@@ -47,4 +102,7 @@ density_map do
   enabled true
   min_threshold 5.0
   max_threshold 10.0
+  # people per square meter from voronoi cells instead of kernel sum, thresholds are then like 0.5 and 4.0 (optional)
+  # method 'voronoi'
+  # voronoi_cutoff 2.0
 end
@@ -20,6 +20,9 @@ spawn do
   time{ distribution 'uniform' }
   # rate of spawns (men in second)
   rate 1.0
+  # profile mix ratios per spawn area id
+  mix 0, 'adult' => 0.7, 'elderly' => 0.1, 'luggage' => 0.2
+  mix 1, 'adult' => 0.8, 'elderly' => 0.2
 end
 
 # forces description
@@ -48,3 +51,33 @@ density_map do
   min_threshold 4.0
   max_threshold 10.0
 end
+
+# agent profiles description (optional, one 'adult' profile with 0.4 m radius if omitted)
+profiles do
+  # settings that are not given fall back to forces/fov descriptions
+  profile 'adult' do
+    # body radius distribution, meters
+    radius{ distribution 'normal'; mean 0.4; std_deviation 0.02 }
+  end
+  profile 'elderly' do
+    radius{ distribution 'normal'; mean 0.4; std_deviation 0.02 }
+    speed{ distribution 'normal'; mean 0.9; std_deviation 0.2 }
+  end
+  profile 'luggage' do
+    radius{ distribution 'normal'; mean 0.55; std_deviation 0.05 }
+    speed{ distribution 'normal'; mean 1.2; std_deviation 0.2 }
+    repulsion{ distribution 'normal'; mean 1.2; std_deviation 0.1 }
+  end
+end
+
+# service points (optional), target areas marked with x-csim-name in svg file where people are served
+# services do
+#   service 'turnstiles' do
+#     # people served at the same time
+#     servers 3
+#     # direction queue grows from service point, degrees (90 - down the scene)
+#     queue_direction 90
+#     # service time distribution, seconds
+#     dwell{ distribution 'normal'; mean 2.0; std_deviation 0.5 }
+#   end
+# end
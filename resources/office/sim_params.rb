@@ -1,9 +1,55 @@
 type 'escape'
 
+# fixed seed makes runs repeatable (optional)
+# seed 42
+
+# save mid-evacuation state once and branch what-if runs from it (optional)
+# checkpoint do
+#   save_file 'office.checkpoint'
+#   save_time 60.0
+#   # restore_file 'office.checkpoint'
+# end
+
+# extra reasons to stop, the first one met wins (optional)
+# termination do
+#   # simulated seconds
+#   max_time 600.0
+#   # stop once 95% of people are out, stragglers are left on scene
+#   exit_fraction 0.95
+#   # real seconds
+#   wall_clock 300.0
+# end
+
+# positions of everyone for comparison with experiment data (optional)
+# trajectory do
+#   file 'trajectory.txt'
+#   # seconds between frames
+#   interval 0.1
+#   # z column, meters between floors
+#   floor_height 3.5
+#   orientation true
+#   # personal voronoi density
+#   density true
+# end
+
+# counts for fundamental diagram, svg lines with x-csim-class="measure-line" and rects with
+# x-csim-class="measure-area" are measured, both need x-csim-id (optional)
+# measurement do
+#   # seconds
+#   bin 10.0
+#   # crossings of every line, density and speed in every area per bin
+#   file 'measurements.csv'
+#   # density with speed and flow of every area per bin
+#   diagram_file 'diagram.csv'
+# end
+
 # scene description
 scene do
   # svg file with scene geometry
   file '/home/azhi/develop/crowd-sim/resources/office/scene.svg'
+  # multi-level building is described by list of files, one for every floor
+  # file ['/home/azhi/develop/crowd-sim/resources/office/scene.svg',
+  #       '/home/azhi/develop/crowd-sim/resources/office/scene_floor1.svg']
   # file scale (meters per pixel)
   scale 0.1
 end
@@ -22,6 +68,10 @@ spawn do
   time{ distribution 'uniform' }
   # rate of spawns (men in second)
   rate 1.0
+  # people present at start instead of initial spawn, e.g. from observations, positions in meters (optional)
+  # population 'population.csv'
+  # spawn areas filled at start by head count or density in persons per m2 (optional)
+  # initial_population 0, density: 2.0
 end
 
 # forces description
@@ -50,3 +100,24 @@ density_map do
   min_threshold 4.0
   max_threshold 10.0
 end
+
+# connectors between floors, areas are referenced by x-csim-name from svg files
+# connectors do
+#   stairs from: 'stairs-up', to: 'stairs-down', time: 15.0, capacity: 10
+#   lift from: 'lift-floor1', to: 'lift-floor0', time: 8.0, capacity: 6
+# end
+
+# smoke spreading over the scene (optional)
+# hazard do
+#   # grid cell size, meters
+#   cell_size 0.5
+#   # share of concentration difference spreading to neighbour cell per second
+#   diffusion 0.5
+#   # speed and field of view are reduced by this share per unit of concentration
+#   speed_reduction 0.3
+#   fov_reduction 0.5
+#   # force pushing people away from smoke
+#   repulsion 2.0
+#   # smoke source at scene point (pixels) starting at given time, concentration units per second
+#   source at: [200, 150], start: 5.0, rate: 20.0
+# end